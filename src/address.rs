@@ -0,0 +1,177 @@
+// src/address.rs
+use super::error::BlockchainError; // error 모듈의 오류 유형 사용
+use bech32::primitives::decode::CheckedHrpstring;
+use bech32::{Bech32m, Hrp};
+use ripemd::Ripemd160;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// 공개 키 해시의 길이 (바이트)
+pub const PUBKEY_HASH_SIZE: usize = 20;
+
+/// 주소가 속한 네트워크입니다. 주소 접두사(HRP)와 트랜잭션의 네트워크 식별자(chain_id)를 정합니다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Regtest,
+}
+
+impl Network {
+    /// 주소 앞에 붙는 사람이 읽을 수 있는 접두사 (Bech32 HRP)
+    pub const fn hrp(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "bg",
+            Network::Testnet => "tbg",
+            Network::Regtest => "bgrt",
+        }
+    }
+
+    /// 이 네트워크의 트랜잭션이 사용하는 네트워크 식별자
+    pub const fn chain_id(&self) -> u64 {
+        match self {
+            Network::Mainnet => 1,
+            Network::Testnet => 2,
+            Network::Regtest => 3,
+        }
+    }
+
+    /// 네트워크 식별자에 해당하는 네트워크를 찾습니다.
+    pub fn from_chain_id(chain_id: u64) -> Option<Self> {
+        [Network::Mainnet, Network::Testnet, Network::Regtest].into_iter()
+            .find(|network| network.chain_id() == chain_id)
+    }
+
+    // 주소 접두사에 해당하는 네트워크를 찾습니다.
+    fn from_hrp(hrp: &str) -> Option<Self> {
+        [Network::Mainnet, Network::Testnet, Network::Regtest].into_iter()
+            .find(|network| network.hrp() == hrp)
+    }
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    /// 네트워크 이름(mainnet, testnet, regtest)을 해석합니다.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            "regtest" => Ok(Network::Regtest),
+            _ => Err(format!("unknown network: {}", name)),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Network::Mainnet => write!(f, "mainnet"),
+            Network::Testnet => write!(f, "testnet"),
+            Network::Regtest => write!(f, "regtest"),
+        }
+    }
+}
+
+/// 공개 키 해시(HASH160 = RIPEMD-160(SHA-256(압축 공개 키)))와 네트워크로 이루어진 주소입니다.
+///
+/// 문자열로는 네트워크 접두사와 Bech32m 체크섬을 붙여 `bg1...` 형태로 표현하므로,
+/// 한두 글자를 잘못 입력한 주소는 파싱 단계에서 거부됩니다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address {
+    network: Network,
+    pubkey_hash: [u8; PUBKEY_HASH_SIZE],
+}
+
+impl Address {
+    /// 공개 키(SEC1 인코딩 바이트)로부터 주소를 만듭니다.
+    pub fn from_public_key(public_key: &[u8], network: Network) -> Self {
+        Address { network, pubkey_hash: hash160(public_key) }
+    }
+
+    /// 주소 문자열을 해석하고 주어진 네트워크의 주소인지 확인합니다.
+    pub fn parse_for_network(address: &str, network: Network) -> Result<Self, BlockchainError> {
+        let parsed: Address = address.parse()?;
+        if parsed.network != network {
+            return Err(BlockchainError::WrongNetwork { address: address.to_string(), chain_id: network.chain_id() });
+        }
+        Ok(parsed)
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn pubkey_hash(&self) -> &[u8; PUBKEY_HASH_SIZE] {
+        &self.pubkey_hash
+    }
+
+    /// 공개 키가 이 주소의 해시와 일치하는지 확인합니다.
+    pub fn matches_public_key(&self, public_key: &[u8]) -> bool {
+        hash160(public_key) == self.pubkey_hash
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hrp = Hrp::parse_unchecked(self.network.hrp());
+        bech32::encode_lower_to_fmt::<Bech32m, _>(f, hrp, &self.pubkey_hash).map_err(|_| fmt::Error)
+    }
+}
+
+impl FromStr for Address {
+    type Err = BlockchainError;
+
+    /// 접두사, 문자 집합, Bech32m 체크섬, 해시 길이를 모두 확인합니다.
+    /// Bech32m은 전부 대문자인 표현도 허용하지만, 잔액과 기록을 주소 문자열로 색인하므로
+    /// 같은 주소가 두 문자열로 나뉘지 않도록 정규 표현(소문자)만 받습니다.
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| BlockchainError::InvalidAddress { address: address.to_string(), reason };
+        if address.bytes().any(|byte| byte.is_ascii_uppercase()) {
+            return Err(invalid("address must be lowercase".to_string()));
+        }
+        let checked = CheckedHrpstring::new::<Bech32m>(address).map_err(|e| invalid(e.to_string()))?;
+        let network = Network::from_hrp(checked.hrp().as_str())
+            .ok_or_else(|| invalid(format!("unknown prefix {}", checked.hrp())))?;
+        let data: Vec<u8> = checked.byte_iter().collect();
+        let pubkey_hash: [u8; PUBKEY_HASH_SIZE] = data.try_into()
+            .map_err(|data: Vec<u8>| invalid(format!("expected {} byte hash, got {}", PUBKEY_HASH_SIZE, data.len())))?;
+        Ok(Address { network, pubkey_hash })
+    }
+}
+
+// RIPEMD-160(SHA-256(data))
+fn hash160(data: &[u8]) -> [u8; PUBKEY_HASH_SIZE] {
+    Ripemd160::digest(Sha256::digest(data)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_round_trips_through_its_string_form() {
+        let address = Address::from_public_key(&[2; 33], Network::Testnet);
+        let text = address.to_string();
+        assert!(text.starts_with("tbg1"));
+        assert_eq!(Address::parse_for_network(&text, Network::Testnet).unwrap(), address);
+        assert!(matches!(
+            Address::parse_for_network(&text, Network::Mainnet),
+            Err(BlockchainError::WrongNetwork { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_uppercase_and_corrupted_addresses() {
+        let text = Address::from_public_key(&[2; 33], Network::Mainnet).to_string();
+        // 체크섬은 맞지만 정규 표현이 아닌 대문자 주소
+        assert!(matches!(text.to_uppercase().parse::<Address>(), Err(BlockchainError::InvalidAddress { .. })));
+        let mut corrupted = text.clone().into_bytes();
+        let last = corrupted.len() - 1;
+        corrupted[last] = if corrupted[last] == b'q' { b'p' } else { b'q' };
+        assert!(String::from_utf8(corrupted).unwrap().parse::<Address>().is_err());
+    }
+}
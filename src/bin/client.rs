@@ -1,49 +1,57 @@
 // src/bin/client.rs
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
-use serde::{Serialize, Deserialize};
-use serde_json;
 use std::io::{self, BufRead, Write};
-use std::collections::HashMap;
+use std::path::PathBuf;
 
 // 라이브러리 크레이트에서 모듈들을 가져옵니다.
 // `bingry_blockchain_lib`는 Cargo.toml에 정의된 라이브러리 이름입니다.
-use bingry_blockchain_lib::{blockchain, transaction, wallet}; // server는 client에서 직접 사용하지 않으므로 제거
+use bingry_blockchain_lib::{address, hd_wallet, keystore, node_client, protocol, transaction, wallet};
 
-use blockchain::Blockchain;
-use transaction::Transaction;
+use address::{Address, Network};
+use hd_wallet::{HdWallet, DEFAULT_GAP_LIMIT};
+use keystore::Keystore;
+use node_client::NodeClient;
+use protocol::{Request, Response, MAX_BLOCKS_PER_PAGE};
+use transaction::{Transaction, TxOutput};
 use wallet::Wallet;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub enum Request {
-    AddTransaction(Transaction),
-    MineBlock(String), // miner_address
-    GetBalance(String), // address
-    GetChain,
-    GenerateWallet,
-    // 필요에 따라 다른 요청 추가
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub enum Response {
-    Success(String),
-    Blockchain(Blockchain),
-    Balance(u64),
-    Wallet(Wallet),
-    Error(String),
-    // 필요에 따라 다른 응답 추가
-}
+// 키 저장소 파일 기본 경로 (--keystore <경로>로 변경)
+const DEFAULT_KEYSTORE_PATH: &str = "keystore.json";
+// 접속할 노드 기본 주소 (--node <주소>로 변경)
+const DEFAULT_NODE_ADDR: &str = "127.0.0.1:8080";
 
 #[tokio::main]
 pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut stream = TcpStream::connect("127.0.0.1:8080").await?;
-    println!("서버에 연결되었습니다.");
+    let mut keystore_path = PathBuf::from(DEFAULT_KEYSTORE_PATH);
+    let mut node_addr = DEFAULT_NODE_ADDR.to_string();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--keystore" => match args.next() {
+                Some(path) => keystore_path = PathBuf::from(path),
+                None => return Err("--keystore requires a path".into()),
+            },
+            "--node" => match args.next() {
+                Some(addr) => node_addr = addr,
+                None => return Err("--node requires an address".into()),
+            },
+            other => return Err(format!("unknown argument: {}", other).into()),
+        }
+    }
+
+    // 개인 키는 로컬 키 저장소에 암호화되어 보관되며, 노드로는 서명된 트랜잭션만 보냅니다.
+    let mut keystore = Keystore::load(&keystore_path)?;
+    println!("키 저장소 {}에서 계정 {}개를 불러왔습니다.", keystore_path.display(), keystore.accounts.len());
+
+    let mut client = NodeClient::connect(&node_addr).await?;
+    // 새 주소와 입력받은 주소는 노드의 네트워크에 맞춥니다.
+    let chain_id = client.get_chain_id().await?;
+    let network = Network::from_chain_id(chain_id)
+        .ok_or_else(|| format!("node uses unknown chain id {}", chain_id))?;
+    println!("서버 {}에 연결되었습니다. (네트워크: {})", node_addr, network);
 
     let stdin = io::stdin();
     let mut reader = io::BufReader::new(stdin.lock());
 
-    let mut wallets: HashMap<String, Wallet> = HashMap::new();
-
     loop {
         println!("\n--- 메뉴 ---");
         println!("1. 지갑 생성");
@@ -51,7 +59,10 @@ pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("3. 블록 채굴");
         println!("4. 잔액 조회");
         println!("5. 블록체인 전체 조회");
-        println!("6. 종료");
+        println!("6. 계정 목록");
+        println!("7. 시드 문구로 지갑 생성");
+        println!("8. 시드 문구로 지갑 복원");
+        println!("9. 종료");
         print!("선택: ");
         io::stdout().flush()?;
 
@@ -61,45 +72,66 @@ pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         match choice {
             "1" => {
-                let request = Request::GenerateWallet;
-                let response = send_request(&mut stream, request).await?;
-                if let Response::Wallet(wallet) = response {
-                    println!("새 지갑이 생성되었습니다: {}", wallet.get_address());
-                    wallets.insert(wallet.get_address().to_string(), wallet);
-                } else {
-                    println!("오류: {:?}", response);
+                println!("--- 지갑 생성 ---");
+                let name = read_input(&mut reader, "계정 이름 입력: ")?;
+                if name.is_empty() {
+                    println!("오류: 계정 이름이 비어 있습니다.");
+                    continue;
+                }
+                if keystore.find(&name).is_some() {
+                    println!("오류: 이미 있는 계정 이름입니다.");
+                    continue;
+                }
+                let password = read_input(&mut reader, "비밀번호 입력: ")?;
+                let confirmation = read_input(&mut reader, "비밀번호 확인: ")?;
+                if password != confirmation {
+                    println!("오류: 비밀번호가 일치하지 않습니다.");
+                    continue;
+                }
+
+                // 키 쌍은 클라이언트에서 생성하며 개인 키는 노드로 보내지 않습니다.
+                let wallet = Wallet::new_for_network(network);
+                if let Err(e) = keystore.add_account(&name, &wallet, &password) {
+                    println!("오류: {}", e);
+                    continue;
+                }
+                match keystore.save(&keystore_path) {
+                    Ok(()) => println!("새 지갑이 생성되었습니다: {} ({})", name, wallet.get_address()),
+                    Err(e) => {
+                        println!("오류: 키 저장소를 저장하지 못했습니다: {}", e);
+                        let _ = keystore.remove_account(&name);
+                    }
                 }
             }
             "2" => {
-                if wallets.is_empty() {
+                if keystore.accounts.is_empty() {
                     println!("지갑이 없습니다. 먼저 지갑을 생성해주세요.");
                     continue;
                 }
                 println!("--- 트랜잭션 생성 ---");
                 println!("사용 가능한 지갑:");
-                for (addr, _) in &wallets {
-                    println!("- {}", addr);
-                }
+                print_accounts(&keystore);
 
-                print!("송신자 주소 입력: ");
-                io::stdout().flush()?;
-                let mut sender_addr = String::new();
-                reader.read_line(&mut sender_addr)?;
-                let sender_addr = sender_addr.trim().to_string();
-
-                let sender_wallet = match wallets.get(&sender_addr) {
-                    Some(w) => w,
+                let sender = read_input(&mut reader, "송신자 계정 이름 또는 주소 입력: ")?;
+                let sender_addr = match keystore.find(&sender) {
+                    Some(account) => account.address.clone(),
                     None => {
                         println!("오류: 유효하지 않은 송신자 주소입니다.");
                         continue;
                     }
                 };
 
-                print!("수신자 주소 입력: ");
-                io::stdout().flush()?;
-                let mut recipient_addr = String::new();
-                reader.read_line(&mut recipient_addr)?;
-                let recipient_addr = recipient_addr.trim().to_string();
+                // 키 저장소의 계정 이름을 입력하면 그 계정의 주소로 바꿉니다.
+                let recipient = read_input(&mut reader, "수신자 계정 이름 또는 주소 입력: ")?;
+                let recipient_addr = match keystore.find(&recipient) {
+                    Some(account) => account.address.clone(),
+                    None => recipient,
+                };
+                // 잘못 입력한 주소로 코인을 보내지 않도록 체크섬과 네트워크를 확인합니다.
+                if let Err(e) = Address::parse_for_network(&recipient_addr, network) {
+                    println!("오류: {}", e);
+                    continue;
+                }
 
                 print!("금액 입력: ");
                 io::stdout().flush()?;
@@ -113,75 +145,256 @@ pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 };
 
-                let mut tx = Transaction::new(sender_addr.clone(), recipient_addr, amount);
-                let signing_key = sender_wallet.to_signing_key().expect("Failed to get signing key");
-                tx.sign(&signing_key, sender_wallet.get_address().to_string());
+                print!("수수료 입력 (기본 0): ");
+                io::stdout().flush()?;
+                let mut fee_str = String::new();
+                reader.read_line(&mut fee_str)?;
+                let fee: u64 = match fee_str.trim() {
+                    "" => 0,
+                    fee_str => match fee_str.parse() {
+                        Ok(num) => num,
+                        Err(_) => {
+                            println!("오류: 유효하지 않은 수수료입니다.");
+                            continue;
+                        }
+                    },
+                };
+                let total_cost = match amount.checked_add(fee) {
+                    Some(total_cost) => total_cost,
+                    None => {
+                        println!("오류: 금액과 수수료의 합이 너무 큽니다.");
+                        continue;
+                    }
+                };
 
-                let request = Request::AddTransaction(tx);
-                let response = send_request(&mut stream, request).await?;
-                println!("응답: {:?}", response);
+                // 서버가 UTXO 모드이면 송신자의 미사용 출력을 입력으로 사용하고 거스름돈 출력을 만듭니다.
+                let request = Request::GetUtxos(sender_addr.clone());
+                let mut tx = match client.request(request).await? {
+                    Response::Utxos(utxos) => {
+                        let mut inputs = Vec::new();
+                        let mut input_total: u64 = 0;
+                        for (outpoint, output) in utxos {
+                            if input_total >= total_cost {
+                                break;
+                            }
+                            input_total += output.amount;
+                            inputs.push(outpoint);
+                        }
+                        if input_total < total_cost {
+                            println!("오류: 잔액이 부족합니다. 사용 가능한 금액: {}", input_total);
+                            continue;
+                        }
+                        let mut outputs = vec![TxOutput { recipient: recipient_addr.clone(), amount }];
+                        if input_total > total_cost {
+                            outputs.push(TxOutput { recipient: sender_addr.clone(), amount: input_total - total_cost });
+                        }
+                        Transaction::new_utxo(sender_addr.clone(), recipient_addr, inputs, outputs)
+                    }
+                    _ => Transaction::new(sender_addr.clone(), recipient_addr, amount),
+                };
+                tx.fee = fee;
+                // 재전송 방지를 위해 서버에서 다음 nonce와 네트워크 식별자를 받아 서명에 포함합니다.
+                match client.get_nonce(&sender_addr).await {
+                    Ok((nonce, chain_id)) => {
+                        tx.nonce = nonce;
+                        tx.chain_id = chain_id;
+                    }
+                    Err(e) => {
+                        println!("오류: {}", e);
+                        continue;
+                    }
+                }
+                let password = read_input(&mut reader, "송신자 계정 비밀번호 입력: ")?;
+                let sender_wallet = match keystore.unlock(&sender_addr, &password) {
+                    Ok(wallet) => wallet,
+                    Err(e) => {
+                        println!("오류: {}", e);
+                        continue;
+                    }
+                };
+                let signing_key = match sender_wallet.to_signing_key() {
+                    Ok(key) => key,
+                    Err(e) => {
+                        println!("오류: 지갑의 개인 키를 사용할 수 없습니다: {}", e);
+                        continue;
+                    }
+                };
+                tx.sign(&signing_key, sender_wallet.get_public_key_hex().to_string());
+
+                match client.add_transaction(tx).await {
+                    Ok(message) => println!("응답: {}", message),
+                    Err(e) => println!("오류: {}", e),
+                }
             }
             "3" => {
-                if wallets.is_empty() {
+                if keystore.accounts.is_empty() {
                     println!("지갑이 없습니다. 먼저 지갑을 생성해주세요.");
                     continue;
                 }
                 println!("--- 블록 채굴 ---");
                 println!("채굴 보상을 받을 채굴자 주소를 선택하세요:");
-                for (addr, _) in &wallets {
-                    println!("- {}", addr);
-                }
-                print!("채굴자 주소 입력: ");
-                io::stdout().flush()?;
-                let mut miner_addr = String::new();
-                reader.read_line(&mut miner_addr)?;
-                let miner_addr = miner_addr.trim().to_string();
+                print_accounts(&keystore);
+                let miner = read_input(&mut reader, "채굴자 계정 이름 또는 주소 입력: ")?;
+                let miner_addr = match keystore.find(&miner) {
+                    Some(account) => account.address.clone(),
+                    None => {
+                        println!("오류: 유효하지 않은 채굴자 주소입니다.");
+                        continue;
+                    }
+                };
 
-                if !wallets.contains_key(&miner_addr) {
-                    println!("오류: 유효하지 않은 채굴자 주소입니다.");
-                    continue;
+                match client.mine_block(&miner_addr).await {
+                    Ok(message) => println!("응답: {}", message),
+                    Err(e) => println!("오류: {}", e),
                 }
-
-                let request = Request::MineBlock(miner_addr);
-                let response = send_request(&mut stream, request).await?;
-                println!("응답: {:?}", response);
             }
             "4" => {
-                if wallets.is_empty() {
+                if keystore.accounts.is_empty() {
                     println!("지갑이 없습니다. 먼저 지갑을 생성해주세요.");
                     continue;
                 }
                 println!("--- 잔액 조회 ---");
                 println!("잔액을 조회할 주소를 선택하세요:");
-                for (addr, _) in &wallets {
-                    println!("- {}", addr);
-                }
-                print!("주소 입력: ");
-                io::stdout().flush()?;
-                let mut address = String::new();
-                reader.read_line(&mut address)?;
-                let address = address.trim().to_string();
+                print_accounts(&keystore);
+                let account = read_input(&mut reader, "계정 이름 또는 주소 입력: ")?;
+                let address = match keystore.find(&account) {
+                    Some(account) => account.address.clone(),
+                    None => {
+                        println!("오류: 유효하지 않은 주소입니다.");
+                        continue;
+                    }
+                };
 
-                if !wallets.contains_key(&address) {
-                    println!("오류: 유효하지 않은 주소입니다.");
-                    continue;
+                match client.get_balance(&address).await {
+                    Ok(balance) => println!("잔액: {}", balance),
+                    Err(e) => println!("오류: {}", e),
                 }
-
-                let request = Request::GetBalance(address);
-                let response = send_request(&mut stream, request).await?;
-                println!("응답: {:?}", response);
             }
             "5" => {
                 println!("--- 블록체인 전체 조회 ---");
-                let request = Request::GetChain;
-                let response = send_request(&mut stream, request).await?;
-                if let Response::Blockchain(bc) = response {
-                    println!("{:#?}", bc);
-                } else {
-                    println!("오류: {:?}", response);
+                // 체인 전체를 한 번에 받지 않고 페이지 단위로 가져옵니다.
+                let mut start = 0;
+                loop {
+                    match client.get_blocks(start, MAX_BLOCKS_PER_PAGE).await {
+                        Ok(blocks) if blocks.is_empty() => break,
+                        Ok(blocks) => {
+                            start += blocks.len() as u64;
+                            for block in blocks {
+                                println!("{:#?}", block);
+                            }
+                        }
+                        Err(e) => {
+                            println!("오류: {}", e);
+                            break;
+                        }
+                    }
+                }
+                match client.get_chain_tip().await {
+                    Ok(tip) => println!("높이: {}, tip: {}, 다음 목표값: {:08x}, 누적 작업량: {}",
+                                        tip.height, tip.tip_hash, tip.bits, tip.cumulative_work),
+                    Err(e) => println!("오류: {}", e),
                 }
             }
             "6" => {
+                println!("--- 계정 목록 ---");
+                if keystore.accounts.is_empty() {
+                    println!("지갑이 없습니다.");
+                }
+                print_accounts(&keystore);
+            }
+            "7" => {
+                println!("--- 시드 문구로 지갑 생성 ---");
+                let name = read_input(&mut reader, "계정 이름 입력: ")?;
+                if name.is_empty() || keystore.find(&name).is_some() {
+                    println!("오류: 계정 이름이 비어 있거나 이미 있는 이름입니다.");
+                    continue;
+                }
+                let password = read_input(&mut reader, "키 저장소 비밀번호 입력: ")?;
+                let confirmation = read_input(&mut reader, "비밀번호 확인: ")?;
+                if password != confirmation {
+                    println!("오류: 비밀번호가 일치하지 않습니다.");
+                    continue;
+                }
+
+                let phrase = hd_wallet::generate_mnemonic();
+                let hd = match HdWallet::from_mnemonic(&phrase, "", 0, network) {
+                    Ok(hd) => hd,
+                    Err(e) => {
+                        println!("오류: {}", e);
+                        continue;
+                    }
+                };
+                let wallet = match hd.derive_wallet(0) {
+                    Ok(wallet) => wallet,
+                    Err(e) => {
+                        println!("오류: {}", e);
+                        continue;
+                    }
+                };
+                if let Err(e) = keystore.add_derived_account(&name, &wallet, &hd.derivation_path(0), &password)
+                    .and_then(|_| keystore.save(&keystore_path))
+                {
+                    println!("오류: 키 저장소를 저장하지 못했습니다: {}", e);
+                    let _ = keystore.remove_account(&name);
+                    continue;
+                }
+                println!("새 지갑이 생성되었습니다: {} ({})", name, wallet.get_address());
+                println!("아래 시드 문구를 안전한 곳에 적어 두세요. 이 문구로 지갑을 복원할 수 있습니다.");
+                println!("{}", phrase);
+            }
+            "8" => {
+                println!("--- 시드 문구로 지갑 복원 ---");
+                let name = read_input(&mut reader, "계정 이름 입력 (주소마다 이름-번호로 저장): ")?;
+                if name.is_empty() {
+                    println!("오류: 계정 이름이 비어 있습니다.");
+                    continue;
+                }
+                let phrase = read_input(&mut reader, "시드 문구 입력: ")?;
+                let passphrase = read_input(&mut reader, "암호 문구 입력 (없으면 Enter): ")?;
+                let hd = match HdWallet::from_mnemonic(&phrase, &passphrase, 0, network) {
+                    Ok(hd) => hd,
+                    Err(e) => {
+                        println!("오류: {}", e);
+                        continue;
+                    }
+                };
+                let password = read_input(&mut reader, "키 저장소 비밀번호 입력: ")?;
+
+                // 노드에 기록이 있는 주소를 찾고, 새 수신 주소로 쓸 다음 주소도 함께 저장합니다.
+                let discovery = match hd.discover(&mut client, DEFAULT_GAP_LIMIT).await {
+                    Ok(discovery) => discovery,
+                    Err(e) => {
+                        println!("오류: {}", e);
+                        continue;
+                    }
+                };
+                let mut indices: Vec<u32> = discovery.used.iter().map(|used| used.index).collect();
+                indices.push(discovery.next_index);
+                let mut restored = 0;
+                for index in indices {
+                    let wallet = match hd.derive_wallet(index) {
+                        Ok(wallet) => wallet,
+                        Err(_) => continue,
+                    };
+                    if keystore.find(wallet.get_address()).is_some() {
+                        continue;
+                    }
+                    let account_name = format!("{}-{}", name, index);
+                    match keystore.add_derived_account(&account_name, &wallet, &hd.derivation_path(index), &password) {
+                        Ok(()) => restored += 1,
+                        Err(e) => println!("오류: {}", e),
+                    }
+                }
+                if let Err(e) = keystore.save(&keystore_path) {
+                    println!("오류: 키 저장소를 저장하지 못했습니다: {}", e);
+                    continue;
+                }
+                for used in discovery.used.iter() {
+                    println!("- {}-{}: {} (잔액: {}, 트랜잭션: {})", name, used.index, used.address, used.balance, used.transaction_count);
+                }
+                println!("사용된 주소 {}개를 찾았고, 계정 {}개를 키 저장소에 추가했습니다.", discovery.used.len(), restored);
+            }
+            "9" => {
                 println!("클라이언트 종료.");
                 break;
             }
@@ -194,18 +407,18 @@ pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-async fn send_request(stream: &mut TcpStream, request: Request) -> Result<Response, Box<dyn std::error::Error>> {
-    let req_json = serde_json::to_vec(&request)?;
-    stream.write_all(&(req_json.len() as u32).to_be_bytes()).await?;
-    stream.write_all(&req_json).await?;
-
-    let mut len_bytes = [0u8; 4];
-    stream.read_exact(&mut len_bytes).await?;
-    let resp_len = u32::from_be_bytes(len_bytes) as usize;
-
-    let mut buffer = vec![0u8; resp_len];
-    stream.read_exact(&mut buffer).await?;
+// 안내 문구를 출력하고 한 줄을 읽어 앞뒤 공백을 제거하여 반환합니다.
+fn read_input<R: BufRead>(reader: &mut R, prompt: &str) -> io::Result<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    reader.read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
 
-    let response: Response = serde_json::from_slice(&buffer)?;
-    Ok(response)
+// 키 저장소의 계정을 "이름: 주소" 형식으로 출력합니다.
+fn print_accounts(keystore: &Keystore) {
+    for account in keystore.accounts.iter() {
+        println!("- {}: {}", account.name, account.address);
+    }
 }
@@ -1,60 +1,371 @@
 // src/block.rs
 use super::transaction::Transaction; // transaction 모듈의 Transaction 구조체 사용
-use sha256::digest;
+use super::merkle::{self, MerkleProof}; // merkle 모듈의 머클 루트/증명 사용
+use super::difficulty::{self, Target}; // difficulty 모듈의 목표값 사용
+use super::encoding::{Decoder, Encoder}; // encoding 모듈의 정규 바이너리 인코딩 사용
+use super::error::BlockchainError; // error 모듈의 오류 유형 사용
 use chrono::Utc;
 use serde::{Serialize, Deserialize}; // Serialize, Deserialize 트레이트 추가
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use log::debug;
+
+/// 채굴 중 취소 플래그를 확인하고 해시 수를 집계하는 간격 (해시 계산 횟수)
+pub const MINING_CANCEL_CHECK_INTERVAL: u64 = 4096;
 
 #[derive(Debug, Clone, Serialize, Deserialize)] // Serialize, Deserialize 추가
 pub struct Block {
     pub index: u64,
     pub timestamp: i64,
     pub transactions: Vec<Transaction>,
+    #[serde(default)]
+    pub merkle_root: String, // 트랜잭션 ID들의 머클 루트 (블록 해시는 트랜잭션 목록 대신 이 값을 포함)
     pub previous_hash: String,
     pub hash: String,
     pub nonce: u64, // 채굴을 위한 Nonce 값
+    pub bits: u32, // 이 블록의 목표값 (compact 표현). 해시가 이 목표값 이하여야 합니다.
+}
+
+/// 트랜잭션 목록을 제외한 블록 헤더입니다. 블록 해시는 헤더만으로 검증할 수 있습니다.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub index: u64,
+    pub timestamp: i64,
+    pub merkle_root: String,
+    pub previous_hash: String,
+    pub hash: String,
+    pub nonce: u64,
+    pub bits: u32,
+    pub transaction_count: usize, // 블록에 포함된 트랜잭션 수
 }
 
 impl Block {
     /// 새로운 블록을 생성합니다.
     pub fn new(index: u64, previous_hash: String, transactions: Vec<Transaction>) -> Self {
+        let merkle_root = Self::compute_merkle_root(&transactions);
         Block {
             index,
             timestamp: Utc::now().timestamp(), // 현재 UTC 타임스탬프
             transactions,
+            merkle_root,
             previous_hash,
             hash: String::new(), // 초기 해시는 비워둡니다. 채굴 시 계산됩니다.
             nonce: 0, // 초기 Nonce 값
+            bits: 0, // 채굴 시 설정됩니다.
         }
     }
 
-    /// 블록의 해시를 계산합니다.
+    /// 블록의 해시를 계산합니다. (헤더 정규 인코딩의 SHA-256)
     pub fn calculate_hash(&self) -> String {
-        let mut data = String::new();
-        data.push_str(&self.index.to_string());
-        data.push_str(&self.timestamp.to_string());
-        data.push_str(&self.previous_hash);
-        data.push_str(&self.nonce.to_string());
-
-        // 트랜잭션 해시를 모두 연결하여 데이터에 포함
-        // 트랜잭션의 서명용 해시를 사용하여 블록 해시에 포함시킵니다.
+        sha256::digest(self.header_bytes()) // sha256 해시 계산
+    }
+
+    /// 해시 대상인 블록 헤더의 정규 인코딩 (encoding 모듈 참고)
+    /// 트랜잭션 목록 대신 머클 루트를 포함하므로, 헤더만으로 블록 해시를 검증할 수 있습니다.
+    pub fn header_bytes(&self) -> Vec<u8> {
+        let mut encoder = self.header_prefix();
+        encoder.u64(self.nonce);
+        encoder.into_bytes()
+    }
+
+    // 헤더 인코딩에서 nonce 앞부분
+    fn header_prefix(&self) -> Encoder {
+        header_prefix(self.index, self.timestamp, &self.previous_hash, &self.merkle_root, self.bits)
+    }
+
+    /// 블록 전체의 정규 인코딩 (헤더 인코딩 + 트랜잭션 목록). P2P 전송과 블록 저장에 사용합니다.
+    /// 블록 해시는 포함하지 않으며 디코딩할 때 다시 계산합니다.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = self.header_prefix();
+        encoder.u64(self.nonce);
+        encoder.count(self.transactions.len());
         for tx in self.transactions.iter() {
-            data.push_str(&tx.calculate_hash_for_signing()); // tx.calculate_hash() 대신 calculate_hash_for_signing() 사용
+            encoder.bytes(&tx.encode());
         }
+        encoder.into_bytes()
+    }
 
-        sha256::digest(data) // sha256 해시 계산
+    /// `encode`로 만든 바이트열에서 블록을 읽습니다.
+    pub fn decode(bytes: &[u8]) -> Result<Self, BlockchainError> {
+        let mut decoder = Decoder::new(bytes);
+        let header = BlockHeader::decode_fields(&mut decoder)?;
+        let mut transactions = Vec::new();
+        for _ in 0..decoder.count()? {
+            transactions.push(Transaction::decode(decoder.bytes()?)?);
+        }
+        decoder.finish()?;
+        Ok(Block {
+            index: header.index,
+            timestamp: header.timestamp,
+            transactions,
+            merkle_root: header.merkle_root,
+            previous_hash: header.previous_hash,
+            hash: header.hash,
+            nonce: header.nonce,
+            bits: header.bits,
+        })
+    }
+
+    /// 블록의 헤더를 반환합니다.
+    pub fn header(&self) -> BlockHeader {
+        BlockHeader {
+            index: self.index,
+            timestamp: self.timestamp,
+            merkle_root: self.merkle_root.clone(),
+            previous_hash: self.previous_hash.clone(),
+            hash: self.hash.clone(),
+            nonce: self.nonce,
+            bits: self.bits,
+            transaction_count: self.transactions.len(),
+        }
+    }
+
+    /// 트랜잭션 목록으로부터 머클 루트를 계산합니다.
+    pub fn compute_merkle_root(transactions: &[Transaction]) -> String {
+        let txids: Vec<String> = transactions.iter().map(|tx| tx.txid()).collect();
+        merkle::merkle_root(&txids)
+    }
+
+    /// 블록 헤더의 머클 루트가 실제 트랜잭션 목록과 일치하는지 확인합니다.
+    pub fn has_valid_merkle_root(&self) -> bool {
+        self.merkle_root == Self::compute_merkle_root(&self.transactions)
+    }
+
+    /// 주어진 트랜잭션이 이 블록에 포함되어 있음을 증명하는 머클 증명을 만듭니다.
+    pub fn merkle_proof(&self, txid: &str) -> Option<MerkleProof> {
+        let txids: Vec<String> = self.transactions.iter().map(|tx| tx.txid()).collect();
+        let tx_index = txids.iter().position(|id| id == txid)?;
+        merkle::merkle_proof(&txids, tx_index)
+    }
+
+    /// 이 블록의 목표값. `bits`를 해석할 수 없으면 None입니다.
+    pub fn target(&self) -> Option<Target> {
+        Target::from_compact(self.bits)
+    }
+
+    /// 이 블록의 작업량 (목표값 이하의 해시를 찾는 데 필요한 평균 해시 시도 횟수)
+    pub fn work(&self) -> u128 {
+        difficulty::block_work(self.bits)
+    }
+
+    /// 블록 해시가 목표값 이하인지 확인합니다. 해시 문자열을 다이제스트 바이트로 되돌려 비교합니다.
+    /// (해시가 블록 내용과 일치하는지는 확인하지 않습니다)
+    pub fn has_valid_proof_of_work(&self) -> bool {
+        let digest: Option<[u8; 32]> = hex::decode(&self.hash).ok().and_then(|bytes| bytes.try_into().ok());
+        match (self.target(), digest) {
+            (Some(target), Some(digest)) => target.is_met_by(&digest),
+            _ => false,
+        }
     }
 
     /// 블록을 채굴합니다 (Proof of Work).
-    /// 주어진 난이도에 맞는 해시를 찾을 때까지 Nonce 값을 증가시킵니다.
-    pub fn mine_block(&mut self, difficulty: usize) {
-        let target_prefix = "0".repeat(difficulty);
-        println!("Mining block {} with difficulty {}...", self.index, difficulty);
+    /// 해시가 목표값 이하가 될 때까지 Nonce 값을 증가시키고, 찾은 해시를 `hash`에 기록합니다.
+    /// 사용한 목표값은 블록 헤더(`bits`)에 기록되며, `bits`를 목표값으로 해석할 수 없으면 오류를 반환합니다.
+    pub fn mine_block(&mut self, bits: u32) -> Result<(), BlockchainError> {
+        self.bits = bits;
+        let target = self.target().ok_or(BlockchainError::InvalidTarget { index: self.index, bits })?;
+        debug!("Mining block {} with target {}...", self.index, target);
 
-        while !self.hash.starts_with(&target_prefix) {
+        let hasher = HeaderHasher::new(self);
+        while !target.is_met_by(&hasher.digest(self.nonce)) {
             self.nonce += 1;
-            self.hash = self.calculate_hash();
         }
+        self.hash = self.calculate_hash();
+
+        debug!("Block mined: {} with nonce {}", self.hash, self.nonce);
+        Ok(())
+    }
+
+    /// `start`부터 `step` 간격으로 nonce를 바꿔 가며 해시가 `target` 이하가 되는 nonce를 찾습니다.
+    /// 여러 스레드가 서로 다른 `start`와 같은 `step`으로 호출하면 nonce 공간을 겹치지 않게 나눠 탐색합니다.
+    ///
+    /// 헤더에서 nonce 앞부분의 해시 상태를 미리 계산해 두고 재사용하므로 `calculate_hash`보다 빠릅니다.
+    /// `MINING_CANCEL_CHECK_INTERVAL`번 해시를 계산할 때마다 `cancel` 플래그를 확인하고,
+    /// 계산한 해시 수를 `hashes`에 더합니다.
+    pub fn search_nonce(
+        &self,
+        target: &Target,
+        start: u64,
+        step: u64,
+        cancel: &AtomicBool,
+        hashes: &AtomicU64,
+    ) -> NonceSearch {
+        let hasher = HeaderHasher::new(self);
+        let mut nonce = start;
+        let mut counted = 0u64;
+        let result = loop {
+            if counted == MINING_CANCEL_CHECK_INTERVAL {
+                hashes.fetch_add(counted, Ordering::Relaxed);
+                counted = 0;
+                if cancel.load(Ordering::Relaxed) {
+                    break NonceSearch::Cancelled;
+                }
+            }
+            counted += 1;
+            if target.is_met_by(&hasher.digest(nonce)) {
+                break NonceSearch::Found(nonce);
+            }
+            match nonce.checked_add(step) {
+                Some(next) => nonce = next,
+                None => break NonceSearch::Exhausted,
+            }
+        };
+        hashes.fetch_add(counted, Ordering::Relaxed);
+        result
+    }
+}
+
+/// `Block::search_nonce`의 결과
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceSearch {
+    /// 목표값을 충족하는 nonce를 찾았습니다.
+    Found(u64),
+    /// 취소 플래그가 설정되어 탐색을 멈췄습니다.
+    Cancelled,
+    /// 맡은 nonce 범위를 모두 탐색했지만 찾지 못했습니다. (타임스탬프 등 헤더를 바꿔 다시 탐색해야 합니다)
+    Exhausted,
+}
+
+impl BlockHeader {
+    /// 헤더의 필드로 블록 해시를 계산합니다. `Block::calculate_hash`와 같은 값입니다.
+    pub fn calculate_hash(&self) -> String {
+        sha256::digest(self.header_bytes())
+    }
+
+    /// 해시 대상인 헤더의 정규 인코딩. `Block::header_bytes`와 같은 값입니다.
+    pub fn header_bytes(&self) -> Vec<u8> {
+        let mut encoder = header_prefix(self.index, self.timestamp, &self.previous_hash, &self.merkle_root, self.bits);
+        encoder.u64(self.nonce);
+        encoder.into_bytes()
+    }
+
+    /// 전송용 정규 인코딩 (해시 대상 인코딩 + 트랜잭션 수)
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        encoder.u32(u32::try_from(self.transaction_count).expect("transaction count exceeds u32"));
+        [self.header_bytes(), encoder.into_bytes()].concat()
+    }
+
+    /// `encode`로 만든 바이트열에서 헤더를 읽습니다. 블록 해시는 다시 계산합니다.
+    pub fn decode(bytes: &[u8]) -> Result<Self, BlockchainError> {
+        let mut decoder = Decoder::new(bytes);
+        let mut header = Self::decode_fields(&mut decoder)?;
+        header.transaction_count = decoder.u32()? as usize;
+        decoder.finish()?;
+        Ok(header)
+    }
+
+    // 해시 대상 헤더 인코딩을 읽습니다. (transaction_count는 0)
+    fn decode_fields(decoder: &mut Decoder) -> Result<Self, BlockchainError> {
+        decoder.version()?;
+        let mut header = BlockHeader {
+            index: decoder.u64()?,
+            timestamp: decoder.i64()?,
+            previous_hash: decoder.str()?,
+            merkle_root: decoder.str()?,
+            bits: decoder.u32()?,
+            nonce: decoder.u64()?,
+            hash: String::new(),
+            transaction_count: 0,
+        };
+        header.hash = header.calculate_hash();
+        Ok(header)
+    }
+}
+
+// 헤더 인코딩에서 nonce 앞부분: version | index | timestamp | previous_hash | merkle_root | bits
+fn header_prefix(index: u64, timestamp: i64, previous_hash: &str, merkle_root: &str, bits: u32) -> Encoder {
+    let mut encoder = Encoder::versioned();
+    encoder.u64(index).i64(timestamp).str(previous_hash).str(merkle_root).u32(bits);
+    encoder
+}
+
+// nonce만 바꿔 가며 블록 해시를 반복 계산합니다. `calculate_hash`와 같은 값을 만듭니다.
+// 헤더 인코딩에서 nonce가 마지막 필드이므로, 앞부분은 해시 상태에 미리 넣어 두고 재사용합니다.
+struct HeaderHasher {
+    head: Sha256,
+}
+
+impl HeaderHasher {
+    fn new(block: &Block) -> Self {
+        let mut head = Sha256::new();
+        head.update(block.header_prefix().into_bytes());
+        HeaderHasher { head }
+    }
+
+    fn digest(&self, nonce: u64) -> [u8; 32] {
+        let mut hasher = self.head.clone();
+        hasher.update(nonce.to_le_bytes());
+        hasher.finalize().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 해시의 절반 정도가 충족하는 목표값과, 사실상 아무 해시도 충족하지 못하는 목표값 (목표값 1)
+    const EASY_BITS: u32 = 0x207fffff;
+    const IMPOSSIBLE_BITS: u32 = 0x03000001;
+
+    fn sample_block(bits: u32) -> Block {
+        let coinbase = Transaction::new_coinbase("bgrt1miner".to_string(), 100, 1);
+        let mut block = Block::new(1, "00".repeat(32), vec![coinbase]);
+        block.bits = bits;
+        block
+    }
+
+    #[test]
+    fn header_hasher_matches_calculate_hash() {
+        let mut block = sample_block(EASY_BITS);
+        let hasher = HeaderHasher::new(&block);
+        for nonce in [0, 1, 0xdead_beef, u64::MAX] {
+            block.nonce = nonce;
+            assert_eq!(hex::encode(hasher.digest(nonce)), block.calculate_hash());
+        }
+    }
+
+    #[test]
+    fn mined_block_meets_its_target() {
+        let mut block = sample_block(0);
+        block.mine_block(EASY_BITS).unwrap();
+        assert_eq!(block.bits, EASY_BITS);
+        assert_eq!(block.hash, block.calculate_hash());
+        assert!(block.has_valid_proof_of_work());
+
+        let mut invalid = sample_block(0);
+        assert!(matches!(invalid.mine_block(0), Err(BlockchainError::InvalidTarget { index: 1, bits: 0 })));
+    }
+
+    #[test]
+    fn search_nonce_strides_from_its_start() {
+        let block = sample_block(EASY_BITS);
+        let target = block.target().unwrap();
+        let (cancel, hashes) = (AtomicBool::new(false), AtomicU64::new(0));
+        for start in 0..3 {
+            let NonceSearch::Found(nonce) = block.search_nonce(&target, start, 3, &cancel, &hashes) else {
+                panic!("no nonce found from {}", start);
+            };
+            assert_eq!(nonce % 3, start);
+            let mut mined = block.clone();
+            mined.nonce = nonce;
+            mined.hash = mined.calculate_hash();
+            assert!(mined.has_valid_proof_of_work());
+        }
+        assert!(hashes.load(Ordering::Relaxed) >= 3);
+    }
+
+    #[test]
+    fn search_nonce_stops_when_exhausted_or_cancelled() {
+        let block = sample_block(IMPOSSIBLE_BITS);
+        let target = block.target().unwrap();
+        let (cancel, hashes) = (AtomicBool::new(false), AtomicU64::new(0));
+        // u64::MAX - 3, u64::MAX - 1 다음은 넘칩니다.
+        assert_eq!(block.search_nonce(&target, u64::MAX - 3, 2, &cancel, &hashes), NonceSearch::Exhausted);
+        assert_eq!(hashes.load(Ordering::Relaxed), 2);
 
-        println!("Block mined: {} with nonce {}", self.hash, self.nonce);
+        cancel.store(true, Ordering::Relaxed);
+        assert_eq!(block.search_nonce(&target, 0, 1, &cancel, &hashes), NonceSearch::Cancelled);
+        assert_eq!(hashes.load(Ordering::Relaxed), 2 + MINING_CANCEL_CHECK_INTERVAL);
     }
 }
@@ -1,153 +1,1550 @@
 // src/blockchain.rs
-use super::block::Block;           // block 모듈의 Block 구조체 사용
-use super::transaction::Transaction; // transaction 모듈의 Transaction 구조체 사용
+use super::block::{Block, BlockHeader}; // block 모듈의 Block 구조체 사용
+use super::transaction::{OutPoint, Transaction}; // transaction 모듈의 Transaction 구조체 사용
+use super::storage::ChainStore;     // storage 모듈의 ChainStore 트레이트 사용
+use super::utxo::UtxoSet;           // utxo 모듈의 UtxoSet 구조체 사용
+use super::merkle::MerkleProof;     // merkle 모듈의 MerkleProof 구조체 사용
+use super::difficulty::DifficultyParams; // difficulty 모듈의 난이도 조정 매개변수 사용
+use super::error::BlockchainError; // error 모듈의 오류 유형 사용
+use super::events::{ChainEvent, EventBus}; // events 모듈의 이벤트 버스 사용
+use super::index::{ChainIndex, TransactionInfo}; // index 모듈의 조회용 색인 사용
+use super::address::{Address, Network}; // address 모듈의 주소 유형 사용
+use super::chain_params::{ChainParams, RewardSchedule}; // chain_params 모듈의 네트워크 매개변수 사용
 
-// Blockchain 내부에서 필요한 sha256, chrono도 여기서 use 해줍니다.
-use sha256::digest;
-use chrono::Utc;
 use serde::{Serialize, Deserialize}; // Serialize, Deserialize 트레이트 추가
+use chrono::Utc;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use log::{debug, error, info, warn};
+
+/// 여러 Blockchain 복제본이 공유하는 저장소 핸들입니다.
+pub type SharedChainStore = Arc<Mutex<dyn ChainStore>>;
+
+/// 기본 네트워크 식별자 (메인넷). 트랜잭션 서명에 포함되어 다른 네트워크에서의 재사용을 막습니다.
+pub const DEFAULT_CHAIN_ID: u64 = Network::Mainnet.chain_id();
+
+/// 제네시스 블록의 고정 타임스탬프 (2025-07-01 00:00:00 UTC).
+/// 따로 시작한 노드들도 같은 제네시스 블록을 만들어 하나의 체인을 공유할 수 있습니다.
+pub const GENESIS_TIMESTAMP: i64 = 1_751_328_000;
+
+/// 블록 타임스탬프가 현재 시각보다 앞설 수 있는 최대 시간 (초)
+pub const MAX_FUTURE_BLOCK_TIME: i64 = 2 * 60 * 60;
+
+/// 블록에 담을 수 있는 트랜잭션의 기본 최대 총 크기 (직렬화된 바이트 기준, 코인베이스 포함)
+pub const DEFAULT_MAX_BLOCK_SIZE: usize = 1_000_000;
+
+/// 기본 채굴 보상
+pub const DEFAULT_MINING_REWARD: u64 = 100;
+
+/// 대기열에 보관할 수 있는 기본 최대 트랜잭션 수
+pub const DEFAULT_MAX_PENDING_TRANSACTIONS: usize = 10_000;
+/// 대기열에 보관할 수 있는 트랜잭션의 기본 최대 총 크기 (직렬화된 바이트 기준)
+pub const DEFAULT_MAX_PENDING_BYTES: usize = 10_000_000;
+
+/// 곁가지 블록을 보관하는 최대 깊이. 이보다 오래된 분기로는 체인을 재구성하지 않습니다.
+pub const MAX_REORG_DEPTH: u64 = 100;
+
+/// 같은 높이에 보관하는 곁가지 블록의 최대 수. 넘치는 블록은 거부합니다.
+pub const MAX_SIDE_BLOCKS_PER_HEIGHT: usize = 4;
 
+/// 외부에서 받은 블록을 처리한 결과입니다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockAcceptance {
+    /// 현재 tip 뒤에 연결되었습니다.
+    Extended,
+    /// 누적 작업량이 더 많은 곁가지로 체인을 재구성했습니다.
+    Reorganized,
+    /// 곁가지에 보관되었습니다. (현재 체인보다 누적 작업량이 많지 않음)
+    SideBranch,
+    /// 이전 블록을 알 수 없습니다. 동기화가 필요합니다.
+    Orphan,
+    /// 이미 알고 있는 블록입니다.
+    Duplicate,
+    /// 유효하지 않은 블록입니다.
+    Rejected,
+}
+
+/// 체인 끝(tip)의 요약 정보입니다.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainTip {
+    pub height: u64,          // tip 블록의 인덱스
+    pub tip_hash: String,     // tip 블록의 해시
+    pub bits: u32,             // 다음 블록에 요구되는 목표값 (compact 표현)
+    pub cumulative_work: u128, // 메인 체인의 누적 작업량
+}
+
+/// 발행량 요약 정보입니다.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupplyInfo {
+    pub height: u64,                      // tip 블록의 인덱스
+    pub total_supply: u64,                // 현재 유통량 (초기 배정 + 발행된 보상 - 소각된 수수료)
+    pub max_supply: u64,                  // 총 발행 한도
+    pub next_block_subsidy: u64,          // 다음 블록의 보조금 (수수료 제외)
+    pub next_halving_height: Option<u64>, // 다음 반감 높이 (보조금이 이미 0이면 None)
+}
+
+/// 잔액을 관리하는 방식입니다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LedgerMode {
+    /// 주소별 잔액을 송금/수금 합계로 계산합니다. (기본값)
+    #[default]
+    #[serde(alias = "account")]
+    Account,
+    /// 트랜잭션이 이전 출력을 입력으로 소비하고 새 출력을 만듭니다.
+    #[serde(alias = "utxo")]
+    Utxo,
+}
+
+/// 대기열(mempool)의 크기 제한입니다. 가득 차면 새 트랜잭션을 거부합니다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MempoolLimits {
+    pub max_transactions: usize, // 최대 트랜잭션 수
+    pub max_bytes: usize,        // 트랜잭션의 최대 총 크기 (바이트)
+}
+
+impl Default for MempoolLimits {
+    fn default() -> Self {
+        MempoolLimits {
+            max_transactions: DEFAULT_MAX_PENDING_TRANSACTIONS,
+            max_bytes: DEFAULT_MAX_PENDING_BYTES,
+        }
+    }
+}
+
+/// 저장소에서 체인을 열 때 사용하는 설정입니다.
+/// 네트워크의 합의 규칙(`params`)과 이 노드에만 적용되는 정책으로 나뉩니다.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainSettings {
+    pub params: ChainParams,
+    pub ledger_mode: LedgerMode,
+    pub max_block_size: usize,
+    pub mempool_limits: MempoolLimits,
+}
+
+impl Default for ChainSettings {
+    fn default() -> Self {
+        ChainSettings {
+            params: ChainParams::mainnet(),
+            ledger_mode: LedgerMode::Account,
+            max_block_size: DEFAULT_MAX_BLOCK_SIZE,
+            mempool_limits: MempoolLimits::default(),
+        }
+    }
+}
+
+fn default_max_block_size() -> usize {
+    DEFAULT_MAX_BLOCK_SIZE
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)] // Clone, Serialize, Deserialize 추가
 pub struct Blockchain {
     pub chain: Vec<Block>,
-    pub difficulty: usize,
+    pub bits: u32, // 다음 블록에 사용할 목표값 (compact 표현, 블록이 추가될 때마다 다시 계산)
+    #[serde(default)]
+    pub difficulty_params: DifficultyParams,
     pub pending_transactions: Vec<Transaction>,
-    pub mining_reward: u64,
+    #[serde(default)]
+    pub reward: RewardSchedule,
+    #[serde(default = "default_max_block_size")]
+    pub max_block_size: usize,
+    #[serde(default)]
+    pub ledger_mode: LedgerMode,
+    #[serde(default)]
+    pub chain_id: u64,
+    #[serde(default)]
+    pub mempool_limits: MempoolLimits,
+    // 주소별로 다음에 사용해야 할 트랜잭션 nonce (확정된 블록 기준)
+    #[serde(default)]
+    account_nonces: HashMap<String, u64>,
+    // 대기열 트랜잭션의 인코딩 크기 합계. 트랜잭션을 넣고 뺄 때 함께 갱신합니다.
+    #[serde(skip)]
+    pending_bytes: usize,
+    // 대기열 트랜잭션이 입력으로 소비한 출력 (UTXO 모드)
+    #[serde(skip)]
+    pending_spent: HashSet<OutPoint>,
+    // 메인 체인에 속하지 않은 곁가지 블록 (블록 해시 -> 블록)
+    #[serde(skip)]
+    side_blocks: HashMap<String, Block>,
+    // 블록을 영구 저장하는 저장소 (없으면 메모리에만 유지). 직렬화 대상이 아닙니다.
+    #[serde(skip)]
+    store: Option<SharedChainStore>,
+    // 블록 연결, 트랜잭션 추가 이벤트를 구독자에게 전달하는 이벤트 버스
+    #[serde(skip)]
+    events: EventBus,
+    // 잔액, 트랜잭션 위치, 주소별 기록 색인. 블록 연결/분리 시 함께 갱신됩니다. (Response 크기를 줄이려고 Box에 담습니다)
+    #[serde(skip)]
+    index: Box<ChainIndex>,
 }
 
 impl Blockchain {
-    pub fn new(difficulty: usize) -> Self {
+    /// 메인넷 규칙에 초기 목표값만 바꾼 메모리 전용 체인을 만듭니다.
+    /// 초기 목표값에 맞는 제네시스 nonce를 새로 찾으며, `initial_bits`가 올바른 목표값이 아니면 패닉합니다.
+    pub fn new(initial_bits: u32) -> Self {
+        let mut params = ChainParams {
+            difficulty: DifficultyParams::with_initial_bits(initial_bits),
+            ..ChainParams::mainnet()
+        };
+        params.genesis_nonce = params.mine_genesis_nonce().expect("initial bits must be a valid compact target");
+        let genesis_block = params.genesis_block().expect("mined genesis nonce meets its target");
         let mut blockchain = Blockchain {
             chain: Vec::new(),
-            difficulty,
+            bits: initial_bits,
+            difficulty_params: params.difficulty,
             pending_transactions: Vec::new(),
-            mining_reward: 100,
+            reward: params.reward.clone(),
+            max_block_size: DEFAULT_MAX_BLOCK_SIZE,
+            ledger_mode: LedgerMode::Account,
+            chain_id: DEFAULT_CHAIN_ID,
+            mempool_limits: MempoolLimits::default(),
+            account_nonces: HashMap::new(),
+            pending_bytes: 0,
+            pending_spent: HashSet::new(),
+            side_blocks: HashMap::new(),
+            store: None,
+            events: EventBus::new(),
+            index: Box::default(),
         };
-        blockchain.create_genesis_block();
+        blockchain.create_genesis_block(genesis_block);
         blockchain
     }
 
-    fn create_genesis_block(&mut self) {
-        let mut genesis_block = Block::new(0, "0".to_string(), vec![]);
-        // mine_block 내부에서 해시를 계산하고 설정해야 하지만,
-        // 사용자 코드의 흐름을 유지하기 위해 calculate_hash 호출을 남겨둡니다.
-        genesis_block.mine_block(self.difficulty);
-        genesis_block.hash = genesis_block.calculate_hash();
+    /// 저장소에서 체인을 불러와 Blockchain을 생성합니다.
+    /// 저장소가 비어 있으면 제네시스 블록을 새로 만들어 저장하고,
+    /// 저장된 블록이 있으면 제네시스 블록이 네트워크 매개변수와 같은지 확인하고
+    /// 체인 전체의 유효성을 다시 검사한 뒤 사용합니다.
+    pub fn open<S: ChainStore + 'static>(settings: ChainSettings, store: S) -> Result<Self, BlockchainError> {
+        let params = settings.params;
+        params.validate()?;
+        let genesis_block = params.genesis_block()?;
+        let store: SharedChainStore = Arc::new(Mutex::new(store));
+        let blocks = store.lock().unwrap().load_blocks()?;
+
+        let mut blockchain = Blockchain {
+            chain: blocks,
+            bits: params.difficulty.initial_bits,
+            difficulty_params: params.difficulty,
+            pending_transactions: Vec::new(),
+            reward: params.reward.clone(),
+            max_block_size: settings.max_block_size,
+            ledger_mode: settings.ledger_mode,
+            chain_id: params.chain_id(),
+            mempool_limits: settings.mempool_limits,
+            account_nonces: HashMap::new(),
+            pending_bytes: 0,
+            pending_spent: HashSet::new(),
+            side_blocks: HashMap::new(),
+            store: Some(Arc::clone(&store)),
+            events: EventBus::new(),
+            index: Box::default(),
+        };
+
+        if blockchain.chain.is_empty() {
+            store.lock().unwrap().append_block(&genesis_block)?;
+            blockchain.create_genesis_block(genesis_block);
+        } else {
+            // 다른 네트워크의 데이터 디렉터리를 열지 않도록 저장된 제네시스 블록을 확인합니다.
+            if blockchain.chain[0].hash != genesis_block.hash {
+                return Err(BlockchainError::GenesisMismatch {
+                    expected: genesis_block.hash,
+                    actual: blockchain.chain[0].hash.clone(),
+                });
+            }
+            blockchain.validate_chain()?;
+            for i in 0..blockchain.chain.len() {
+                blockchain.record_nonces(i);
+            }
+            blockchain.rebuild_index();
+            blockchain.bits = blockchain.next_bits();
+            info!("Loaded {} blocks from storage.", blockchain.chain.len());
+        }
+        Ok(blockchain)
+    }
+
+    fn create_genesis_block(&mut self, genesis_block: Block) {
+        self.index.connect_block(&genesis_block);
         self.chain.push(genesis_block);
-        println!("Genesis block created: {:?}", self.chain[0]);
+        info!("Genesis block created: {}", self.chain[0].hash);
     }
 
-    fn add_block(&mut self, mut new_block: Block) {
-        let latest_block = self.get_latest_block().unwrap();
-        new_block.previous_hash = latest_block.hash.clone();
-        new_block.mine_block(self.difficulty);
-        new_block.hash = new_block.calculate_hash(); // calculate_hash 호출 유지
+    /// 다른 노드가 채굴한 블록을 처리합니다.
+    /// 현재 tip을 잇는 블록은 검증 후 바로 연결하고, 곁가지 블록은 보관해 두었다가
+    /// 곁가지의 누적 작업량이 메인 체인보다 많아지면 그 곁가지로 체인을 재구성합니다.
+    pub fn accept_block(&mut self, block: Block) -> BlockAcceptance {
+        if self.has_block(&block.hash) {
+            return BlockAcceptance::Duplicate;
+        }
+
+        let tip_hash = self.get_latest_block().unwrap().hash.clone();
+        if block.previous_hash == tip_hash {
+            let index = block.index;
+            if let Err(e) = self.extend_tip(block) {
+                warn!("Received invalid block {}: {}", index, e);
+                return BlockAcceptance::Rejected;
+            }
+            return BlockAcceptance::Extended;
+        }
+
+        let parent_index = match self.side_blocks.get(&block.previous_hash)
+            .or_else(|| self.get_block_by_hash(&block.previous_hash))
+        {
+            Some(parent) => parent.index,
+            None => return BlockAcceptance::Orphan,
+        };
+
+        // 곁가지 블록은 재구성 시 전체 검증하므로, 보관 전에는 위치와 작업 증명만 확인합니다.
+        // 인덱스는 상대가 정한 값이므로 이전 블록 바로 다음인지 먼저 확인합니다.
+        let height = self.chain.len() as u64;
+        if block.index != parent_index + 1
+            || block.index.saturating_add(MAX_REORG_DEPTH) < height
+            || block.hash != block.calculate_hash()
+            || !block.has_valid_merkle_root()
+            || !block.has_valid_proof_of_work()
+        {
+            warn!("Received invalid side branch block {}.", block.index);
+            return BlockAcceptance::Rejected;
+        }
+        // 목표값은 곁가지의 조상 블록으로 계산한 값이어야 합니다. (가장 쉬운 목표값으로 만든 블록을 막습니다)
+        let expected_bits = self.difficulty_params
+            .expected_bits_at(block.index, |index| self.ancestor_at(&block.previous_hash, index));
+        if block.bits != expected_bits {
+            warn!("Side branch block {} has target bits {:08x}, expected {:08x}.", block.index, block.bits, expected_bits);
+            return BlockAcceptance::Rejected;
+        }
+        let same_height = self.side_blocks.values().filter(|side| side.index == block.index).count();
+        if same_height >= MAX_SIDE_BLOCKS_PER_HEIGHT {
+            warn!("Too many side branch blocks at height {}. Ignoring {}.", block.index, block.hash);
+            return BlockAcceptance::Rejected;
+        }
+        let block_hash = block.hash.clone();
+        self.side_blocks.insert(block_hash.clone(), block);
+        match self.try_reorganize(&block_hash) {
+            Ok(acceptance) => acceptance,
+            Err(e) => {
+                error!("Failed to reorganize to side branch ending at {}: {}", block_hash, e);
+                BlockAcceptance::Rejected
+            }
+        }
+    }
+
+    /// 이 노드에서 채굴한 블록을 검증하여 체인에 연결합니다.
+    /// 채굴하는 동안 다른 블록이 먼저 연결되어 블록이 현재 tip을 잇지 않으면 `StaleBlock` 오류를 반환합니다.
+    pub fn submit_block(&mut self, block: Block) -> Result<(), BlockchainError> {
+        let tip_hash = &self.get_latest_block().ok_or(BlockchainError::EmptyChain)?.hash;
+        if block.previous_hash != *tip_hash {
+            return Err(BlockchainError::StaleBlock { index: block.index });
+        }
+        self.extend_tip(block)
+    }
+
+    // 현재 tip을 잇는 블록을 검증하여 연결하고, 블록에 포함된 트랜잭션을 대기열에서 뺍니다.
+    fn extend_tip(&mut self, block: Block) -> Result<(), BlockchainError> {
+        let mut state = self.ledger_state(self.chain.len());
+        self.validate_block(&self.chain, &block, &mut state)?;
+        let included: HashSet<String> = block.transactions.iter().map(|tx| tx.txid()).collect();
+        self.connect_block(block)?;
+        self.refresh_pending_transactions(Vec::new(), &included);
+        self.prune_side_blocks();
+        Ok(())
+    }
+
+    /// `branch_tip`에서 끝나는 곁가지의 누적 작업량이 메인 체인보다 많으면 체인을 재구성합니다.
+    /// 재구성 중 저장소 기록에 실패하면 원래 체인을 되돌리고 오류를 반환합니다.
+    fn try_reorganize(&mut self, branch_tip: &str) -> Result<BlockAcceptance, BlockchainError> {
+        // 곁가지를 따라 메인 체인과 만나는 지점(분기점)까지 거슬러 올라갑니다.
+        let mut branch = Vec::new();
+        let mut cursor = branch_tip.to_string();
+        while let Some(block) = self.side_blocks.get(&cursor) {
+            cursor = block.previous_hash.clone();
+            branch.push(block.clone());
+        }
+        branch.reverse();
+        let fork_index = match self.main_chain_position(&cursor) {
+            Some(index) => index,
+            None => return Ok(BlockAcceptance::Orphan),
+        };
+
+        let branch_work: u128 = branch.iter()
+            .fold(0, |acc, block| acc.saturating_add(block.work()));
+        let main_work: u128 = self.chain[fork_index + 1..].iter()
+            .fold(0, |acc, block| acc.saturating_add(block.work()));
+        if branch_work <= main_work {
+            return Ok(BlockAcceptance::SideBranch);
+        }
+
+        // 분기점 이후의 곁가지 블록을 순서대로 전부 검증합니다.
+        {
+            let mut state = self.ledger_state(fork_index + 1);
+            let mut candidate = self.chain[..=fork_index].to_vec();
+            for (n, block) in branch.iter().enumerate() {
+                if let Err(e) = self.validate_block(&candidate, block, &mut state) {
+                    warn!("Side branch block {} is invalid ({}). Discarding branch.", block.index, e);
+                    for invalid in branch[n..].iter() {
+                        self.side_blocks.remove(&invalid.hash);
+                    }
+                    return Ok(BlockAcceptance::Rejected);
+                }
+                candidate.push(block.clone());
+            }
+        }
+
+        info!("Reorganizing chain at height {}: {} blocks disconnected, {} blocks connected.",
+                 fork_index + 1, self.chain.len() - fork_index - 1, branch.len());
+
+        let disconnected = self.disconnect_after(fork_index)?;
+        for block in branch.iter() {
+            if let Err(e) = self.connect_block(block.clone()) {
+                self.restore_chain(fork_index, disconnected);
+                return Err(e);
+            }
+        }
+
+        let mut included = HashSet::new();
+        for block in branch {
+            self.side_blocks.remove(&block.hash);
+            included.extend(block.transactions.iter().map(|tx| tx.txid()));
+        }
+
+        // 분리된 블록의 트랜잭션은 새 tip에서도 유효하면 대기열로 되돌립니다.
+        let mut returned = Vec::new();
+        for block in disconnected {
+            returned.extend(block.transactions.iter().filter(|tx| !tx.is_coinbase()).cloned());
+            self.side_blocks.insert(block.hash.clone(), block);
+        }
+        self.refresh_pending_transactions(returned, &included);
+        self.prune_side_blocks();
+        Ok(BlockAcceptance::Reorganized)
+    }
+
+    /// 메인 체인에서 `fork_index` 뒤의 블록을 모두 분리하여 반환합니다. 저장소를 먼저 같은 높이로 자르며,
+    /// 실패하면 체인을 변경하지 않습니다.
+    fn disconnect_after(&mut self, fork_index: usize) -> Result<Vec<Block>, BlockchainError> {
+        if let Some(store) = &self.store {
+            store.lock().unwrap().truncate_blocks(fork_index as u64 + 1)?;
+        }
+        let disconnected = self.chain.split_off(fork_index + 1);
+        for block in disconnected.iter().rev() {
+            self.index.disconnect_block(block);
+        }
+        self.account_nonces.clear();
+        for i in 0..self.chain.len() {
+            self.record_nonces(i);
+        }
+        self.bits = self.next_bits();
+        Ok(disconnected)
+    }
+
+    /// 재구성에 실패했을 때 `fork_index` 뒤에 연결한 곁가지 블록을 분리하고 `original` 블록을 다시 연결합니다.
+    /// 곁가지 블록은 `side_blocks`에 그대로 남아 있습니다. 저장소 기록이 다시 실패하면 그 자리에서 멈추고,
+    /// 연결하지 못한 원래 블록은 곁가지로 보관하여 체인과 저장소가 어긋나지 않게 합니다.
+    fn restore_chain(&mut self, fork_index: usize, original: Vec<Block>) {
+        let mut remaining = original.into_iter();
+        match self.disconnect_after(fork_index) {
+            Ok(_) => {
+                for block in remaining.by_ref() {
+                    if let Err(e) = self.connect_block(block.clone()) {
+                        error!("Failed to restore block {}: {}", block.index, e);
+                        self.side_blocks.insert(block.hash.clone(), block);
+                        break;
+                    }
+                }
+            }
+            Err(e) => error!("Failed to disconnect side branch blocks: {}", e),
+        }
+        for block in remaining {
+            self.side_blocks.insert(block.hash.clone(), block);
+        }
+    }
+
+    /// `returned` 트랜잭션과 기존 대기열을 새 tip 기준으로 다시 검사하여 대기열을 만듭니다.
+    /// `included`에 있는 (이미 블록에 포함된) 트랜잭션은 제외합니다.
+    fn refresh_pending_transactions(&mut self, returned: Vec<Transaction>, included: &HashSet<String>) {
+        let pending = std::mem::take(&mut self.pending_transactions);
+        self.pending_bytes = 0;
+        self.pending_spent.clear();
+        for tx in returned.into_iter().chain(pending) {
+            if !included.contains(&tx.txid())
+                && let Err(e) = self.admit_transaction(tx)
+            {
+                info!("Dropped pending transaction: {}", e);
+            }
+        }
+    }
+
+    /// 너무 오래된 곁가지 블록을 제거합니다.
+    fn prune_side_blocks(&mut self) {
+        let height = self.chain.len() as u64;
+        self.side_blocks.retain(|_, block| block.index.saturating_add(MAX_REORG_DEPTH) >= height);
+    }
+
+    /// 메인 체인에서 주어진 해시를 가진 블록의 위치를 찾습니다.
+    fn main_chain_position(&self, hash: &str) -> Option<usize> {
+        self.chain.iter().rposition(|block| block.hash == hash)
+    }
+
+    /// `tip_hash` 블록(메인 체인 또는 곁가지)과 그 조상 중 인덱스가 `index`인 블록을 찾습니다.
+    fn ancestor_at(&self, tip_hash: &str, index: u64) -> Option<&Block> {
+        let mut cursor = tip_hash;
+        while let Some(block) = self.side_blocks.get(cursor) {
+            if block.index <= index {
+                return (block.index == index).then_some(block);
+            }
+            cursor = &block.previous_hash;
+        }
+        let position = self.main_chain_position(cursor)?;
+        self.chain[..=position].get(index as usize)
+    }
+
+    /// 메인 체인 또는 곁가지에 해당 해시의 블록이 있는지 확인합니다.
+    pub fn has_block(&self, hash: &str) -> bool {
+        self.side_blocks.contains_key(hash) || self.main_chain_position(hash).is_some()
+    }
+
+    /// 메인 체인의 `block_index` 블록에 트랜잭션이 포함되어 있음을 증명하는 머클 증명을 반환합니다.
+    pub fn get_merkle_proof(&self, block_index: u64, txid: &str) -> Option<MerkleProof> {
+        self.chain.get(block_index as usize)?.merkle_proof(txid)
+    }
+
+    /// 메인 체인에서 해시로 블록을 찾습니다.
+    pub fn get_block_by_hash(&self, hash: &str) -> Option<&Block> {
+        self.main_chain_position(hash).map(|index| &self.chain[index])
+    }
+
+    /// 인덱스 `start`부터 최대 `limit`개의 블록을 반환합니다.
+    pub fn get_blocks(&self, start: u64, limit: usize) -> &[Block] {
+        let start = (start as usize).min(self.chain.len());
+        let end = start.saturating_add(limit).min(self.chain.len());
+        &self.chain[start..end]
+    }
+
+    /// 인덱스 `start`부터 최대 `limit`개의 블록 헤더를 반환합니다.
+    pub fn get_headers(&self, start: u64, limit: usize) -> Vec<BlockHeader> {
+        self.get_blocks(start, limit).iter().map(|block| block.header()).collect()
+    }
+
+    /// 체인 끝의 높이, 해시, 다음 목표값, 누적 작업량을 반환합니다.
+    pub fn chain_tip(&self) -> ChainTip {
+        let tip = self.get_latest_block().unwrap(); // 체인에는 항상 제네시스 블록이 있습니다.
+        ChainTip {
+            height: tip.index,
+            tip_hash: tip.hash.clone(),
+            bits: self.bits,
+            cumulative_work: self.cumulative_work(),
+        }
+    }
+
+    /// 높이 `height` 블록이 발행할 수 있는 보조금 (수수료 제외)
+    pub fn block_subsidy(&self, height: u64) -> u64 {
+        self.reward.subsidy_at(height, self.genesis_supply())
+    }
+
+    /// 현재 유통량, 발행 한도, 다음 블록 보조금과 다음 반감 높이를 반환합니다.
+    pub fn supply_info(&self) -> SupplyInfo {
+        let height = self.get_latest_block().map_or(0, |block| block.index);
+        let next_block_subsidy = self.block_subsidy(height + 1);
+        SupplyInfo {
+            height,
+            total_supply: self.index.total_supply(),
+            max_supply: self.reward.max_supply,
+            next_block_subsidy,
+            // 발행 한도에 도달했으면 반감될 보조금이 없습니다.
+            next_halving_height: self.reward.next_halving_height(height).filter(|_| next_block_subsidy > 0),
+        }
+    }
+
+    // 제네시스 블록의 초기 배정 합계
+    fn genesis_supply(&self) -> u64 {
+        self.chain.first().map_or(0, |genesis_block| {
+            genesis_block.transactions.iter().fold(0u64, |acc, tx| acc.saturating_add(tx.amount))
+        })
+    }
+
+    /// 메인 체인의 누적 작업량. 노드 간 체인 선택(더 많은 작업량의 체인을 따름)에 사용됩니다.
+    pub fn cumulative_work(&self) -> u128 {
+        self.index.chain_work()
+    }
+
+    /// 동기화 요청에 사용할 블록 위치 목록(locator)을 만듭니다.
+    /// tip부터 최근 10개 블록은 모두 포함하고, 이후로는 간격을 두 배씩 늘리며 제네시스까지 포함합니다.
+    pub fn block_locator(&self) -> Vec<String> {
+        let mut locator = Vec::new();
+        let mut index = self.chain.len() as i64 - 1;
+        let mut step = 1;
+        while index > 0 {
+            locator.push(self.chain[index as usize].hash.clone());
+            if locator.len() >= 10 {
+                step *= 2;
+            }
+            index -= step;
+        }
+        locator.push(self.chain[0].hash.clone());
+        locator
+    }
+
+    /// locator에서 메인 체인과 처음으로 일치하는 블록 다음부터 최대 `limit`개의 블록을 반환합니다.
+    pub fn blocks_after_locator(&self, locator: &[String], limit: usize) -> Vec<Block> {
+        let start = locator.iter()
+            .find_map(|hash| self.main_chain_position(hash))
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        self.chain.iter().skip(start).take(limit).cloned().collect()
+    }
+
+    /// 블록을 저장소에 기록하고 체인에 연결합니다.
+    /// 체인에 연결하기 전에 저장소에 먼저 기록하며, 실패하면 체인을 변경하지 않습니다.
+    fn connect_block(&mut self, new_block: Block) -> Result<(), BlockchainError> {
+        self.persist_block(&new_block)?;
+        self.push_block(new_block);
+        Ok(())
+    }
+
+    /// 블록을 저장소에 기록합니다. 저장소가 없으면 아무것도 하지 않습니다.
+    fn persist_block(&self, block: &Block) -> Result<(), BlockchainError> {
+        if let Some(store) = &self.store {
+            store.lock().unwrap().append_block(block)?;
+        }
+        Ok(())
+    }
+
+    /// 저장소에 기록된 블록을 체인 끝에 연결하고 nonce와 목표값을 갱신합니다.
+    fn push_block(&mut self, new_block: Block) {
+        self.events.publish(ChainEvent::BlockConnected(Arc::new(new_block.clone())));
+        self.index.connect_block(&new_block);
         self.chain.push(new_block);
-        println!("New block added: {:?}", self.get_latest_block());
+        self.record_nonces(self.chain.len() - 1);
+        self.bits = self.next_bits();
+        info!("New block added: {:?}", self.get_latest_block().map(|block| (block.index, &block.hash)));
+    }
+
+    /// 블록 연결과 트랜잭션 추가 이벤트를 받을 수신자를 만듭니다.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ChainEvent> {
+        self.events.subscribe()
+    }
+
+    /// 다음에 추가될 블록이 사용해야 하는 목표값(compact 표현)을 계산합니다.
+    pub fn next_bits(&self) -> u32 {
+        self.difficulty_params.expected_bits(&self.chain)
+    }
+
+    /// 체인에 연결된 블록의 트랜잭션 nonce를 계정별 다음 nonce에 반영합니다.
+    fn record_nonces(&mut self, block_index: usize) {
+        for tx in self.chain[block_index].transactions.iter() {
+            if !tx.is_coinbase() {
+                self.account_nonces.insert(tx.sender.clone(), tx.nonce + 1);
+            }
+        }
+    }
+
+    /// 이 체인의 네트워크. 알려지지 않은 chain_id를 쓰는 체인이면 None입니다.
+    pub fn network(&self) -> Option<Network> {
+        Network::from_chain_id(self.chain_id)
+    }
+
+    /// 주소 문자열을 해석하고 이 체인의 네트워크 주소인지 확인합니다.
+    pub fn parse_address(&self, address: &str) -> Result<Address, BlockchainError> {
+        let network = self.network()
+            .ok_or_else(|| BlockchainError::WrongNetwork { address: address.to_string(), chain_id: self.chain_id })?;
+        Address::parse_for_network(address, network)
+    }
+
+    /// 주소가 다음 트랜잭션에 사용해야 할 nonce를 반환합니다.
+    /// 확정된 블록과 대기열의 트랜잭션을 모두 고려합니다.
+    pub fn get_next_nonce(&self, address: &str) -> u64 {
+        let confirmed = self.account_nonces.get(address).copied().unwrap_or(0);
+        let pending = self.pending_transactions.iter()
+            .filter(|tx| tx.sender == address)
+            .count() as u64;
+        confirmed + pending
     }
 
-    pub fn add_transaction(&mut self, transaction: Transaction) {
-        // 트랜잭션 유효성 검사 (is_valid()는 transaction.rs에 정의되어야 함)
-        if !transaction.is_valid() {
-            println!("Error: Invalid transaction added to pending pool.");
-            return;
+    /// 트랜잭션을 대기열에 추가합니다. 거부되면 그 이유를 반환합니다.
+    /// 대기열에 이미 있는 트랜잭션이 사용한 자금을 다시 사용하는 트랜잭션은 거부됩니다.
+    pub fn add_transaction(&mut self, transaction: Transaction) -> Result<(), BlockchainError> {
+        self.admit_transaction(transaction)?;
+        if let Some(tx) = self.pending_transactions.last() {
+            self.events.publish(ChainEvent::TransactionAccepted(Arc::new(tx.clone())));
         }
+        Ok(())
+    }
+
+    /// 트랜잭션을 검사하여 대기열에 넣습니다. 이벤트는 발행하지 않습니다.
+    /// (체인이 바뀐 뒤 기존 대기열을 다시 검사할 때 사용)
+    fn admit_transaction(&mut self, transaction: Transaction) -> Result<(), BlockchainError> {
+        // 코인베이스는 채굴자가 블록을 만들 때만 생성하므로, 형식과 관계없이 대기열에서는 거부합니다.
+        if transaction.is_coinbase() || transaction.coinbase_height.is_some() {
+            return Err(BlockchainError::CoinbaseNotAllowed);
+        }
+        transaction.validate()?;
+        if transaction.chain_id != self.chain_id {
+            return Err(BlockchainError::WrongChainId { expected: self.chain_id, actual: transaction.chain_id });
+        }
+        // 이미 사용된 nonce(재전송)나 건너뛴 nonce(순서 어긋남)는 거부합니다.
+        let expected_nonce = self.get_next_nonce(&transaction.sender);
+        if transaction.nonce != expected_nonce {
+            return Err(BlockchainError::InvalidNonce { expected: expected_nonce, actual: transaction.nonce });
+        }
+
+        match self.ledger_mode {
+            LedgerMode::Account => self.check_account_transaction(&transaction)?,
+            LedgerMode::Utxo => self.check_utxo_transaction(&transaction)?,
+        }
+        let tx_size = transaction.size();
+        self.check_mempool_limits(tx_size)?;
+
+        self.pending_bytes += tx_size;
+        self.pending_spent.extend(transaction.inputs.iter().cloned());
         self.pending_transactions.push(transaction);
-        println!("Transaction added to pending pool: {:?}", self.pending_transactions.last());
+        debug!("Transaction added to pending pool: {:?}", self.pending_transactions.last());
+        Ok(())
     }
 
-    pub fn mine_pending_transactions(&mut self, miner_address: String) {
-        if self.pending_transactions.is_empty() {
-            println!("No pending transactions to mine.");
-            return;
+    /// 크기가 `tx_size`인 트랜잭션을 추가해도 대기열이 크기 제한을 넘지 않는지 확인합니다.
+    fn check_mempool_limits(&self, tx_size: usize) -> Result<(), BlockchainError> {
+        let limits = &self.mempool_limits;
+        if self.pending_transactions.len() >= limits.max_transactions {
+            return Err(BlockchainError::MempoolFull(format!("{} transactions", limits.max_transactions)));
+        }
+        if self.pending_bytes.saturating_add(tx_size) > limits.max_bytes {
+            return Err(BlockchainError::MempoolFull(format!("{} bytes", limits.max_bytes)));
         }
+        Ok(())
+    }
 
-        // 채굴 보상 트랜잭션 생성
-        let reward_tx = Transaction::new("coinbase_reward".to_string(), miner_address, self.mining_reward);
-        
-        // 보상 트랜잭션을 포함하여 모든 대기 중인 트랜잭션을 블록에 추가
-        let mut transactions_to_mine = vec![reward_tx];
-        transactions_to_mine.extend(self.pending_transactions.drain(..)); // pending_transactions 비움
+    /// 계정 모드: 확정 잔액에서 대기열의 출금액을 뺀 금액으로 지불할 수 있는지 확인합니다.
+    fn check_account_transaction(&self, transaction: &Transaction) -> Result<(), BlockchainError> {
+        if transaction.is_utxo() {
+            return Err(BlockchainError::WrongLedgerMode(
+                "UTXO transactions are not accepted in account mode".to_string(),
+            ));
+        }
+        let pending_spent = self.pending_transactions.iter()
+            .filter(|tx| tx.sender == transaction.sender)
+            .fold(0u64, |acc, tx| acc.saturating_add(tx.total_cost()));
+        let available = self.get_balance_of_address(&transaction.sender).saturating_sub(pending_spent);
+        if available < transaction.total_cost() {
+            return Err(BlockchainError::InsufficientFunds { available, required: transaction.total_cost() });
+        }
+        Ok(())
+    }
+
+    /// UTXO 모드: 대기열의 트랜잭션이 이미 소비한 출력을 제외하고 입력을 검사합니다.
+    fn check_utxo_transaction(&self, transaction: &Transaction) -> Result<(), BlockchainError> {
+        self.index.utxo_set().check_transaction_with_spent(transaction, &self.pending_spent)
+    }
 
+    /// 현재 체인의 미사용 출력 집합을 반환합니다.
+    pub fn utxo_set(&self) -> UtxoSet {
+        self.index.utxo_set().clone()
+    }
+
+    /// 조회용 색인과 대기열의 크기 합계, 소비된 출력 목록을 다시 만듭니다.
+    /// `chain`이나 `pending_transactions`를 직접 바꾸었거나 역직렬화한 Blockchain을 사용하기 전에 호출합니다.
+    pub fn rebuild_index(&mut self) {
+        *self.index = ChainIndex::from_blocks(&self.chain);
+        self.pending_bytes = self.pending_transactions.iter().map(Transaction::size).sum();
+        self.pending_spent = self.pending_transactions.iter().flat_map(|tx| tx.inputs.iter().cloned()).collect();
+    }
+
+    /// 대기열 트랜잭션의 인코딩 크기 합계 (바이트)
+    pub fn pending_bytes(&self) -> usize {
+        self.pending_bytes
+    }
+
+    /// 트랜잭션 ID로 트랜잭션을 찾습니다. 메인 체인과 대기열을 모두 찾아봅니다.
+    pub fn get_transaction(&self, txid: &str) -> Option<TransactionInfo> {
+        if let Some(location) = self.index.locate_transaction(txid) {
+            let block = self.chain.get(location.block_index as usize)?;
+            let transaction = block.transactions.get(location.position)?.clone();
+            return Some(TransactionInfo { transaction, location: Some(location) });
+        }
+        self.pending_transactions.iter()
+            .find(|tx| tx.txid() == txid)
+            .map(|tx| TransactionInfo { transaction: tx.clone(), location: None })
+    }
+
+    /// 주소가 관련된 트랜잭션 기록을 최신 것부터 `skip`개 건너뛰고 최대 `limit`개 반환합니다.
+    /// 반환값: (전체 기록 수, 트랜잭션 목록)
+    pub fn get_address_history(&self, address: &str, skip: usize, limit: usize) -> (usize, Vec<TransactionInfo>) {
+        let history = self.index.address_history(address);
+        let transactions = history.iter()
+            .rev()
+            .skip(skip)
+            .take(limit)
+            .filter_map(|location| {
+                let block = self.chain.get(location.block_index as usize)?;
+                let transaction = block.transactions.get(location.position)?.clone();
+                Some(TransactionInfo { transaction, location: Some(*location) })
+            })
+            .collect();
+        (history.len(), transactions)
+    }
+
+    /// 대기 중인 트랜잭션과 채굴 보상으로 새 블록을 채굴하여 체인에 연결합니다.
+    /// 잠금을 잡은 채로 채굴하므로, 서버에서는 잠금 없이 채굴하는 `miner::mine_block`을 사용합니다.
+    pub fn mine_pending_transactions(&mut self, miner_address: String) -> Result<(), BlockchainError> {
+        let mut new_block = self.block_template(&miner_address)?;
+        new_block.mine_block(new_block.bits)?;
+        self.submit_block(new_block)
+    }
+
+    /// 현재 tip 위에 채굴할 블록 템플릿을 만듭니다. (작업 증명 전이며 대기열은 바꾸지 않습니다)
+    /// 수수료율이 높은 트랜잭션부터 블록 크기 제한까지 담고, 맨 앞에 보상 트랜잭션을 둡니다.
+    /// 대기열이 비어 있어도 보상 트랜잭션만 담은 블록을 만들어, 새 체인에서도 코인을 발행할 수 있습니다.
+    pub fn block_template(&self, miner_address: &str) -> Result<Block, BlockchainError> {
+        // 보상을 받을 주소가 이 네트워크의 주소인지 먼저 확인합니다.
+        self.parse_address(miner_address)?;
         let new_block_index = self.chain.len() as u64;
-        let latest_block_hash = self.get_latest_block().unwrap().hash.clone();
 
-        let mut new_block = Block::new(new_block_index, latest_block_hash, transactions_to_mine);
-        self.add_block(new_block);
+        // 코인베이스가 차지할 공간을 먼저 확보한 뒤 트랜잭션을 고릅니다.
+        // 수수료가 더해지면 금액의 자릿수가 늘어날 수 있으므로 최대 금액 기준으로 크기를 잡습니다.
+        let reserved_size = Transaction::new_coinbase(miner_address.to_string(), u64::MAX, new_block_index).size();
+        let size_limit = self.max_block_size.saturating_sub(reserved_size);
+        let (selected, _) = self.select_transactions(size_limit);
+
+        // 채굴 보상 트랜잭션 생성 (이 높이의 보조금 + 선택된 트랜잭션의 수수료)
+        let fees = selected.iter().fold(0u64, |acc, tx| acc.saturating_add(tx.fee));
+        let reward = self.block_subsidy(new_block_index).saturating_add(fees);
+        let reward_tx = Transaction::new_coinbase(miner_address.to_string(), reward, new_block_index);
+
+        // 보상 트랜잭션을 첫 번째로 두고 선택된 트랜잭션을 블록에 추가
+        let mut transactions_to_mine = vec![reward_tx];
+        transactions_to_mine.extend(selected);
+
+        let latest_block_hash = self.get_latest_block().ok_or(BlockchainError::EmptyChain)?.hash.clone();
+        let mut template = Block::new(new_block_index, latest_block_hash, transactions_to_mine);
+        template.bits = self.next_bits();
+        Ok(template)
+    }
+
+    /// 대기열에서 블록에 담을 트랜잭션을 수수료율(수수료 / 크기) 순으로 고릅니다.
+    /// 같은 송신자의 트랜잭션은 nonce 순서대로만 담을 수 있으므로, 송신자별 대기열의 맨 앞 트랜잭션끼리 비교합니다.
+    /// 반환값: (선택된 트랜잭션, 대기열에 남을 트랜잭션)
+    fn select_transactions(&self, size_limit: usize) -> (Vec<Transaction>, Vec<Transaction>) {
+        // 송신자별로 대기열 순서(= nonce 순서)를 유지하며 나눕니다. 크기는 여기서 한 번만 계산합니다.
+        let mut queues: Vec<VecDeque<(&Transaction, usize)>> = Vec::new();
+        let mut queue_of_sender: HashMap<&str, usize> = HashMap::new();
+        for tx in self.pending_transactions.iter() {
+            let queue_index = *queue_of_sender.entry(tx.sender.as_str()).or_insert_with(|| {
+                queues.push(VecDeque::new());
+                queues.len() - 1
+            });
+            queues[queue_index].push_back((tx, tx.size()));
+        }
+
+        let mut selected_ids = HashSet::new();
+        let mut selected = Vec::new();
+        let mut used_size = 0usize;
+        loop {
+            // 각 송신자 대기열의 맨 앞 트랜잭션 중 수수료율이 가장 높은 것을 고릅니다.
+            let best = queues.iter()
+                .enumerate()
+                .filter_map(|(queue_index, queue)| queue.front().map(|&(tx, tx_size)| (queue_index, tx, tx_size)))
+                .max_by(|(_, a, a_size), (_, b, b_size)| {
+                    // a.fee / a_size 와 b.fee / b_size 비교 (나눗셈 없이)
+                    (a.fee as u128 * *b_size as u128).cmp(&(b.fee as u128 * *a_size as u128))
+                });
+            let (queue_index, tx, tx_size) = match best {
+                Some(best) => best,
+                None => break,
+            };
+            if used_size + tx_size > size_limit {
+                // 이 트랜잭션이 들어가지 않으면 같은 송신자의 이후 트랜잭션도 담을 수 없습니다.
+                queues[queue_index].clear();
+                continue;
+            }
+            used_size += tx_size;
+            selected_ids.insert(tx.txid());
+            selected.push(tx.clone());
+            queues[queue_index].pop_front();
+        }
+
+        let remaining = self.pending_transactions.iter()
+            .filter(|tx| !selected_ids.contains(&tx.txid()))
+            .cloned()
+            .collect();
+        (selected, remaining)
     }
 
     pub fn get_latest_block(&self) -> Option<&Block> {
         self.chain.last()
     }
 
-    /// 주어진 주소의 잔액을 계산합니다.
-    /// 트랜잭션 금액은 u64 타입으로 가정하며, saturating_sub/saturating_add를 사용합니다.
+    /// 주어진 주소의 잔액을 반환합니다. (색인에 저장된 값)
+    /// UTXO 모드에서는 주소가 소유한 미사용 출력의 합을 반환합니다.
     pub fn get_balance_of_address(&self, address: &str) -> u64 {
-        let mut balance: u64 = 0; // 잔액을 u64로 명시적으로 선언
-
-        for block in self.chain.iter() {
-            for tx in block.transactions.iter() {
-                // tx.amount를 u64로 명시적 캐스팅하여 타입 불일치 오류 방지
-                // 만약 tx.amount가 이미 u64라면 이 캐스팅은 최적화됩니다.
-                let amount_u64 = tx.amount as u64;
-
-                // 코인베이스 보상 트랜잭션은 송신자가 없으므로 잔액에서 빼지 않습니다.
-                // 이 부분은 transaction.rs의 is_valid 로직과 일관성을 유지해야 합니다.
-                if tx.sender == address && tx.sender != "coinbase_reward" {
-                    // 잔액에서 송금액을 안전하게 뺍니다 (언더플로우 방지)
-                    balance = balance.saturating_sub(amount_u64);
-                }
-                if tx.recipient == address {
-                    // 잔액에 수금액을 안전하게 더합니다 (오버플로우 방지)
-                    balance = balance.saturating_add(amount_u64);
-                }
-            }
+        match self.ledger_mode {
+            LedgerMode::Account => self.index.account_balance(address),
+            LedgerMode::Utxo => self.index.utxo_balance(address),
         }
-        balance
     }
 
-    /// 블록체인의 유효성을 검사합니다.
+    /// 블록체인의 유효성을 검사합니다. 유효하지 않으면 처음 발견된 문제를 반환합니다.
     /// 각 블록의 해시와 이전 블록 해시가 올바른지, 난이도 조건을 충족하는지,
     /// 그리고 블록 내 모든 트랜잭션이 유효한지 확인합니다.
-    pub fn is_chain_valid(&self) -> bool {
+    pub fn validate_chain(&self) -> Result<(), BlockchainError> {
+        // 블록을 순서대로 적용하며 이중 지불과 잔액 초과 지출을 확인합니다.
+        let mut state = LedgerState::default();
+        let genesis_block = self.chain.first().ok_or(BlockchainError::EmptyChain)?;
+        state.apply_block(genesis_block);
+
         // 제네시스 블록은 이전 블록이 없으므로 인덱스 1부터 시작
         for i in 1..self.chain.len() {
-            let current_block = &self.chain[i];
-            let previous_block = &self.chain[i - 1];
-
-            // 1. 현재 블록의 해시가 올바르게 계산되었는지 확인
-            if current_block.hash != current_block.calculate_hash() {
-                println!("Invalid block hash at index {}. Expected: {}, Got: {}",
-                         i, current_block.calculate_hash(), current_block.hash);
-                return false;
-            }
-
-            // 2. 현재 블록의 previous_hash가 이전 블록의 실제 해시와 일치하는지 확인
-            if current_block.previous_hash != previous_block.hash {
-                println!("Invalid previous hash at index {}. Expected: {}, Got: {}",
-                         i, previous_block.hash, current_block.previous_hash);
-                return false;
-            }
-
-            // 3. 현재 블록의 해시가 난이도 조건을 충족하는지 확인
-            let target_prefix = "0".repeat(self.difficulty);
-            if !current_block.hash.starts_with(&target_prefix) {
-                println!("Block {} does not meet difficulty requirement. Hash: {}", i, current_block.hash);
-                return false;
-            }
-
-            // 4. 블록 내 모든 트랜잭션이 유효한지 확인
-            for tx in current_block.transactions.iter() {
-                if !tx.is_valid() {
-                    // 유효하지 않은 트랜잭션이 발견되면 해당 블록과 트랜잭션의 위치를 출력
-                    // calculate_hash_for_signing()을 사용하여 트랜잭션의 고유 식별자를 얻습니다.
-                    let tx_index = current_block.transactions.iter().position(|t| t.calculate_hash_for_signing() == tx.calculate_hash_for_signing()).unwrap_or(0);
-                    println!("Invalid transaction in block {} at index {}. Transaction: {:?}", i, tx_index, tx);
-                    return false;
+            self.validate_block(&self.chain[..i], &self.chain[i], &mut state)?;
+        }
+        Ok(()) // 모든 검사를 통과하면 유효
+    }
+
+    /// `previous_blocks` 바로 뒤에 연결될 블록의 유효성을 검사합니다.
+    /// 검사에 성공하면 블록의 트랜잭션이 `state`에 반영됩니다.
+    fn validate_block<'a>(
+        &self,
+        previous_blocks: &[Block],
+        current_block: &'a Block,
+        state: &mut LedgerState<'a>,
+    ) -> Result<(), BlockchainError> {
+        let previous_block = previous_blocks.last().ok_or(BlockchainError::EmptyChain)?;
+        let index = previous_blocks.len() as u64;
+
+        if current_block.index != index {
+            return Err(BlockchainError::InvalidBlockIndex { expected: index, actual: current_block.index });
+        }
+
+        // 1. 현재 블록의 해시가 올바르게 계산되었는지 확인
+        if current_block.hash != current_block.calculate_hash() {
+            return Err(BlockchainError::InvalidBlockHash { index });
+        }
+        if !current_block.has_valid_merkle_root() {
+            return Err(BlockchainError::InvalidMerkleRoot { index });
+        }
+
+        // 2. 현재 블록의 previous_hash가 이전 블록의 실제 해시와 일치하는지 확인
+        if current_block.previous_hash != previous_block.hash {
+            return Err(BlockchainError::BrokenLink { index });
+        }
+
+        // 3. 현재 블록이 해당 높이에서 요구되는 목표값을 사용했고, 해시 다이제스트가 목표값 이하인지 확인
+        let expected_bits = self.difficulty_params.expected_bits(previous_blocks);
+        if current_block.bits != expected_bits {
+            return Err(BlockchainError::WrongTarget { index, expected: expected_bits, actual: current_block.bits });
+        }
+        if !current_block.has_valid_proof_of_work() {
+            return Err(BlockchainError::InsufficientWork { index });
+        }
+
+        // 타임스탬프는 난이도 조정에 사용되므로 이전 블록보다 앞서거나 너무 먼 미래일 수 없습니다.
+        if current_block.timestamp < previous_block.timestamp
+            || current_block.timestamp > Utc::now().timestamp() + MAX_FUTURE_BLOCK_TIME
+        {
+            return Err(BlockchainError::InvalidTimestamp { index, timestamp: current_block.timestamp });
+        }
+
+        // 블록에 담긴 트랜잭션의 총 크기가 제한을 넘지 않는지 확인
+        let block_size: usize = current_block.transactions.iter().map(|tx| tx.size()).sum();
+        if block_size > self.max_block_size {
+            return Err(BlockchainError::BlockTooLarge { index, size: block_size, limit: self.max_block_size });
+        }
+
+        // 블록 안의 트랜잭션 오류에는 블록과 트랜잭션의 위치를 함께 기록합니다.
+        let invalid_transaction = |position: usize, reason: BlockchainError| {
+            BlockchainError::InvalidBlockTransaction { index, position, reason: Box::new(reason) }
+        };
+
+        // 4. 코인베이스는 블록의 첫 번째 트랜잭션 하나뿐이며, 이 블록의 높이에 묶여 있는지 확인
+        //    (높이가 해시에 포함되므로 같은 채굴자가 같은 금액을 받아도 코인베이스의 txid가 겹치지 않습니다)
+        match current_block.transactions.first() {
+            Some(coinbase) if coinbase.is_coinbase() => {
+                if coinbase.coinbase_height != Some(index) {
+                    let bound = coinbase.coinbase_height.map_or("no height".to_string(), |height| format!("height {}", height));
+                    let reason = format!("coinbase is bound to {}, expected {}", bound, index);
+                    return Err(invalid_transaction(0, BlockchainError::InvalidCoinbase(reason)));
                 }
             }
+            _ => return Err(BlockchainError::MissingCoinbase { index }),
+        }
+        if let Some(position) = current_block.transactions.iter().skip(1).position(Transaction::is_coinbase) {
+            let reason = BlockchainError::InvalidCoinbase("only the first transaction may be a coinbase".to_string());
+            return Err(invalid_transaction(position + 1, reason));
+        }
+
+        // 5. 블록 내 모든 트랜잭션이 유효한지 확인
+        for (position, tx) in current_block.transactions.iter().enumerate() {
+            tx.validate().map_err(|e| invalid_transaction(position, e))?;
+        }
+
+        // 6. 트랜잭션이 현재 네트워크용이며, 송신자별 nonce가 순서대로 사용되었는지 확인
+        for (position, tx) in current_block.transactions.iter().enumerate() {
+            // 코인베이스는 chain_id가 없으므로 채굴자 주소의 네트워크를 확인합니다.
+            if tx.is_coinbase() {
+                self.parse_address(&tx.recipient).map_err(|e| invalid_transaction(position, e))?;
+                continue;
+            }
+            if tx.chain_id != self.chain_id {
+                let reason = BlockchainError::WrongChainId { expected: self.chain_id, actual: tx.chain_id };
+                return Err(invalid_transaction(position, reason));
+            }
+            let expected_nonce = state.nonces.get(tx.sender.as_str()).copied().unwrap_or(0);
+            if tx.nonce != expected_nonce {
+                let reason = BlockchainError::InvalidNonce { expected: expected_nonce, actual: tx.nonce };
+                return Err(invalid_transaction(position, reason));
+            }
+            state.nonces.insert(tx.sender.as_str(), expected_nonce + 1);
+        }
+
+        // 7. 잔액 모델에 따라 트랜잭션이 사용 가능한 자금만 소비하는지 확인
+        for (position, tx) in current_block.transactions.iter().enumerate() {
+            match self.ledger_mode {
+                LedgerMode::Account => state.apply_account_transaction(tx),
+                LedgerMode::Utxo => state.utxo_set.apply_transaction(tx),
+            }
+            .map_err(|e| invalid_transaction(position, e))?;
+        }
+
+        // 8. 코인베이스가 이 높이의 보조금과 블록에 담긴 트랜잭션 수수료의 합보다 많이 지급하지 않는지 확인
+        let (claimed, fees) = current_block.transactions.iter().fold((0u64, 0u64), |(claimed, fees), tx| {
+            if tx.is_coinbase() {
+                (claimed.saturating_add(tx.amount), fees)
+            } else {
+                (claimed, fees.saturating_add(tx.fee))
+            }
+        });
+        let allowed = self.block_subsidy(index).saturating_add(fees);
+        if claimed > allowed {
+            return Err(BlockchainError::ExcessiveCoinbase { index, claimed, allowed });
+        }
+        Ok(())
+    }
+
+    /// 메인 체인의 처음 `height`개 블록을 적용한 장부 상태를 만듭니다. (검증 없이 재생)
+    fn ledger_state(&self, height: usize) -> LedgerState<'_> {
+        let mut state = LedgerState::default();
+        for block in self.chain[..height].iter() {
+            state.apply_block(block);
+        }
+        state
+    }
+}
+
+/// 블록 검증 중 누적되는 장부 상태 (계정 잔액, 미사용 출력, 계정별 다음 nonce)
+#[derive(Default)]
+struct LedgerState<'a> {
+    utxo_set: UtxoSet,
+    balances: HashMap<&'a str, u64>,
+    nonces: HashMap<&'a str, u64>,
+}
+
+impl<'a> LedgerState<'a> {
+    /// 이미 검증된 블록의 트랜잭션을 상태에 반영합니다.
+    fn apply_block(&mut self, block: &'a Block) {
+        for tx in block.transactions.iter() {
+            let _ = self.utxo_set.apply_transaction(tx);
+            let _ = self.apply_account_transaction(tx);
+            if !tx.is_coinbase() {
+                self.nonces.insert(tx.sender.as_str(), tx.nonce + 1);
+            }
+        }
+    }
+
+    /// 계정 모드 잔액에 트랜잭션을 적용합니다. 송신자 잔액이 부족하면 오류를 반환합니다.
+    fn apply_account_transaction(&mut self, tx: &'a Transaction) -> Result<(), BlockchainError> {
+        if tx.is_utxo() {
+            return Err(BlockchainError::WrongLedgerMode(
+                "UTXO transactions are not accepted in account mode".to_string(),
+            ));
+        }
+        if !tx.is_coinbase() {
+            let sender_balance = self.balances.entry(tx.sender.as_str()).or_insert(0);
+            let required = tx.total_cost();
+            *sender_balance = sender_balance.checked_sub(required)
+                .ok_or(BlockchainError::InsufficientFunds { available: *sender_balance, required })?;
+        }
+        let recipient_balance = self.balances.entry(tx.recipient.as_str()).or_insert(0);
+        *recipient_balance = recipient_balance.saturating_add(tx.amount);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_params::GenesisAllocation;
+    use crate::index::TxLocation;
+    use crate::transaction::TxOutput;
+    use crate::wallet::Wallet;
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // 블록을 메모리에만 보관하는 저장소. `failing_appends`만큼 다음 기록이 실패합니다.
+    #[derive(Debug, Default)]
+    struct MemoryStore {
+        blocks: Arc<Mutex<Vec<Block>>>,
+        failing_appends: Arc<AtomicUsize>,
+    }
+
+    impl ChainStore for MemoryStore {
+        fn load_blocks(&mut self) -> io::Result<Vec<Block>> {
+            Ok(self.blocks.lock().unwrap().clone())
+        }
+
+        fn append_block(&mut self, block: &Block) -> io::Result<()> {
+            if self.failing_appends.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1)).is_ok() {
+                return Err(io::Error::other("disk full"));
+            }
+            self.blocks.lock().unwrap().push(block.clone());
+            Ok(())
+        }
+
+        fn truncate_blocks(&mut self, height: u64) -> io::Result<()> {
+            self.blocks.lock().unwrap().truncate(height as usize);
+            Ok(())
+        }
+    }
+
+    fn regtest_chain(ledger_mode: LedgerMode) -> Blockchain {
+        chain_with_params(ChainParams::regtest(), ledger_mode)
+    }
+
+    fn chain_with_params(params: ChainParams, ledger_mode: LedgerMode) -> Blockchain {
+        let settings = ChainSettings { params, ledger_mode, ..Default::default() };
+        Blockchain::open(settings, MemoryStore::default()).unwrap()
+    }
+
+    fn regtest_wallet() -> Wallet {
+        Wallet::new_for_network(Network::Regtest)
+    }
+
+    // 대기열의 트랜잭션과 보상으로 블록을 채굴하고 연결된 블록을 반환합니다.
+    fn mine(chain: &mut Blockchain, miner: &Wallet) -> Block {
+        chain.mine_pending_transactions(miner.address.clone()).unwrap();
+        chain.get_latest_block().unwrap().clone()
+    }
+
+    // `from` 지갑이 서명한 계정 모드 송금 트랜잭션 (다음 nonce 사용)
+    fn transfer(chain: &Blockchain, from: &Wallet, to: &Wallet, amount: u64, fee: u64) -> Transaction {
+        let mut tx = Transaction::new(from.address.clone(), to.address.clone(), amount);
+        tx.fee = fee;
+        tx.nonce = chain.get_next_nonce(&from.address);
+        tx.chain_id = chain.chain_id;
+        tx.sign(&from.to_signing_key().unwrap(), from.public_key_hex.clone());
+        tx
+    }
+
+    // `parent` 뒤에 연결될 코인베이스만 담은 블록을 채굴합니다. (검증을 거치지 않음)
+    fn mine_on(parent: &Block, index: u64, miner: &Wallet) -> Block {
+        let coinbase = Transaction::new_coinbase(miner.address.clone(), DEFAULT_MINING_REWARD, index);
+        let mut block = Block::new(index, parent.hash.clone(), vec![coinbase]);
+        block.timestamp = parent.timestamp + 1;
+        block.mine_block(parent.bits).unwrap();
+        block
+    }
+
+    #[test]
+    fn supply_info_counts_allocations_and_stops_at_the_cap() {
+        let (alice, miner) = (regtest_wallet(), regtest_wallet());
+        let mut params = ChainParams::regtest();
+        params.allocations.push(GenesisAllocation { address: alice.address.clone(), amount: 1_000 });
+        params.reward.max_supply = 1_000 + 2 * DEFAULT_MINING_REWARD + 40;
+        params.genesis_nonce = params.mine_genesis_nonce().unwrap();
+        let mut chain = chain_with_params(params, LedgerMode::Account);
+
+        let info = chain.supply_info();
+        assert_eq!((info.height, info.total_supply, info.max_supply), (0, 1_000, 1_240));
+        assert_eq!(info.next_block_subsidy, DEFAULT_MINING_REWARD);
+        assert_eq!(info.next_halving_height, Some(150));
+
+        for _ in 0..3 {
+            mine(&mut chain, &miner);
+        }
+        assert_eq!(chain.get_balance_of_address(&miner.address), 240);
+        let info = chain.supply_info();
+        assert_eq!((info.height, info.total_supply), (3, 1_240));
+        assert_eq!(info.next_block_subsidy, 0);
+        assert_eq!(info.next_halving_height, None);
+    }
+
+    #[test]
+    fn transaction_lookup_and_address_history_pages() {
+        let (alice, bob) = (regtest_wallet(), regtest_wallet());
+        let mut chain = regtest_chain(LedgerMode::Account);
+        mine(&mut chain, &alice);
+        let mut sent = Vec::new();
+        for amount in 1..=4 {
+            let tx = transfer(&chain, &alice, &bob, amount, 0);
+            chain.add_transaction(tx.clone()).unwrap();
+            sent.push(tx.txid());
+            if amount % 2 == 0 {
+                mine(&mut chain, &bob);
+            }
         }
-        true // 모든 검사를 통과하면 유효
+        let pending = transfer(&chain, &alice, &bob, 5, 0);
+        chain.add_transaction(pending.clone()).unwrap();
+
+        let info = chain.get_transaction(&sent[2]).unwrap();
+        assert_eq!(info.location, Some(TxLocation { block_index: 3, position: 1 }));
+        assert_eq!(chain.get_transaction(&pending.txid()).unwrap().location, None);
+        assert!(chain.get_transaction("missing").is_none());
+
+        // alice의 기록: 코인베이스 1개 + 확정된 송금 4개 (대기열은 제외), 최신 것부터
+        let (total, page) = chain.get_address_history(&alice.address, 0, 2);
+        assert_eq!(total, 5);
+        let ids: Vec<String> = page.iter().map(|info| info.transaction.txid()).collect();
+        assert_eq!(ids, vec![sent[3].clone(), sent[2].clone()]);
+        let (_, page) = chain.get_address_history(&alice.address, 4, 2);
+        assert_eq!(page.len(), 1);
+        assert!(page[0].transaction.is_coinbase());
+        assert!(chain.get_address_history(&alice.address, 5, 2).1.is_empty());
+        assert!(chain.get_address_history(&alice.address, 0, 0).1.is_empty());
+        // bob은 송금 4개와 코인베이스 2개
+        assert_eq!(chain.get_address_history(&bob.address, 0, usize::MAX).1.len(), 6);
+    }
+
+    #[test]
+    fn opening_another_networks_blocks_is_refused() {
+        let store = MemoryStore::default();
+        let stored = store.blocks.clone();
+        let settings = ChainSettings { params: ChainParams::regtest(), ..Default::default() };
+        let mut chain = Blockchain::open(settings.clone(), store).unwrap();
+        mine(&mut chain, &regtest_wallet());
+        let reopened = Blockchain::open(settings, MemoryStore { blocks: stored.clone(), ..Default::default() }).unwrap();
+        assert_eq!(reopened.chain.len(), 2);
+
+        let testnet = ChainSettings { params: ChainParams::testnet(), ..Default::default() };
+        let result = Blockchain::open(testnet, MemoryStore { blocks: stored, ..Default::default() });
+        assert!(matches!(result, Err(BlockchainError::GenesisMismatch { .. })));
+    }
+
+    #[test]
+    fn side_block_with_wrong_index_is_rejected() {
+        let miner = regtest_wallet();
+        let mut chain = regtest_chain(LedgerMode::Account);
+        let genesis = chain.chain[0].clone();
+        mine(&mut chain, &miner);
+        mine(&mut chain, &miner);
+
+        // 인덱스를 u64 끝 근처로 속인 곁가지 블록도 오버플로 없이 거부됩니다.
+        for index in [u64::MAX, u64::MAX - MAX_REORG_DEPTH / 2, 5] {
+            let block = mine_on(&genesis, index, &miner);
+            assert_eq!(chain.accept_block(block), BlockAcceptance::Rejected);
+        }
+        assert_eq!(chain.side_blocks.len(), 0);
+        mine(&mut chain, &miner);
+        assert_eq!(chain.chain.len(), 4);
+    }
+
+    #[test]
+    fn side_block_must_use_expected_target() {
+        // 초기 목표값이 가장 쉬운 목표값보다 어려운 체인
+        let mut params = ChainParams::regtest();
+        params.difficulty.initial_bits = 0x2000_ffff;
+        params.genesis_nonce = params.mine_genesis_nonce().unwrap();
+        let miner = regtest_wallet();
+        let mut chain = chain_with_params(params.clone(), LedgerMode::Account);
+        let genesis = chain.chain[0].clone();
+        mine(&mut chain, &miner);
+        mine(&mut chain, &miner);
+
+        // 가장 쉬운 목표값으로 만든 곁가지 블록은 작업 증명이 맞아도 거부됩니다.
+        let mut cheap = mine_on(&genesis, 1, &miner);
+        cheap.mine_block(params.difficulty.pow_limit_bits).unwrap();
+        assert!(cheap.has_valid_proof_of_work());
+        assert_eq!(chain.accept_block(cheap), BlockAcceptance::Rejected);
+
+        let honest = mine_on(&genesis, 1, &miner);
+        assert_eq!(chain.accept_block(honest), BlockAcceptance::SideBranch);
+    }
+
+    #[test]
+    fn side_blocks_per_height_are_capped() {
+        let miner = regtest_wallet();
+        let mut chain = regtest_chain(LedgerMode::Account);
+        let genesis = chain.chain[0].clone();
+        mine(&mut chain, &miner);
+        mine(&mut chain, &miner);
+
+        for n in 0..MAX_SIDE_BLOCKS_PER_HEIGHT {
+            let block = mine_on(&genesis, 1, &regtest_wallet());
+            assert_eq!(chain.accept_block(block), BlockAcceptance::SideBranch, "side block {}", n);
+        }
+        let extra = mine_on(&genesis, 1, &regtest_wallet());
+        assert_eq!(chain.accept_block(extra), BlockAcceptance::Rejected);
+        assert_eq!(chain.side_blocks.len(), MAX_SIDE_BLOCKS_PER_HEIGHT);
+    }
+
+    #[test]
+    fn failed_reorganization_restores_the_original_chain() {
+        let store = MemoryStore::default();
+        let (stored, failing_appends) = (store.blocks.clone(), store.failing_appends.clone());
+        let settings = ChainSettings { params: ChainParams::regtest(), ..Default::default() };
+        let mut chain = Blockchain::open(settings, store).unwrap();
+        let mut rival = regtest_chain(LedgerMode::Account);
+        let (miner, rival_miner) = (regtest_wallet(), regtest_wallet());
+        mine(&mut chain, &miner);
+        mine(&mut chain, &miner);
+        let rival_blocks: Vec<Block> = (0..4).map(|_| mine(&mut rival, &rival_miner)).collect();
+        let original: Vec<String> = chain.chain.iter().map(|block| block.hash.clone()).collect();
+
+        assert_eq!(chain.accept_block(rival_blocks[0].clone()), BlockAcceptance::SideBranch);
+        assert_eq!(chain.accept_block(rival_blocks[1].clone()), BlockAcceptance::SideBranch);
+        // 곁가지 첫 블록을 기록할 때 저장소가 실패합니다.
+        failing_appends.store(1, Ordering::Relaxed);
+        assert_eq!(chain.accept_block(rival_blocks[2].clone()), BlockAcceptance::Rejected);
+
+        let restored: Vec<String> = chain.chain.iter().map(|block| block.hash.clone()).collect();
+        assert_eq!(restored, original);
+        let persisted: Vec<String> = stored.lock().unwrap().iter().map(|block| block.hash.clone()).collect();
+        assert_eq!(persisted, original);
+        assert_eq!(chain.get_balance_of_address(&miner.address), 2 * DEFAULT_MINING_REWARD);
+        assert!(chain.validate_chain().is_ok());
+
+        // 곁가지는 보관되어 있으므로 저장소가 회복되면 다음 블록에서 재구성됩니다.
+        assert_eq!(chain.accept_block(rival_blocks[3].clone()), BlockAcceptance::Reorganized);
+        assert_eq!(chain.get_latest_block().unwrap().hash, rival_blocks[3].hash);
+        assert_eq!(stored.lock().unwrap().len(), 5);
+        assert_eq!(chain.get_balance_of_address(&miner.address), 0);
+    }
+
+    #[test]
+    fn reorganization_returns_disconnected_transactions_to_the_mempool() {
+        let (alice, bob, miner, rival_miner) = (regtest_wallet(), regtest_wallet(), regtest_wallet(), regtest_wallet());
+        let mut chain = regtest_chain(LedgerMode::Account);
+        let funded = mine(&mut chain, &alice);
+        let payment = transfer(&chain, &alice, &bob, 30, 2);
+        chain.add_transaction(payment.clone()).unwrap();
+        mine(&mut chain, &miner);
+        assert!(chain.pending_transactions.is_empty());
+        assert_eq!(chain.get_balance_of_address(&bob.address), 30);
+
+        // 송금이 없는 더 긴 곁가지로 재구성합니다.
+        let first = mine_on(&funded, 2, &rival_miner);
+        let second = mine_on(&first, 3, &rival_miner);
+        assert_eq!(chain.accept_block(first), BlockAcceptance::SideBranch);
+        assert_eq!(chain.accept_block(second.clone()), BlockAcceptance::Reorganized);
+        assert_eq!(chain.get_latest_block().unwrap().hash, second.hash);
+
+        // 끊긴 블록의 송금은 대기열로 돌아오고, 코인베이스는 돌아오지 않습니다.
+        let pending: Vec<String> = chain.pending_transactions.iter().map(|tx| tx.txid()).collect();
+        assert_eq!(pending, vec![payment.txid()]);
+        assert_eq!(chain.get_balance_of_address(&bob.address), 0);
+        assert_eq!(chain.get_balance_of_address(&miner.address), 0);
+        // 확정된 nonce는 되돌아가고, 대기열의 송금이 다음 nonce를 차지합니다.
+        assert_eq!(chain.account_nonces.get(&alice.address), None);
+        assert_eq!(chain.get_next_nonce(&alice.address), 1);
+
+        // 다음 블록에 다시 담깁니다. (곁가지 블록의 시각이 앞서 있으므로 그 뒤로 맞춥니다)
+        let mut block = chain.block_template(&rival_miner.address).unwrap();
+        block.timestamp = second.timestamp + 1;
+        block.mine_block(block.bits).unwrap();
+        chain.submit_block(block.clone()).unwrap();
+        assert!(block.transactions.iter().any(|tx| tx.txid() == payment.txid()));
+        assert_eq!(chain.get_balance_of_address(&bob.address), 30);
+    }
+
+    #[test]
+    fn mempool_byte_limit_tracks_admitted_transactions() {
+        let (alice, bob) = (regtest_wallet(), regtest_wallet());
+        let mut chain = regtest_chain(LedgerMode::Account);
+        mine(&mut chain, &alice);
+
+        let tx_size = transfer(&chain, &alice, &bob, 1, 1).size();
+        chain.mempool_limits.max_bytes = 2 * tx_size + tx_size / 2;
+        for _ in 0..2 {
+            chain.add_transaction(transfer(&chain, &alice, &bob, 1, 1)).unwrap();
+        }
+        assert_eq!(chain.pending_bytes(), 2 * tx_size);
+        let overflow = transfer(&chain, &alice, &bob, 1, 1);
+        assert!(matches!(chain.add_transaction(overflow), Err(BlockchainError::MempoolFull(_))));
+
+        // 블록에 담긴 트랜잭션이 빠지면 크기 합계도 줄어듭니다.
+        mine(&mut chain, &bob);
+        assert_eq!(chain.pending_bytes(), 0);
+        chain.add_transaction(transfer(&chain, &alice, &bob, 1, 1)).unwrap();
+        assert_eq!(chain.pending_bytes(), tx_size);
+    }
+
+    #[test]
+    fn block_template_prefers_higher_fee_rate_within_size_limit() {
+        let (alice, carol, bob) = (regtest_wallet(), regtest_wallet(), regtest_wallet());
+        let mut chain = regtest_chain(LedgerMode::Account);
+        mine(&mut chain, &alice);
+        mine(&mut chain, &carol);
+
+        let cheap = transfer(&chain, &alice, &bob, 10, 1);
+        let generous = transfer(&chain, &carol, &bob, 10, 5);
+        chain.add_transaction(cheap.clone()).unwrap();
+        chain.add_transaction(generous.clone()).unwrap();
+
+        let template = chain.block_template(&bob.address).unwrap();
+        let txids: Vec<String> = template.transactions.iter().skip(1).map(Transaction::txid).collect();
+        assert_eq!(txids, vec![generous.txid(), cheap.txid()]);
+
+        // 코인베이스와 트랜잭션 하나만 들어가는 크기 제한
+        let coinbase_size = template.transactions[0].size();
+        chain.max_block_size = coinbase_size + generous.size();
+        let template = chain.block_template(&bob.address).unwrap();
+        assert_eq!(template.transactions.len(), 2);
+        assert_eq!(template.transactions[1].txid(), generous.txid());
+    }
+
+    // `from` 지갑의 미사용 출력 `inputs`를 모두 소비하여 `to`에게 `amount`를 보내고 나머지를 거스름돈으로 돌려받습니다.
+    fn utxo_transfer(chain: &Blockchain, from: &Wallet, to: &Wallet, inputs: Vec<OutPoint>, amount: u64) -> Transaction {
+        let utxo_set = chain.index.utxo_set();
+        let total: u64 = inputs.iter().map(|input| utxo_set.get(input).map_or(0, |output| output.amount)).sum();
+        let outputs = vec![
+            TxOutput { recipient: to.address.clone(), amount },
+            TxOutput { recipient: from.address.clone(), amount: total - amount },
+        ];
+        let mut tx = Transaction::new_utxo(from.address.clone(), to.address.clone(), inputs, outputs);
+        tx.nonce = chain.get_next_nonce(&from.address);
+        tx.chain_id = chain.chain_id;
+        tx.sign(&from.to_signing_key().unwrap(), from.public_key_hex.clone());
+        tx
+    }
+
+    #[test]
+    fn utxo_double_spend_is_rejected_in_mempool_and_blocks() {
+        let (alice, bob, carol) = (regtest_wallet(), regtest_wallet(), regtest_wallet());
+        let mut chain = regtest_chain(LedgerMode::Utxo);
+        mine(&mut chain, &alice);
+        let coins: Vec<OutPoint> = chain.utxo_set().outputs_of(&alice.address).into_iter().map(|(outpoint, _)| outpoint).collect();
+        assert_eq!(coins.len(), 1);
+
+        let to_bob = utxo_transfer(&chain, &alice, &bob, coins.clone(), 60);
+        chain.add_transaction(to_bob.clone()).unwrap();
+        // 대기열의 트랜잭션이 이미 소비한 출력은 다시 쓸 수 없습니다.
+        let mut to_carol = utxo_transfer(&chain, &alice, &carol, coins.clone(), 60);
+        assert!(matches!(chain.add_transaction(to_carol.clone()), Err(BlockchainError::UnknownInput(_))));
+
+        mine(&mut chain, &bob);
+        assert_eq!(chain.get_balance_of_address(&bob.address), 60 + DEFAULT_MINING_REWARD);
+        assert_eq!(chain.get_balance_of_address(&alice.address), 40);
+
+        // 이미 확정된 출력을 다시 쓰는 블록도 거부됩니다.
+        to_carol.nonce = chain.get_next_nonce(&alice.address);
+        to_carol.sign(&alice.to_signing_key().unwrap(), alice.public_key_hex.clone());
+        let mut block = chain.block_template(&carol.address).unwrap();
+        block.transactions.push(to_carol);
+        block.merkle_root = Block::compute_merkle_root(&block.transactions);
+        block.mine_block(block.bits).unwrap();
+        let error = chain.submit_block(block).unwrap_err();
+        assert!(matches!(error, BlockchainError::InvalidBlockTransaction { position: 1, .. }), "{}", error);
+    }
+
+    #[test]
+    fn replayed_and_out_of_order_nonces_are_rejected() {
+        let (alice, bob) = (regtest_wallet(), regtest_wallet());
+        let mut chain = regtest_chain(LedgerMode::Account);
+        mine(&mut chain, &alice);
+
+        let first = transfer(&chain, &alice, &bob, 10, 0);
+        assert_eq!(first.nonce, 0);
+        chain.add_transaction(first.clone()).unwrap();
+        // 같은 트랜잭션을 다시 보내면 이미 사용된 nonce입니다.
+        assert!(matches!(
+            chain.add_transaction(first.clone()),
+            Err(BlockchainError::InvalidNonce { expected: 1, actual: 0 })
+        ));
+
+        // nonce를 건너뛴 트랜잭션
+        let mut skipped = transfer(&chain, &alice, &bob, 10, 0);
+        skipped.nonce += 1;
+        skipped.sign(&alice.to_signing_key().unwrap(), alice.public_key_hex.clone());
+        assert!(matches!(
+            chain.add_transaction(skipped),
+            Err(BlockchainError::InvalidNonce { expected: 1, actual: 2 })
+        ));
+
+        // 다른 네트워크용으로 서명한 트랜잭션
+        let (carol, dave) = (Wallet::new_for_network(Network::Testnet), Wallet::new_for_network(Network::Testnet));
+        let mut foreign = Transaction::new(carol.address.clone(), dave.address.clone(), 10);
+        foreign.chain_id = Network::Testnet.chain_id();
+        foreign.sign(&carol.to_signing_key().unwrap(), carol.public_key_hex.clone());
+        assert!(matches!(chain.add_transaction(foreign), Err(BlockchainError::WrongChainId { .. })));
+
+        // 블록에 확정된 뒤에도 같은 트랜잭션은 대기열과 블록 모두에서 거부됩니다.
+        mine(&mut chain, &bob);
+        assert_eq!(chain.get_next_nonce(&alice.address), 1);
+        assert!(matches!(chain.add_transaction(first.clone()), Err(BlockchainError::InvalidNonce { .. })));
+        let mut block = chain.block_template(&bob.address).unwrap();
+        block.transactions.push(first);
+        block.merkle_root = Block::compute_merkle_root(&block.transactions);
+        block.mine_block(block.bits).unwrap();
+        let error = chain.submit_block(block).unwrap_err();
+        assert!(matches!(
+            &error,
+            BlockchainError::InvalidBlockTransaction { reason, .. } if matches!(**reason, BlockchainError::InvalidNonce { .. })
+        ), "{}", error);
+    }
+
+    // 템플릿의 트랜잭션을 바꾼 뒤 머클 루트를 다시 계산하고 채굴하여 제출합니다.
+    fn submit_modified(chain: &mut Blockchain, miner: &Wallet, modify: impl FnOnce(&mut Vec<Transaction>)) -> Result<(), BlockchainError> {
+        let mut block = chain.block_template(&miner.address).unwrap();
+        modify(&mut block.transactions);
+        block.merkle_root = Block::compute_merkle_root(&block.transactions);
+        block.mine_block(block.bits).unwrap();
+        chain.submit_block(block)
+    }
+
+    fn is_invalid_coinbase_at(result: Result<(), BlockchainError>, expected_position: usize) -> bool {
+        matches!(
+            result,
+            Err(BlockchainError::InvalidBlockTransaction { position, reason, .. })
+                if position == expected_position && matches!(*reason, BlockchainError::InvalidCoinbase(_))
+        )
+    }
+
+    #[test]
+    fn blocks_must_carry_one_coinbase_bound_to_their_height() {
+        let (alice, miner) = (regtest_wallet(), regtest_wallet());
+        let mut chain = regtest_chain(LedgerMode::Account);
+        mine(&mut chain, &alice);
+        let index = chain.chain.len() as u64;
+
+        let result = submit_modified(&mut chain, &miner, |txs| { txs.remove(0); });
+        assert!(matches!(result, Err(BlockchainError::MissingCoinbase { index: i }) if i == index));
+
+        let result = submit_modified(&mut chain, &miner, |txs| {
+            txs.push(Transaction::new_coinbase(miner.address.clone(), 1, index));
+        });
+        assert!(is_invalid_coinbase_at(result, 1));
+
+        for height in [Some(index + 1), Some(index - 1), None] {
+            let result = submit_modified(&mut chain, &miner, |txs| txs[0].coinbase_height = height);
+            assert!(is_invalid_coinbase_at(result, 0), "coinbase bound to {:?}", height);
+        }
+        assert_eq!(chain.chain.len() as u64, index);
+    }
+
+    #[test]
+    fn coinbase_may_claim_at_most_subsidy_plus_fees() {
+        let (alice, bob, miner) = (regtest_wallet(), regtest_wallet(), regtest_wallet());
+        let mut chain = regtest_chain(LedgerMode::Account);
+        mine(&mut chain, &alice);
+        chain.add_transaction(transfer(&chain, &alice, &bob, 10, 3)).unwrap();
+        let index = chain.chain.len() as u64;
+        let allowed = chain.block_subsidy(index) + 3;
+
+        let result = submit_modified(&mut chain, &miner, |txs| txs[0].amount += 1);
+        assert!(matches!(
+            result,
+            Err(BlockchainError::ExcessiveCoinbase { index: i, claimed, allowed: a })
+                if i == index && claimed == allowed + 1 && a == allowed
+        ));
+        submit_modified(&mut chain, &miner, |txs| assert_eq!(txs[0].amount, allowed)).unwrap();
+        assert_eq!(chain.get_balance_of_address(&miner.address), allowed);
+    }
+
+    #[test]
+    fn coinbase_is_not_allowed_in_the_mempool() {
+        let miner = regtest_wallet();
+        let mut chain = regtest_chain(LedgerMode::Account);
+        let coinbase = Transaction::new_coinbase(miner.address.clone(), DEFAULT_MINING_REWARD, 1);
+        assert!(matches!(chain.add_transaction(coinbase), Err(BlockchainError::CoinbaseNotAllowed)));
+
+        // 코인베이스 형식이 아니어도 블록 높이를 가진 트랜잭션은 거부됩니다.
+        mine(&mut chain, &miner);
+        let mut bound = transfer(&chain, &miner, &regtest_wallet(), 1, 0);
+        bound.coinbase_height = Some(2);
+        assert!(matches!(chain.add_transaction(bound), Err(BlockchainError::CoinbaseNotAllowed)));
+        assert!(chain.pending_transactions.is_empty());
     }
 }
@@ -0,0 +1,488 @@
+// src/chain_params.rs
+use super::address::{Address, Network}; // address 모듈의 주소 유형 사용
+use super::block::Block;                // block 모듈의 Block 구조체 사용
+use super::blockchain::{DEFAULT_MINING_REWARD, GENESIS_TIMESTAMP};
+use super::difficulty::{DifficultyParams, Target}; // difficulty 모듈의 난이도 매개변수와 목표값 사용
+use super::error::BlockchainError;      // error 모듈의 오류 유형 사용
+use super::transaction::Transaction;    // transaction 모듈의 Transaction 구조체 사용
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+// 테스트넷, regtest 제네시스 블록의 타임스탬프 (각각 2025-07-02, 2025-07-03 00:00:00 UTC).
+// 초기 배정이 없는 네트워크끼리도 제네시스 해시가 달라지도록 네트워크마다 다른 값을 씁니다.
+const TESTNET_GENESIS_TIMESTAMP: i64 = 1_751_414_400;
+const REGTEST_GENESIS_TIMESTAMP: i64 = 1_751_500_800;
+// 각 프리셋의 제네시스 블록이 초기 목표값을 충족하는 nonce (`mine_genesis_nonce`로 찾은 값)
+const MAINNET_GENESIS_NONCE: u64 = 248;
+const TESTNET_GENESIS_NONCE: u64 = 95;
+const REGTEST_GENESIS_NONCE: u64 = 1;
+
+/// 블록 보상이 절반으로 줄어드는 기본 간격 (블록 수)
+pub const DEFAULT_HALVING_INTERVAL: u64 = 210_000;
+/// 기본 총 발행 한도. 기본 보상과 반감 간격으로 발행할 수 있는 양(보상 × 간격 × 2)입니다.
+pub const DEFAULT_MAX_SUPPLY: u64 = DEFAULT_MINING_REWARD * DEFAULT_HALVING_INTERVAL * 2;
+
+// regtest의 반감 간격
+const REGTEST_HALVING_INTERVAL: u64 = 150;
+// regtest의 고정 목표값 (해시 두 번에 한 번꼴)
+const REGTEST_POW_LIMIT_BITS: u32 = 0x207f_ffff;
+
+// 보상을 이만큼 반감하면 항상 0이 됩니다.
+const MAX_HALVINGS: u64 = u64::BITS as u64;
+
+/// 블록 보상(새로 발행되는 보조금) 규칙입니다.
+///
+/// 높이 h 블록의 보조금은 `block_reward >> (h / halving_interval)`이며,
+/// 제네시스 초기 배정과 지금까지의 보조금 합계가 `max_supply`를 넘지 않도록 마지막 보조금은 잘립니다.
+/// 채굴자는 보조금에 더해 블록에 담은 트랜잭션의 수수료를 받습니다.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RewardSchedule {
+    /// 첫 반감 전까지 블록마다 발행하는 보조금
+    pub block_reward: u64,
+    /// 보조금이 절반으로 줄어드는 간격 (블록 수)
+    pub halving_interval: u64,
+    /// 제네시스 초기 배정을 포함한 총 발행 한도
+    pub max_supply: u64,
+}
+
+impl Default for RewardSchedule {
+    fn default() -> Self {
+        RewardSchedule {
+            block_reward: DEFAULT_MINING_REWARD,
+            halving_interval: DEFAULT_HALVING_INTERVAL,
+            max_supply: DEFAULT_MAX_SUPPLY,
+        }
+    }
+}
+
+impl RewardSchedule {
+    /// 높이 `height` 블록이 발행할 수 있는 보조금입니다. (수수료 제외)
+    /// `genesis_supply`는 제네시스 블록의 초기 배정 합계입니다.
+    pub fn subsidy_at(&self, height: u64, genesis_supply: u64) -> u64 {
+        let issued_before = genesis_supply as u128 + self.scheduled_issuance(height.saturating_sub(1));
+        let remaining = (self.max_supply as u128).saturating_sub(issued_before);
+        (self.scheduled_subsidy(height) as u128).min(remaining) as u64
+    }
+
+    /// `height` 다음으로 보조금이 반감되는 높이. 보조금이 이미 0이면 None입니다.
+    pub fn next_halving_height(&self, height: u64) -> Option<u64> {
+        if self.halving_interval == 0 || self.scheduled_subsidy(height.max(1)) == 0 {
+            return None;
+        }
+        (height / self.halving_interval + 1).checked_mul(self.halving_interval)
+    }
+
+    // 발행 한도를 적용하기 전의 보조금 (제네시스 블록은 0)
+    fn scheduled_subsidy(&self, height: u64) -> u64 {
+        if height == 0 || self.halving_interval == 0 {
+            return 0;
+        }
+        let halvings = height / self.halving_interval;
+        if halvings >= MAX_HALVINGS { 0 } else { self.block_reward >> halvings }
+    }
+
+    // 높이 1부터 `height`까지 발행 한도를 적용하기 전의 보조금 합계. 반감 구간마다 한 번에 더합니다.
+    fn scheduled_issuance(&self, height: u64) -> u128 {
+        let mut total: u128 = 0;
+        let mut era_start: u64 = 1;
+        while era_start <= height {
+            let subsidy = self.scheduled_subsidy(era_start);
+            if subsidy == 0 {
+                break;
+            }
+            let halvings = era_start / self.halving_interval;
+            let era_end = (halvings + 1).saturating_mul(self.halving_interval).saturating_sub(1).min(height);
+            total += subsidy as u128 * (era_end - era_start + 1) as u128;
+            era_start = match era_end.checked_add(1) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        total
+    }
+}
+
+/// 제네시스 블록에서 주소에 미리 배정하는 금액입니다.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisAllocation {
+    pub address: String,
+    pub amount: u64,
+}
+
+/// 네트워크의 합의 규칙과 제네시스 블록을 정하는 매개변수입니다.
+///
+/// 같은 매개변수로는 항상 같은 제네시스 블록이 만들어지며, 제네시스 블록 해시는
+/// 노드 간 핸드셰이크에서 같은 네트워크인지 확인하는 식별자로 쓰입니다.
+/// 이름 있는 프리셋(mainnet, testnet, regtest)을 쓰거나 JSON 제네시스 파일에서 읽습니다.
+///
+/// ```json
+/// {
+///   "network": "testnet",
+///   "genesis_timestamp": 1751328000,
+///   "genesis_nonce": 118,
+///   "allocations": [{ "address": "tbg1...", "amount": 1000000 }],
+///   "difficulty": { "initial_bits": "2000ffff", "retarget_interval": 10, "target_block_time": 10, "pow_limit_bits": "200fffff" },
+///   "reward": { "block_reward": 100, "halving_interval": 210000, "max_supply": 42000000 },
+///   "genesis_hash": "00..."
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainParams {
+    pub network: Network,
+    pub genesis_timestamp: i64,
+    /// 제네시스 블록의 nonce. 제네시스 블록은 다시 채굴하지 않고 이 값이 초기 목표값을 충족하는지만 확인합니다.
+    pub genesis_nonce: u64,
+    #[serde(default)]
+    pub allocations: Vec<GenesisAllocation>,
+    #[serde(default)]
+    pub difficulty: DifficultyParams,
+    #[serde(default)]
+    pub reward: RewardSchedule,
+    // 기대하는 제네시스 블록 해시. 지정하면 만들어진 제네시스 블록과 일치하는지 확인합니다.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub genesis_hash: Option<String>,
+}
+
+impl Default for ChainParams {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+impl ChainParams {
+    /// 메인넷 프리셋
+    pub fn mainnet() -> Self {
+        ChainParams {
+            network: Network::Mainnet,
+            genesis_timestamp: GENESIS_TIMESTAMP,
+            genesis_nonce: MAINNET_GENESIS_NONCE,
+            allocations: Vec::new(),
+            difficulty: DifficultyParams::default(),
+            reward: RewardSchedule::default(),
+            genesis_hash: None,
+        }
+    }
+
+    /// 테스트넷 프리셋. 규칙은 메인넷과 같고 주소와 제네시스 블록만 다릅니다.
+    pub fn testnet() -> Self {
+        ChainParams {
+            network: Network::Testnet,
+            genesis_timestamp: TESTNET_GENESIS_TIMESTAMP,
+            genesis_nonce: TESTNET_GENESIS_NONCE,
+            ..Self::mainnet()
+        }
+    }
+
+    /// 로컬 테스트용 프리셋. 목표값이 가장 쉬운 값으로 고정되어 블록을 즉시 채굴할 수 있고,
+    /// 보조금이 150블록마다 반감되어 발행 일정을 빠르게 확인할 수 있습니다.
+    pub fn regtest() -> Self {
+        ChainParams {
+            network: Network::Regtest,
+            genesis_timestamp: REGTEST_GENESIS_TIMESTAMP,
+            genesis_nonce: REGTEST_GENESIS_NONCE,
+            difficulty: DifficultyParams {
+                initial_bits: REGTEST_POW_LIMIT_BITS,
+                pow_limit_bits: REGTEST_POW_LIMIT_BITS,
+                no_retargeting: true,
+                ..Default::default()
+            },
+            reward: RewardSchedule {
+                halving_interval: REGTEST_HALVING_INTERVAL,
+                max_supply: DEFAULT_MINING_REWARD * REGTEST_HALVING_INTERVAL * 2,
+                ..Default::default()
+            },
+            ..Self::mainnet()
+        }
+    }
+
+    /// 네트워크 이름에 해당하는 프리셋
+    pub fn preset(network: Network) -> Self {
+        match network {
+            Network::Mainnet => Self::mainnet(),
+            Network::Testnet => Self::testnet(),
+            Network::Regtest => Self::regtest(),
+        }
+    }
+
+    /// JSON 제네시스 파일에서 매개변수를 읽고 검사합니다.
+    pub fn from_genesis_file(path: &Path) -> Result<Self, BlockchainError> {
+        let contents = fs::read(path).map_err(|e| {
+            BlockchainError::InvalidChainParams(format!("cannot read genesis file {}: {}", path.display(), e))
+        })?;
+        let params: ChainParams = serde_json::from_slice(&contents).map_err(|e| {
+            BlockchainError::InvalidChainParams(format!("malformed genesis file {}: {}", path.display(), e))
+        })?;
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// 이 네트워크의 트랜잭션이 사용하는 네트워크 식별자
+    pub fn chain_id(&self) -> u64 {
+        self.network.chain_id()
+    }
+
+    /// 제네시스 블록의 초기 배정 합계
+    pub fn genesis_supply(&self) -> u64 {
+        self.allocations.iter().fold(0u64, |acc, allocation| acc.saturating_add(allocation.amount))
+    }
+
+    /// 매개변수가 일관된지 검사합니다.
+    /// - 초기 배정 주소는 이 네트워크의 주소이고, 중복되지 않으며, 금액이 0보다 커야 합니다.
+    /// - 난이도 범위와 조정 간격이 올바라야 합니다.
+    /// - 제네시스 nonce가 초기 목표값을 충족해야 하고, 기대하는 제네시스 해시가 있으면 제네시스 블록과 일치해야 합니다.
+    pub fn validate(&self) -> Result<(), BlockchainError> {
+        let invalid = |reason: String| BlockchainError::InvalidChainParams(reason);
+
+        let mut addresses = HashSet::new();
+        let mut genesis_supply: u64 = 0;
+        for allocation in self.allocations.iter() {
+            Address::parse_for_network(&allocation.address, self.network)?;
+            if !addresses.insert(allocation.address.as_str()) {
+                return Err(invalid(format!("duplicate allocation for {}", allocation.address)));
+            }
+            if allocation.amount == 0 {
+                return Err(invalid(format!("allocation for {} must be positive", allocation.address)));
+            }
+            genesis_supply = genesis_supply.checked_add(allocation.amount).ok_or(BlockchainError::AmountOverflow)?;
+        }
+        if genesis_supply > self.reward.max_supply {
+            return Err(invalid(format!(
+                "genesis allocations {} exceed max supply {}", genesis_supply, self.reward.max_supply
+            )));
+        }
+
+        let difficulty = &self.difficulty;
+        match (Target::from_compact(difficulty.initial_bits), difficulty.pow_limit()) {
+            (Some(initial), Some(pow_limit)) if initial <= pow_limit => {}
+            _ => {
+                return Err(invalid(format!(
+                    "initial target {:08x} must be a valid target no easier than the limit {:08x}",
+                    difficulty.initial_bits, difficulty.pow_limit_bits
+                )));
+            }
+        }
+        if difficulty.retarget_interval == 0 || difficulty.target_block_time <= 0 {
+            return Err(invalid("retarget interval and target block time must be positive".to_string()));
+        }
+        if self.reward.block_reward == 0 || self.reward.halving_interval == 0 {
+            return Err(invalid("block reward and halving interval must be positive".to_string()));
+        }
+
+        let genesis_block = self.genesis_block()?;
+        if let Some(expected) = &self.genesis_hash {
+            let actual = genesis_block.hash;
+            if *expected != actual {
+                return Err(BlockchainError::GenesisMismatch { expected: expected.clone(), actual });
+            }
+        }
+        Ok(())
+    }
+
+    /// 제네시스 블록을 만듭니다. 초기 배정은 높이 0의 코인베이스 트랜잭션으로 기록됩니다.
+    /// 채굴하지 않고 `genesis_nonce`를 사용하며, 블록 해시가 초기 목표값을 충족하지 않으면 오류를 반환합니다.
+    pub fn genesis_block(&self) -> Result<Block, BlockchainError> {
+        let mut genesis_block = self.unmined_genesis_block();
+        genesis_block.nonce = self.genesis_nonce;
+        genesis_block.hash = genesis_block.calculate_hash();
+        if !genesis_block.has_valid_proof_of_work() {
+            return Err(BlockchainError::InvalidChainParams(format!(
+                "genesis nonce {} does not meet the initial target {:08x}", self.genesis_nonce, genesis_block.bits
+            )));
+        }
+        Ok(genesis_block)
+    }
+
+    /// 제네시스 블록이 초기 목표값을 충족하는 가장 작은 nonce를 찾습니다.
+    /// 새 네트워크의 제네시스 파일을 만들거나 초기 목표값을 바꿀 때 사용합니다.
+    pub fn mine_genesis_nonce(&self) -> Result<u64, BlockchainError> {
+        let mut genesis_block = self.unmined_genesis_block();
+        if genesis_block.target().is_none() {
+            return Err(BlockchainError::InvalidChainParams(format!(
+                "invalid initial target {:08x}", genesis_block.bits
+            )));
+        }
+        genesis_block.mine_block(genesis_block.bits)?;
+        Ok(genesis_block.nonce)
+    }
+
+    // nonce와 해시를 정하기 전의 제네시스 블록
+    fn unmined_genesis_block(&self) -> Block {
+        let transactions = self.allocations.iter()
+            .map(|allocation| {
+                let mut tx = Transaction::new_coinbase(allocation.address.clone(), allocation.amount, 0);
+                tx.timestamp = self.genesis_timestamp;
+                tx
+            })
+            .collect();
+        let mut genesis_block = Block::new(0, "0".to_string(), transactions);
+        genesis_block.timestamp = self.genesis_timestamp;
+        genesis_block.bits = self.difficulty.initial_bits;
+        genesis_block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::Wallet;
+
+    // 테스트마다 다른 임시 파일 경로
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("bingry-genesis-{}-{}.json", name, std::process::id()))
+    }
+
+    fn read_genesis(name: &str, contents: &str) -> Result<ChainParams, BlockchainError> {
+        let path = temp_path(name);
+        fs::write(&path, contents).unwrap();
+        let params = ChainParams::from_genesis_file(&path);
+        fs::remove_file(&path).unwrap();
+        params
+    }
+
+    // 초기 배정이 있는 테스트넷 매개변수 (제네시스 nonce를 새로 찾음)
+    fn funded_testnet(amount: u64) -> ChainParams {
+        let wallet = Wallet::new_for_network(Network::Testnet);
+        let mut params = ChainParams::testnet();
+        params.allocations.push(GenesisAllocation { address: wallet.address, amount });
+        params.genesis_nonce = params.mine_genesis_nonce().unwrap();
+        params
+    }
+
+    fn schedule(block_reward: u64, halving_interval: u64, max_supply: u64) -> RewardSchedule {
+        RewardSchedule { block_reward, halving_interval, max_supply }
+    }
+
+    #[test]
+    fn subsidy_halves_at_each_interval() {
+        let reward = schedule(100, 10, u64::MAX);
+        assert_eq!(reward.subsidy_at(0, 0), 0); // 제네시스 블록
+        assert_eq!(reward.subsidy_at(1, 0), 100);
+        assert_eq!(reward.subsidy_at(9, 0), 100);
+        assert_eq!(reward.subsidy_at(10, 0), 50);
+        assert_eq!(reward.subsidy_at(20, 0), 25);
+        assert_eq!(reward.scheduled_issuance(20), 9 * 100 + 10 * 50 + 25);
+
+        assert_eq!(reward.next_halving_height(0), Some(10));
+        assert_eq!(reward.next_halving_height(9), Some(10));
+        assert_eq!(reward.next_halving_height(10), Some(20));
+    }
+
+    #[test]
+    fn subsidy_is_truncated_at_max_supply() {
+        // 초기 배정 70 + 높이 1~9의 보조금 900 + 높이 10에서 남은 30
+        let reward = schedule(100, 10, 1_000);
+        assert_eq!(reward.subsidy_at(9, 70), 100);
+        assert_eq!(reward.subsidy_at(10, 70), 30);
+        assert_eq!(reward.subsidy_at(11, 70), 0);
+        assert_eq!(reward.subsidy_at(1, 1_000), 0);
+        let issued: u64 = (1..=30).map(|height| reward.subsidy_at(height, 70)).sum();
+        assert_eq!(70 + issued, reward.max_supply);
+    }
+
+    #[test]
+    fn subsidy_reaches_zero_after_64_halvings() {
+        let reward = schedule(u64::MAX, 1, u64::MAX);
+        assert_eq!(reward.scheduled_subsidy(63), 1);
+        assert_eq!(reward.scheduled_subsidy(64), 0);
+        assert_eq!(reward.scheduled_subsidy(u64::MAX), 0);
+        assert_eq!(reward.next_halving_height(63), Some(64));
+        assert_eq!(reward.next_halving_height(64), None);
+        // 높이 h의 보조금은 2^(64-h) - 1이므로 합계는 2^64 - 2 - 63입니다.
+        assert_eq!(reward.scheduled_issuance(u64::MAX), (1u128 << 64) - 65);
+    }
+
+    #[test]
+    fn presets_carry_their_genesis_nonce() {
+        let mut hashes = HashSet::new();
+        for params in [ChainParams::mainnet(), ChainParams::testnet(), ChainParams::regtest()] {
+            params.validate().unwrap();
+            assert_eq!(params.mine_genesis_nonce().unwrap(), params.genesis_nonce);
+            let genesis = params.genesis_block().unwrap();
+            assert_eq!(genesis.nonce, params.genesis_nonce);
+            assert_eq!(genesis.hash, genesis.calculate_hash());
+            assert!(hashes.insert(genesis.hash));
+        }
+    }
+
+    #[test]
+    fn genesis_nonce_must_meet_the_initial_target() {
+        // 메인넷 제네시스의 가장 작은 nonce보다 작은 값은 목표값을 충족하지 않습니다.
+        let params = ChainParams { genesis_nonce: 0, ..ChainParams::mainnet() };
+        assert!(matches!(params.genesis_block(), Err(BlockchainError::InvalidChainParams(_))));
+        assert!(matches!(params.validate(), Err(BlockchainError::InvalidChainParams(_))));
+
+        let mut params = ChainParams::mainnet();
+        params.difficulty.initial_bits = 0x1d80_0000;
+        assert!(params.mine_genesis_nonce().is_err());
+    }
+
+    #[test]
+    fn genesis_file_round_trips_and_checks_its_hash() {
+        let mut params = funded_testnet(1_000);
+        params.genesis_hash = Some(params.genesis_block().unwrap().hash);
+        let json = serde_json::to_string_pretty(&params).unwrap();
+        assert_eq!(read_genesis("round-trip", &json).unwrap(), params);
+
+        // 다른 매개변수로 만든 제네시스 해시를 기대하면 거부됩니다.
+        let mut mismatched = params.clone();
+        mismatched.genesis_hash = Some(ChainParams::testnet().genesis_block().unwrap().hash);
+        let json = serde_json::to_string(&mismatched).unwrap();
+        assert!(matches!(read_genesis("mismatch", &json), Err(BlockchainError::GenesisMismatch { .. })));
+
+        // 생략한 규칙은 기본값을 사용하므로, 테스트넷 프리셋과 같은 최소 파일
+        let json = format!(
+            r#"{{ "network": "testnet", "genesis_timestamp": {}, "genesis_nonce": {} }}"#,
+            TESTNET_GENESIS_TIMESTAMP, TESTNET_GENESIS_NONCE
+        );
+        assert_eq!(read_genesis("minimal", &json).unwrap(), ChainParams::testnet());
+    }
+
+    #[test]
+    fn rejects_malformed_genesis_files() {
+        assert!(matches!(
+            ChainParams::from_genesis_file(&temp_path("missing")),
+            Err(BlockchainError::InvalidChainParams(_))
+        ));
+        for json in [
+            "not json",
+            r#"{ "network": "regtest", "genesis_timestamp": 0 }"#,
+            r#"{ "network": "regtest", "genesis_timestamp": 0, "genesis_nonce": 0, "extra": 1 }"#,
+        ] {
+            assert!(matches!(read_genesis("malformed", json), Err(BlockchainError::InvalidChainParams(_))), "{}", json);
+        }
+    }
+
+    #[test]
+    fn validate_checks_allocations_and_rules() {
+        let params = funded_testnet(10);
+        params.validate().unwrap();
+
+        let mut duplicate = params.clone();
+        duplicate.allocations.push(params.allocations[0].clone());
+        assert!(matches!(duplicate.validate(), Err(BlockchainError::InvalidChainParams(_))));
+
+        let mut zero = params.clone();
+        zero.allocations[0].amount = 0;
+        assert!(matches!(zero.validate(), Err(BlockchainError::InvalidChainParams(_))));
+
+        let mut other_network = params.clone();
+        other_network.allocations[0].address = Wallet::new_for_network(Network::Mainnet).address;
+        assert!(matches!(other_network.validate(), Err(BlockchainError::WrongNetwork { .. })));
+
+        let mut oversupplied = params.clone();
+        oversupplied.reward.max_supply = 9;
+        assert!(matches!(oversupplied.validate(), Err(BlockchainError::InvalidChainParams(_))));
+
+        let mut easier_than_limit = params.clone();
+        easier_than_limit.difficulty.pow_limit_bits = 0x1f00_ffff;
+        assert!(matches!(easier_than_limit.validate(), Err(BlockchainError::InvalidChainParams(_))));
+
+        let mut no_halving = params.clone();
+        no_halving.reward.halving_interval = 0;
+        assert!(matches!(no_halving.validate(), Err(BlockchainError::InvalidChainParams(_))));
+    }
+}
@@ -0,0 +1,353 @@
+// src/config.rs
+use super::address::Network; // address 모듈의 네트워크 유형 사용
+use super::blockchain::{ChainSettings, LedgerMode, MempoolLimits};
+use super::chain_params::{ChainParams, RewardSchedule}; // chain_params 모듈의 네트워크 매개변수 사용
+use super::difficulty::{compact_hex, parse_bits, DifficultyParams}; // difficulty 모듈의 난이도 매개변수 사용
+use log::LevelFilter;
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::thread;
+
+/// 노드 실행 설정입니다. TOML 파일(`--config <경로>`)에서 읽고, 명령줄 인자로 개별 항목을 덮어씁니다.
+/// 파일에 없는 항목은 기본값을 사용합니다.
+///
+/// 합의 규칙은 `network` 프리셋이나 `genesis_file`(JSON 제네시스 파일)에서 정해집니다.
+/// `[difficulty]`, `[reward]`에 지정한 항목은 그 값만 대체하므로, 같은 네트워크의 모든 노드가 같은 값을 써야 합니다.
+///
+/// ```toml
+/// network = "testnet"          # 생략하면 제네시스 파일의 네트워크, 둘 다 없으면 mainnet
+/// genesis_file = "genesis.json"
+/// data_dir = "data"
+/// ledger_mode = "account"      # 또는 "utxo"
+/// log_level = "info"           # off, error, warn, info, debug, trace
+/// listen_addr = "127.0.0.1:8080"
+/// p2p_listen_addr = "127.0.0.1:9080"
+/// rpc_listen_addr = "127.0.0.1:8545"
+/// rpc_allowed_origin = "http://localhost:3000"  # 생략하면 브라우저에서 보낸 JSON-RPC 요청을 거부
+/// peers = ["127.0.0.1:9081"]
+/// mining_threads = 4           # 0이면 CPU 코어 수
+///
+/// [difficulty]
+/// initial_bits = "2000ffff"    # 초기 목표값 (compact 표현)
+/// retarget_interval = 10
+/// target_block_time = 10
+/// pow_limit_bits = "200fffff"  # 가장 쉬운 목표값
+/// no_retargeting = false
+///
+/// [reward]
+/// block_reward = 100
+/// halving_interval = 210000
+/// max_supply = 42000000
+///
+/// [mempool]
+/// max_transactions = 10000
+/// max_bytes = 10000000
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NodeConfig {
+    pub network: Option<Network>,
+    pub genesis_file: Option<String>,
+    pub data_dir: String,
+    pub ledger_mode: LedgerMode,
+    pub log_level: String,
+    pub listen_addr: String,     // 클라이언트 서버 주소
+    pub p2p_listen_addr: String, // P2P 네트워크 주소
+    pub rpc_listen_addr: String, // HTTP JSON-RPC 주소
+    pub rpc_allowed_origin: Option<String>, // 브라우저에서 JSON-RPC를 호출할 수 있는 출처 (없으면 허용하지 않음)
+    pub peers: Vec<String>,
+    pub mining_threads: usize,   // 채굴 스레드 수 (0이면 CPU 코어 수)
+    pub difficulty: DifficultyOverrides,
+    pub reward: RewardOverrides,
+    pub mempool: MempoolLimits,
+}
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        NodeConfig {
+            network: None,
+            genesis_file: None,
+            data_dir: "data".to_string(),
+            ledger_mode: LedgerMode::Account,
+            log_level: "info".to_string(),
+            listen_addr: "127.0.0.1:8080".to_string(),
+            p2p_listen_addr: "127.0.0.1:9080".to_string(),
+            rpc_listen_addr: "127.0.0.1:8545".to_string(),
+            rpc_allowed_origin: None,
+            peers: Vec::new(),
+            mining_threads: 0,
+            difficulty: DifficultyOverrides::default(),
+            reward: RewardOverrides::default(),
+            mempool: MempoolLimits::default(),
+        }
+    }
+}
+
+impl NodeConfig {
+    /// TOML 설정 파일을 읽습니다.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("설정 파일 {}을(를) 읽을 수 없습니다: {}", path, e))?;
+        toml::from_str(&contents)
+            .map_err(|e| format!("설정 파일 {} 형식 오류: {}", path, e))
+    }
+
+    /// 프로세스의 명령줄 인자에서 설정을 읽습니다.
+    pub fn from_args() -> Result<Self, String> {
+        Self::parse_args(std::env::args().skip(1).collect())
+    }
+
+    /// 명령줄 인자를 해석합니다. `--config <경로>`가 있으면 그 파일을 먼저 읽고,
+    /// 나머지 인자는 인자 순서와 관계없이 파일의 값을 덮어씁니다.
+    ///
+    /// `--listen`, `--p2p-listen`, `--rpc-listen <주소>`: 바인드 주소
+    /// `--rpc-allow-origin <출처>`: 브라우저에서 JSON-RPC를 호출할 수 있는 출처 (예: `http://localhost:3000`)
+    /// `--port`, `--p2p-port`, `--rpc-port <포트>`: 바인드 주소의 포트만 변경
+    /// `--peer <주소>` (여러 번 지정 가능), `--data-dir <경로>`, `--network <이름>`, `--genesis <경로>`, `--utxo`,
+    /// `--initial-bits <목표값>`, `--block-reward <보상>`, `--mining-threads <개수>`, `--max-mempool-txs <개수>`,
+    /// `--max-mempool-bytes <바이트>`, `--log-level <수준>`
+    pub fn parse_args(args: Vec<String>) -> Result<Self, String> {
+        let mut config = match args.iter().position(|arg| arg == "--config") {
+            Some(position) => {
+                let path = args.get(position + 1).ok_or("--config 인자에 값이 필요합니다.")?;
+                NodeConfig::load(path)?
+            }
+            None => NodeConfig::default(),
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--utxo" {
+                config.ledger_mode = LedgerMode::Utxo;
+                continue;
+            }
+            let value = match arg.as_str() {
+                "--config" | "--listen" | "--p2p-listen" | "--rpc-listen" | "--port" | "--p2p-port"
+                | "--rpc-port" | "--rpc-allow-origin" | "--peer" | "--data-dir" | "--network" | "--genesis" | "--initial-bits" | "--block-reward"
+                | "--mining-threads" | "--max-mempool-txs" | "--max-mempool-bytes" | "--log-level" => {
+                    args.next().ok_or(format!("{} 인자에 값이 필요합니다.", arg))?
+                }
+                _ => return Err(format!("알 수 없는 인자: {}", arg)),
+            };
+            match arg.as_str() {
+                "--config" => {} // 이미 읽었습니다.
+                "--listen" => config.listen_addr = value,
+                "--p2p-listen" => config.p2p_listen_addr = value,
+                "--rpc-listen" => config.rpc_listen_addr = value,
+                "--port" => config.listen_addr = with_port(&config.listen_addr, &value)?,
+                "--p2p-port" => config.p2p_listen_addr = with_port(&config.p2p_listen_addr, &value)?,
+                "--rpc-port" => config.rpc_listen_addr = with_port(&config.rpc_listen_addr, &value)?,
+                "--rpc-allow-origin" => config.rpc_allowed_origin = Some(value),
+                "--peer" => config.peers.push(value),
+                "--data-dir" => config.data_dir = value,
+                "--network" => config.network = Some(value.parse()?),
+                "--genesis" => config.genesis_file = Some(value),
+                "--initial-bits" => config.difficulty.initial_bits = Some(parse_bits(&value)?),
+                "--block-reward" => config.reward.block_reward = Some(parse_number(&arg, &value)?),
+                "--mining-threads" => config.mining_threads = parse_number(&arg, &value)?,
+                "--max-mempool-txs" => config.mempool.max_transactions = parse_number(&arg, &value)?,
+                "--max-mempool-bytes" => config.mempool.max_bytes = parse_number(&arg, &value)?,
+                _ => config.log_level = value,
+            }
+        }
+        config.log_level_filter()?;
+        Ok(config)
+    }
+
+    /// 설정된 로그 수준
+    pub fn log_level_filter(&self) -> Result<LevelFilter, String> {
+        LevelFilter::from_str(&self.log_level).map_err(|_| format!("잘못된 로그 수준: {}", self.log_level))
+    }
+
+    /// 실제로 사용할 채굴 스레드 수. 설정하지 않았으면(0) 사용 가능한 CPU 코어 수를 사용합니다.
+    pub fn mining_thread_count(&self) -> usize {
+        match self.mining_threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            threads => threads,
+        }
+    }
+
+    /// 네트워크 매개변수. 제네시스 파일이 있으면 읽고, 없으면 네트워크 프리셋을 사용한 뒤
+    /// `[difficulty]`, `[reward]`에 지정한 항목만 덮어씁니다.
+    pub fn chain_params(&self) -> Result<ChainParams, String> {
+        let mut params = match &self.genesis_file {
+            Some(path) => {
+                let params = ChainParams::from_genesis_file(Path::new(path)).map_err(|e| e.to_string())?;
+                if let Some(network) = self.network
+                    && network != params.network
+                {
+                    return Err(format!("제네시스 파일 {}은(는) {} 네트워크용입니다. (설정: {})", path, params.network, network));
+                }
+                params
+            }
+            None => ChainParams::preset(self.network.unwrap_or(Network::Mainnet)),
+        };
+        let initial_bits = params.difficulty.initial_bits;
+        self.difficulty.apply(&mut params.difficulty);
+        self.reward.apply(&mut params.reward);
+        // 초기 목표값을 바꾸면 제네시스 블록도 달라지므로, 바뀐 목표값을 충족하는 nonce를 다시 찾습니다.
+        if params.difficulty.initial_bits != initial_bits {
+            params.genesis_nonce = params.mine_genesis_nonce().map_err(|e| e.to_string())?;
+        }
+        params.validate().map_err(|e| e.to_string())?;
+        Ok(params)
+    }
+
+    /// 블록체인 생성에 사용할 설정
+    pub fn chain_settings(&self) -> Result<ChainSettings, String> {
+        Ok(ChainSettings {
+            params: self.chain_params()?,
+            ledger_mode: self.ledger_mode,
+            mempool_limits: self.mempool,
+            ..Default::default()
+        })
+    }
+}
+
+/// `[difficulty]` 설정. 지정한 항목만 네트워크 매개변수의 난이도 규칙을 덮어씁니다.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DifficultyOverrides {
+    #[serde(with = "compact_hex::option", skip_serializing_if = "Option::is_none")]
+    pub initial_bits: Option<u32>,
+    pub retarget_interval: Option<u64>,
+    pub target_block_time: Option<i64>,
+    #[serde(with = "compact_hex::option", skip_serializing_if = "Option::is_none")]
+    pub pow_limit_bits: Option<u32>,
+    pub no_retargeting: Option<bool>,
+}
+
+impl DifficultyOverrides {
+    fn apply(&self, params: &mut DifficultyParams) {
+        if let Some(initial_bits) = self.initial_bits {
+            params.initial_bits = initial_bits;
+        }
+        if let Some(retarget_interval) = self.retarget_interval {
+            params.retarget_interval = retarget_interval;
+        }
+        if let Some(target_block_time) = self.target_block_time {
+            params.target_block_time = target_block_time;
+        }
+        if let Some(pow_limit_bits) = self.pow_limit_bits {
+            params.pow_limit_bits = pow_limit_bits;
+        }
+        if let Some(no_retargeting) = self.no_retargeting {
+            params.no_retargeting = no_retargeting;
+        }
+    }
+}
+
+/// `[reward]` 설정. 지정한 항목만 네트워크 매개변수의 보상 규칙을 덮어씁니다.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RewardOverrides {
+    pub block_reward: Option<u64>,
+    pub halving_interval: Option<u64>,
+    pub max_supply: Option<u64>,
+}
+
+impl RewardOverrides {
+    fn apply(&self, schedule: &mut RewardSchedule) {
+        if let Some(block_reward) = self.block_reward {
+            schedule.block_reward = block_reward;
+        }
+        if let Some(halving_interval) = self.halving_interval {
+            schedule.halving_interval = halving_interval;
+        }
+        if let Some(max_supply) = self.max_supply {
+            schedule.max_supply = max_supply;
+        }
+    }
+}
+
+// "호스트:포트" 주소의 포트만 바꿉니다.
+fn with_port(addr: &str, port: &str) -> Result<String, String> {
+    let port: u16 = port.parse().map_err(|_| format!("잘못된 포트: {}", port))?;
+    let host = addr.rsplit_once(':').map_or(addr, |(host, _)| host);
+    Ok(format!("{}:{}", host, port))
+}
+
+fn parse_number<T: FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("{} 인자의 값이 잘못되었습니다: {}", arg, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<NodeConfig, String> {
+        NodeConfig::parse_args(args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    #[test]
+    fn preset_is_used_without_overrides() {
+        assert_eq!(parse(&[]).unwrap().chain_params().unwrap(), ChainParams::mainnet());
+        let config = parse(&["--network", "regtest"]).unwrap();
+        assert_eq!(config.chain_params().unwrap(), ChainParams::regtest());
+    }
+
+    #[test]
+    fn command_line_overrides_only_the_given_field() {
+        let regtest = ChainParams::regtest();
+        // regtest 자신의 목표값도 프리셋의 목표값 한도 안에서 받아들여집니다.
+        let config = parse(&["--network", "regtest", "--initial-bits", "207fffff"]).unwrap();
+        assert_eq!(config.chain_params().unwrap(), regtest);
+
+        let params = parse(&["--block-reward", "50", "--network", "regtest"]).unwrap().chain_params().unwrap();
+        assert_eq!(params.reward.block_reward, 50);
+        assert_eq!(params.reward.halving_interval, regtest.reward.halving_interval);
+        assert_eq!(params.reward.max_supply, regtest.reward.max_supply);
+        assert_eq!(params.difficulty, regtest.difficulty);
+
+        // 초기 목표값을 바꾸면 그 목표값에 맞는 제네시스 블록을 사용합니다.
+        let params = parse(&["--initial-bits", "200fffff"]).unwrap().chain_params().unwrap();
+        let genesis = params.genesis_block().unwrap();
+        assert_eq!(genesis.bits, 0x200f_ffff);
+        assert_ne!(genesis.hash, ChainParams::mainnet().genesis_block().unwrap().hash);
+
+        // 프리셋의 한도보다 쉬운 목표값은 거부됩니다.
+        let config = parse(&["--network", "mainnet", "--initial-bits", "207fffff"]).unwrap();
+        assert!(config.chain_params().is_err());
+    }
+
+    #[test]
+    fn partial_config_sections_keep_preset_values() {
+        let config: NodeConfig = toml::from_str(
+            "network = \"regtest\"\n[difficulty]\ntarget_block_time = 30\n[reward]\nblock_reward = 50\n",
+        ).unwrap();
+        let params = config.chain_params().unwrap();
+        let regtest = ChainParams::regtest();
+        assert_eq!(params.difficulty, DifficultyParams { target_block_time: 30, ..regtest.difficulty });
+        assert_eq!(params.reward, RewardSchedule { block_reward: 50, ..regtest.reward });
+
+        let config: NodeConfig = toml::from_str("[difficulty]\ninitial_bits = \"1f00ffff\"\n").unwrap();
+        assert_eq!(config.difficulty.initial_bits, Some(0x1f00_ffff));
+        assert!(toml::from_str::<NodeConfig>("[difficulty]\ninitial_bits = \"1d800000\"\n").is_err());
+        assert!(toml::from_str::<NodeConfig>("[reward]\nhalving = 10\n").is_err());
+    }
+
+    #[test]
+    fn arguments_override_the_config_file() {
+        let path = std::env::temp_dir().join(format!("bingry-config-{}.toml", std::process::id()));
+        fs::write(&path, "network = \"regtest\"\nlisten_addr = \"0.0.0.0:7000\"\npeers = [\"127.0.0.1:9081\"]\n").unwrap();
+        let config = parse(&["--port", "7001", "--config", path.to_str().unwrap(), "--peer", "127.0.0.1:9082", "--utxo"]);
+        fs::remove_file(&path).unwrap();
+        let config = config.unwrap();
+        assert_eq!(config.network, Some(Network::Regtest));
+        assert_eq!(config.listen_addr, "0.0.0.0:7001");
+        assert_eq!(config.peers, vec!["127.0.0.1:9081", "127.0.0.1:9082"]);
+        assert_eq!(config.ledger_mode, LedgerMode::Utxo);
+    }
+
+    #[test]
+    fn rejects_malformed_arguments() {
+        assert!(parse(&["--unknown"]).is_err());
+        assert!(parse(&["--peer"]).is_err());
+        assert!(parse(&["--port", "70000"]).is_err());
+        assert!(parse(&["--block-reward", "-1"]).is_err());
+        assert!(parse(&["--log-level", "loud"]).is_err());
+        assert!(parse(&["--network", "devnet"]).is_err());
+    }
+}
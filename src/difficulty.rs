@@ -0,0 +1,418 @@
+// src/difficulty.rs
+use super::block::Block; // block 모듈의 Block 구조체 사용
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+/// 256비트 목표값(target)입니다.
+///
+/// 블록 해시(SHA-256 다이제스트)를 256비트 빅엔디언 정수로 보았을 때 목표값 이하이면 작업 증명을 충족합니다.
+/// 목표값이 작을수록 조건을 충족하는 해시가 드물어 채굴이 어렵습니다.
+/// 블록 헤더에는 32비트 compact 표현(`bits`)으로 기록합니다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Target([u8; 32]); // 빅엔디언 바이트 (배열의 사전순 비교 = 정수 비교)
+
+impl Target {
+    /// 가장 큰(가장 쉬운) 목표값
+    pub const MAX: Target = Target([0xff; 32]);
+
+    /// 빅엔디언 바이트로 목표값을 만듭니다.
+    pub const fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        Target(bytes)
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// compact 표현(nBits)을 해석합니다.
+    ///
+    /// 상위 8비트는 바이트 단위 크기(지수), 하위 23비트는 가수이며 값은 `가수 * 256^(지수 - 3)`입니다.
+    /// 부호 비트(0x00800000)가 설정되었거나, 값이 0이거나, 256비트를 넘으면 None을 반환합니다.
+    pub fn from_compact(bits: u32) -> Option<Self> {
+        if bits & 0x0080_0000 != 0 {
+            return None;
+        }
+        let exponent = (bits >> 24) as i32;
+        let mantissa = bits & 0x007f_ffff;
+        let mut bytes = [0u8; 32];
+        if exponent <= 3 {
+            let value = mantissa >> (8 * (3 - exponent));
+            bytes[29..].copy_from_slice(&value.to_be_bytes()[1..]);
+        } else {
+            // 가수의 세 바이트를 상위 바이트부터 제자리에 놓습니다.
+            for (k, byte) in mantissa.to_be_bytes()[1..].iter().enumerate() {
+                let position = 32 - exponent + k as i32;
+                if position < 0 {
+                    if *byte != 0 {
+                        return None; // 256비트를 넘는 값
+                    }
+                    continue;
+                }
+                bytes[position as usize] = *byte;
+            }
+        }
+        let target = Target(bytes);
+        if target.is_zero() { None } else { Some(target) }
+    }
+
+    /// compact 표현(nBits)으로 변환합니다. 가수에 담기지 않는 하위 바이트는 버려집니다.
+    pub fn to_compact(&self) -> u32 {
+        let first = match self.0.iter().position(|&b| b != 0) {
+            Some(first) => first,
+            None => return 0,
+        };
+        let mut size = (32 - first) as u32;
+        let mut mantissa = self.0[first..].iter().take(3)
+            .fold(0u32, |acc, &b| (acc << 8) | b as u32);
+        if size < 3 {
+            mantissa <<= 8 * (3 - size);
+        }
+        // 가수의 최상위 비트는 부호 비트이므로, 설정되면 한 바이트 밀어 냅니다.
+        if mantissa & 0x0080_0000 != 0 {
+            mantissa >>= 8;
+            size += 1;
+        }
+        (size << 24) | mantissa
+    }
+
+    /// 해시 다이제스트가 이 목표값 이하인지(작업 증명을 충족하는지) 확인합니다.
+    pub fn is_met_by(&self, digest: &[u8; 32]) -> bool {
+        *digest <= self.0
+    }
+
+    /// 이 목표값 이하의 해시를 찾는 데 필요한 평균 해시 시도 횟수(작업량) `2^256 / (target + 1)`을 계산합니다.
+    /// 목표값이 2^128보다 작으면(현실적으로 채굴할 수 없는 난이도) u128 최댓값으로 포화됩니다.
+    pub fn work(&self) -> u128 {
+        let target = to_limbs(&self.0);
+        if target[2] == 0 && target[3] == 0 {
+            return u128::MAX;
+        }
+        // 2^256 / (t + 1) = (2^256 - 1 - t) / (t + 1) + 1
+        let divisor = match add_one(target) {
+            Some(divisor) => divisor,
+            None => return 1, // t = 2^256 - 1
+        };
+        let dividend = target.map(|limb| !limb);
+        div_u128(dividend, divisor) + 1
+    }
+
+    /// 목표값에 `numerator / denominator`를 곱합니다. 256비트를 넘으면 `Target::MAX`로 포화되고, 0이 되면 1로 올립니다.
+    pub fn scale(&self, numerator: u64, denominator: u64) -> Target {
+        let limbs = to_limbs(&self.0);
+        // 320비트 곱셈
+        let mut product = [0u64; 5];
+        let mut carry = 0u128;
+        for i in 0..4 {
+            let value = limbs[i] as u128 * numerator as u128 + carry;
+            product[i] = value as u64;
+            carry = value >> 64;
+        }
+        product[4] = carry as u64;
+        // 64비트 나눗셈 (상위 limb부터)
+        let mut remainder = 0u128;
+        for limb in product.iter_mut().rev() {
+            let value = (remainder << 64) | *limb as u128;
+            *limb = (value / denominator.max(1) as u128) as u64;
+            remainder = value % denominator.max(1) as u128;
+        }
+        if product[4] != 0 {
+            return Target::MAX;
+        }
+        let scaled = Target(from_limbs([product[0], product[1], product[2], product[3]]));
+        if scaled.is_zero() { Target(from_limbs([1, 0, 0, 0])) } else { scaled }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.iter().all(|&b| b == 0)
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+// 빅엔디언 바이트 <-> 리틀엔디언 64비트 limb
+fn to_limbs(bytes: &[u8; 32]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (i, chunk) in bytes.chunks_exact(8).rev().enumerate() {
+        limbs[i] = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    limbs
+}
+
+fn from_limbs(limbs: [u64; 4]) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, chunk) in bytes.chunks_exact_mut(8).rev().enumerate() {
+        chunk.copy_from_slice(&limbs[i].to_be_bytes());
+    }
+    bytes
+}
+
+// 256비트 값에 1을 더합니다. 넘치면 None
+fn add_one(mut limbs: [u64; 4]) -> Option<[u64; 4]> {
+    for limb in limbs.iter_mut() {
+        let (value, overflow) = limb.overflowing_add(1);
+        *limb = value;
+        if !overflow {
+            return Some(limbs);
+        }
+    }
+    None
+}
+
+// 몫이 128비트에 들어간다고 알려진 256비트 나눗셈 (비트 단위 나머지 나눗셈)
+fn div_u128(dividend: [u64; 4], divisor: [u64; 4]) -> u128 {
+    let mut quotient = 0u128;
+    let mut remainder = [0u64; 4];
+    for bit in (0..256).rev() {
+        // remainder = remainder * 2 + 다음 비트
+        let overflow = remainder[3] >> 63 == 1;
+        for i in (1..4).rev() {
+            remainder[i] = (remainder[i] << 1) | (remainder[i - 1] >> 63);
+        }
+        remainder[0] = (remainder[0] << 1) | ((dividend[bit / 64] >> (bit % 64)) & 1);
+        // 256비트를 넘었거나 나누는 수 이상이면 뺍니다. (넘친 경우 wrapping 뺄셈이 올바른 값을 줍니다)
+        if overflow || !less_than(&remainder, &divisor) {
+            let mut borrow = false;
+            for i in 0..4 {
+                let (value, b1) = remainder[i].overflowing_sub(divisor[i]);
+                let (value, b2) = value.overflowing_sub(borrow as u64);
+                remainder[i] = value;
+                borrow = b1 || b2;
+            }
+            if bit < 128 {
+                quotient |= 1 << bit;
+            }
+        }
+    }
+    quotient
+}
+
+fn less_than(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    a.iter().rev().cmp(b.iter().rev()) == std::cmp::Ordering::Less
+}
+
+/// 목표값 compact 표현의 16진수 문자열("1d00ffff" 또는 "0x1d00ffff")을 해석합니다.
+pub fn parse_bits(text: &str) -> Result<u32, String> {
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    let bits = u32::from_str_radix(digits, 16).map_err(|_| format!("invalid compact target: {}", text))?;
+    Target::from_compact(bits).ok_or_else(|| format!("invalid compact target: {}", text))?;
+    Ok(bits)
+}
+
+// 설정 파일과 제네시스 파일에서는 목표값을 16진수 문자열로 씁니다.
+pub(crate) mod compact_hex {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bits: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:08x}", bits))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        super::parse_bits(&text).map_err(de::Error::custom)
+    }
+
+    /// 생략할 수 있는 목표값 (`Option<u32>`)
+    pub(crate) mod option {
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(bits: &Option<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+            match bits {
+                Some(bits) => super::serialize(bits, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+            let text = String::deserialize(deserializer)?;
+            super::super::parse_bits(&text).map(Some).map_err(de::Error::custom)
+        }
+    }
+}
+
+/// 난이도 조정(retargeting)에 사용되는 매개변수입니다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultyParams {
+    /// 제네시스 블록과 첫 조정 전까지 사용하는 목표값 (compact 표현)
+    #[serde(with = "compact_hex")]
+    pub initial_bits: u32,
+    /// 난이도를 조정하는 블록 간격 (N 블록마다 조정)
+    pub retarget_interval: u64,
+    /// 목표 블록 생성 간격 (초)
+    pub target_block_time: i64,
+    /// 가장 쉬운 목표값 (compact 표현). 조정된 목표값은 이보다 커질 수 없습니다.
+    #[serde(with = "compact_hex")]
+    pub pow_limit_bits: u32,
+    /// true이면 목표값을 조정하지 않고 항상 `initial_bits`를 사용합니다.
+    pub no_retargeting: bool,
+}
+
+impl DifficultyParams {
+    /// 기본 조정 규칙(10블록마다, 목표 10초)에 초기 목표값만 지정하여 생성합니다.
+    pub fn with_initial_bits(initial_bits: u32) -> Self {
+        DifficultyParams {
+            initial_bits,
+            ..Default::default()
+        }
+    }
+
+    /// 가장 쉬운 목표값
+    pub fn pow_limit(&self) -> Option<Target> {
+        Target::from_compact(self.pow_limit_bits)
+    }
+
+    /// 주어진 높이의 블록이 사용해야 하는 목표값(compact 표현)을 계산합니다.
+    /// `previous_blocks`는 해당 높이 이전의 블록들(인덱스 0부터 height - 1까지)입니다.
+    ///
+    /// 조정 높이가 아니면 직전 블록의 목표값을 그대로 사용합니다.
+    /// 조정 높이에서는 최근 `retarget_interval`개 블록 간격의 실제 소요 시간을 목표 시간과 비교하여
+    /// 목표값에 `실제 시간 / 목표 시간`을 곱합니다. (첫 조정에서는 제네시스 블록 앞에 블록이 없어 간격이 하나 적습니다) 한 번에 4배 넘게 바뀌지 않도록 실제 시간을
+    /// 목표 시간의 1/4 ~ 4배로 제한하며, 결과는 `pow_limit_bits`보다 쉬워지지 않습니다.
+    pub fn expected_bits(&self, previous_blocks: &[Block]) -> u32 {
+        self.expected_bits_at(previous_blocks.len() as u64, |index| previous_blocks.get(index as usize))
+    }
+
+    /// `expected_bits`와 같지만, 이전 블록을 슬라이스 대신 `ancestor(인덱스)`로 찾습니다.
+    /// 곁가지처럼 조상 블록이 한 곳에 모여 있지 않을 때 사용합니다.
+    /// 직전 블록과 조정 구간 바로 앞의 블록만 조회합니다.
+    pub fn expected_bits_at<'a>(&self, height: u64, ancestor: impl Fn(u64) -> Option<&'a Block>) -> u32 {
+        let last_block = match height.checked_sub(1).and_then(&ancestor) {
+            Some(block) => block,
+            None => return self.initial_bits,
+        };
+        if self.no_retargeting || self.retarget_interval == 0 || !height.is_multiple_of(self.retarget_interval) {
+            return last_block.bits;
+        }
+
+        // 구간의 블록 `retarget_interval`개가 만든 간격을 모두 재도록 구간 바로 앞 블록부터 잽니다.
+        let first_height = height.saturating_sub(self.retarget_interval + 1);
+        let gaps = height - 1 - first_height;
+        if gaps == 0 {
+            return last_block.bits;
+        }
+        let first_block = match ancestor(first_height) {
+            Some(block) => block,
+            None => return last_block.bits,
+        };
+        let expected_timespan = self.target_block_time.saturating_mul(gaps as i64).max(1);
+        let actual_timespan = (last_block.timestamp - first_block.timestamp)
+            .clamp((expected_timespan / 4).max(1), expected_timespan.saturating_mul(4));
+
+        let pow_limit = self.pow_limit().unwrap_or(Target::MAX);
+        let last_target = Target::from_compact(last_block.bits).unwrap_or(pow_limit);
+        last_target.scale(actual_timespan as u64, expected_timespan as u64)
+            .min(pow_limit)
+            .to_compact()
+    }
+}
+
+/// compact 목표값의 블록 하나에 담긴 작업량(평균 해시 시도 횟수)을 계산합니다.
+/// 해석할 수 없는 목표값이면 0입니다.
+pub fn block_work(bits: u32) -> u128 {
+    Target::from_compact(bits).map_or(0, |target| target.work())
+}
+
+impl Default for DifficultyParams {
+    fn default() -> Self {
+        DifficultyParams {
+            initial_bits: 0x2000_ffff,   // 해시 256번에 한 번꼴
+            retarget_interval: 10,
+            target_block_time: 10,
+            pow_limit_bits: 0x200f_ffff, // 해시 16번에 한 번꼴
+            no_retargeting: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 조정 구간을 채우는 블록들 (`spacing`초 간격, 모두 `bits` 사용)
+    fn blocks_with_spacing(count: u64, spacing: i64, bits: u32) -> Vec<Block> {
+        (0..count).map(|index| {
+            let mut block = Block::new(index, String::new(), Vec::new());
+            block.timestamp = 1_700_000_000 + index as i64 * spacing;
+            block.bits = bits;
+            block
+        }).collect()
+    }
+
+    #[test]
+    fn compact_round_trips() {
+        for bits in [0x1d00_ffff, 0x207f_ffff, 0x2000_ffff, 0x1b04_04cb, 0x0312_3456, 0x0212_3400, 0x0112_0000] {
+            let target = Target::from_compact(bits).unwrap();
+            assert_eq!(target.to_compact(), bits, "{:08x}", bits);
+        }
+        let target = Target::from_compact(0x1d00_ffff).unwrap().to_be_bytes();
+        assert_eq!(target[..6], [0, 0, 0, 0, 0xff, 0xff]);
+        assert!(target[6..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn rejects_negative_zero_and_oversized_targets() {
+        assert_eq!(Target::from_compact(0x1d80_0000), None); // 부호 비트
+        assert_eq!(Target::from_compact(0x1d00_0000), None); // 0
+        assert_eq!(Target::from_compact(0x0100_ffff), None); // 가수가 모두 밀려나 0
+        assert_eq!(Target::from_compact(0x2101_0000), None); // 256비트 초과
+        assert!(parse_bits("0x1d00ffff").is_ok());
+        assert!(parse_bits("1d800000").is_err());
+        assert!(parse_bits("zz").is_err());
+    }
+
+    #[test]
+    fn work_is_inverse_of_target() {
+        assert_eq!(block_work(0x1d00_ffff), 4_295_032_833);
+        assert_eq!(block_work(0x207f_ffff), 2);
+        assert_eq!(Target::MAX.work(), 1);
+        // 2^128보다 작은 목표값은 포화됩니다.
+        assert_eq!(block_work(0x0101_0000), u128::MAX);
+        assert_eq!(block_work(0x1d80_0000), 0);
+        assert!(block_work(0x1c00_ffff) > block_work(0x1d00_ffff));
+    }
+
+    #[test]
+    fn retarget_scales_and_clamps_the_target() {
+        let params = DifficultyParams::default();
+        let bits = params.initial_bits;
+        // 조정 높이가 아니면 직전 블록의 목표값을 유지합니다.
+        assert_eq!(params.expected_bits(&[]), bits);
+        assert_eq!(params.expected_bits(&blocks_with_spacing(5, 1, bits)), bits);
+
+        // 첫 조정은 간격 9개: 소요 시간 180초 / 목표 90초
+        assert_eq!(params.expected_bits(&blocks_with_spacing(10, 20, bits)), 0x2001_fffe);
+        // 이후에는 간격 10개: 소요 시간 150초 / 목표 100초
+        assert_eq!(params.expected_bits(&blocks_with_spacing(20, 15, bits)), 0x2001_7ffe);
+        // 너무 빠르면 1/4, 너무 느리면 4배까지만 바뀝니다.
+        assert_eq!(params.expected_bits(&blocks_with_spacing(20, 1, bits)), 0x1f3f_ffc0);
+        assert_eq!(params.expected_bits(&blocks_with_spacing(20, 100, bits)), 0x2003_fffc);
+        // 가장 쉬운 목표값을 넘지 않습니다.
+        assert_eq!(params.expected_bits(&blocks_with_spacing(20, 100, params.pow_limit_bits)), params.pow_limit_bits);
+
+        // 매 블록 조정하면 첫 블록 다음부터 직전 간격 하나로 조정합니다.
+        let every_block = DifficultyParams { retarget_interval: 1, ..params };
+        assert_eq!(every_block.expected_bits(&blocks_with_spacing(1, 20, bits)), bits);
+        assert_eq!(every_block.expected_bits(&blocks_with_spacing(2, 20, bits)), 0x2001_fffe);
+
+        let fixed = DifficultyParams { no_retargeting: true, ..params };
+        assert_eq!(fixed.expected_bits(&blocks_with_spacing(10, 1, bits)), bits);
+    }
+
+    #[test]
+    fn exact_target_spacing_keeps_the_target() {
+        let default = DifficultyParams::default();
+        let slow = DifficultyParams { retarget_interval: 5, target_block_time: 60, ..default };
+        for params in [default, slow] {
+            let bits = params.initial_bits;
+            // 첫 조정부터 이후의 조정까지 목표값이 그대로여야 합니다.
+            for windows in 1..=3 {
+                let blocks = blocks_with_spacing(params.retarget_interval * windows, params.target_block_time, bits);
+                assert_eq!(params.expected_bits(&blocks), bits, "window {} of {:?}", windows, params);
+            }
+        }
+    }
+}
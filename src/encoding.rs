@@ -0,0 +1,316 @@
+// src/encoding.rs
+use super::error::BlockchainError; // error 모듈의 오류 유형 사용
+
+/// 블록, 블록 헤더, 트랜잭션의 정규(canonical) 바이너리 인코딩 버전입니다. 형식이 호환되지 않게 바뀌면 올립니다.
+///
+/// 블록 해시와 트랜잭션 ID(서명 대상 해시)는 이 인코딩의 SHA-256이며, P2P 메시지와 블록 저장소도 이 인코딩을 사용합니다.
+/// 모든 가변 길이 필드에 길이를 붙이므로 서로 다른 값은 항상 서로 다른 바이트열이 됩니다.
+/// (문자열을 이어 붙이던 이전 방식에서는 송신자 "ab" + 수신자 "c"와 "a" + "bc"의 해시가 같았습니다)
+///
+/// - 정수: 고정 길이 리틀 엔디언 (`u8`, `u32`, `u64`, `i64`, `u128`)
+/// - 문자열/바이트열: `u32` 길이 + 내용 (문자열은 UTF-8)
+/// - 목록: `u32` 개수 + 각 항목
+/// - `Option<u64>`: 없으면 `0x00`, 있으면 `0x01` + 값
+/// - 최상위 인코딩은 버전 1바이트로 시작하며, 디코딩할 때 남는 바이트가 있으면 거부합니다.
+///
+/// | 인코딩 | 내용 |
+/// |---|---|
+/// | `Transaction::signing_bytes` | `version \| sender \| recipient \| amount u64 \| fee u64 \| timestamp i64 \| nonce u64 \| chain_id u64 \| inputs [txid str \| index u32] \| outputs [recipient str \| amount u64] \| coinbase_height` |
+/// | `Transaction::encode` | 서명 대상 인코딩 뒤에 `public_key str \| signature str` |
+/// | `Block::header_bytes` | `version \| index u64 \| timestamp i64 \| previous_hash str \| merkle_root str \| bits u32 \| nonce u64` |
+/// | `BlockHeader::encode` | 헤더 인코딩 뒤에 `transaction_count u32` |
+/// | `Block::encode` | 헤더 인코딩 뒤에 `transactions [bytes]` (각 항목은 `Transaction::encode`) |
+///
+/// 헤더는 nonce를 마지막에 두어 채굴할 때 앞부분의 해시 상태를 재사용합니다.
+/// 블록 해시는 인코딩에 포함하지 않고 디코딩할 때 다시 계산합니다. 예시 값은 `VECTOR_*` 상수를 참고하세요.
+pub const ENCODING_VERSION: u8 = 1;
+
+// --- 예시 값 (encode → decode → encode 결과가 같아야 합니다) ---
+
+/// `Transaction::new_coinbase("bg1miner", 100, 1)` (timestamp = 1700000000)의 `encode` 결과
+pub const VECTOR_COINBASE_TX_HEX: &str = concat!(
+    "01", "0f000000", "636f696e626173655f726577617264", "08000000", "6267316d696e6572",
+    "6400000000000000", "0000000000000000", "00f1536500000000", "0000000000000000", "0000000000000000",
+    "00000000", "00000000", "010100000000000000",
+    "08000000", "6267316d696e6572", "14000000", "554e5349474e45445f434f494e424153455f5458",
+);
+/// 위 코인베이스 트랜잭션의 ID (`calculate_hash_for_signing`)
+pub const VECTOR_COINBASE_TXID: &str = "a2ed14ac7741ef8365ff4cedf4314bc8e9b6e9d9f3a4bda2ae9506f397ea055a";
+
+/// 송신자 "ab", 수신자 "c", 금액 1, timestamp 0인 트랜잭션의 ID
+pub const VECTOR_AB_C_TXID: &str = "3bd0b0872a5eb5f7304b1f0047eac3f21bd9ae51734f2fff0337fdd77b9266db";
+/// 송신자 "a", 수신자 "bc", 금액 1, timestamp 0인 트랜잭션의 ID (`VECTOR_AB_C_TXID`와 달라야 합니다)
+pub const VECTOR_A_BC_TXID: &str = "e7b3ec1bbd17b6d018a960baeee4e330edc2e8e9de45b7b54a937b668287db43";
+
+/// 위 코인베이스 하나만 담은 블록 (index 1, timestamp 1700000000, previous_hash "00" x 32, bits 0x207fffff, nonce 0)의
+/// `header_bytes` 결과
+pub const VECTOR_BLOCK_HEADER_HEX: &str = concat!(
+    "01", "0100000000000000", "00f1536500000000",
+    "40000000", "30303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030",
+    "40000000", "64666631323136306436613962353165303261666633623834333339656537326462613539643965393539336532386631333737663133343464373539373033",
+    "ffff7f20", "0000000000000000",
+);
+/// 위 블록의 해시
+pub const VECTOR_BLOCK_HASH: &str = "998e06a7e3b425801b5c6cc0c3362bf03791c54384c93bb5ea80cb9ed3477241";
+
+/// 값을 정규 바이너리 형식으로 기록합니다.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+
+    /// `ENCODING_VERSION`으로 시작하는 최상위 인코딩을 시작합니다.
+    pub fn versioned() -> Self {
+        let mut encoder = Encoder::new();
+        encoder.u8(ENCODING_VERSION);
+        encoder
+    }
+
+    pub fn u8(&mut self, value: u8) -> &mut Self {
+        self.bytes.push(value);
+        self
+    }
+
+    pub fn u32(&mut self, value: u32) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn u64(&mut self, value: u64) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn i64(&mut self, value: i64) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn u128(&mut self, value: u128) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// 길이(`u32`)를 앞에 붙인 바이트열
+    pub fn bytes(&mut self, value: &[u8]) -> &mut Self {
+        self.count(value.len());
+        self.bytes.extend_from_slice(value);
+        self
+    }
+
+    /// 길이(`u32`)를 앞에 붙인 UTF-8 문자열
+    pub fn str(&mut self, value: &str) -> &mut Self {
+        self.bytes(value.as_bytes())
+    }
+
+    /// 목록의 항목 수. 이어서 각 항목을 기록합니다.
+    pub fn count(&mut self, count: usize) -> &mut Self {
+        // 프레임 크기 제한(`protocol::MAX_FRAME_SIZE`)보다 훨씬 크므로 실제로 넘칠 일은 없습니다.
+        self.u32(u32::try_from(count).expect("encoded length exceeds u32"))
+    }
+
+    pub fn option_u64(&mut self, value: Option<u64>) -> &mut Self {
+        match value {
+            Some(value) => self.u8(1).u64(value),
+            None => self.u8(0),
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// 정규 바이너리 형식의 값을 읽습니다. 남은 바이트보다 긴 값을 읽으려 하면 오류를 반환합니다.
+#[derive(Debug)]
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Decoder { bytes }
+    }
+
+    /// 최상위 인코딩의 버전 바이트를 읽고 지원하는 버전인지 확인합니다.
+    pub fn version(&mut self) -> Result<u8, BlockchainError> {
+        let version = self.u8()?;
+        if version != ENCODING_VERSION {
+            return Err(malformed(format!("unsupported encoding version {}", version)));
+        }
+        Ok(version)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, BlockchainError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32, BlockchainError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, BlockchainError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub fn i64(&mut self) -> Result<i64, BlockchainError> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    pub fn u128(&mut self) -> Result<u128, BlockchainError> {
+        Ok(u128::from_le_bytes(self.array()?))
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8], BlockchainError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    pub fn str(&mut self) -> Result<String, BlockchainError> {
+        let bytes = self.bytes()?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| malformed("string is not valid UTF-8".to_string()))
+    }
+
+    /// 목록의 항목 수. 항목마다 최소 1바이트가 필요하므로 남은 바이트보다 많으면 오류입니다.
+    pub fn count(&mut self) -> Result<usize, BlockchainError> {
+        let count = self.u32()? as usize;
+        if count > self.bytes.len() {
+            return Err(malformed(format!("list of {} items exceeds remaining {} bytes", count, self.bytes.len())));
+        }
+        Ok(count)
+    }
+
+    pub fn option_u64(&mut self) -> Result<Option<u64>, BlockchainError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.u64()?)),
+            tag => Err(malformed(format!("invalid option tag {}", tag))),
+        }
+    }
+
+    /// 모든 바이트를 읽었는지 확인합니다. 뒤에 남은 바이트가 있으면 정규 인코딩이 아닙니다.
+    pub fn finish(self) -> Result<(), BlockchainError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(malformed(format!("{} trailing bytes", self.bytes.len())))
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BlockchainError> {
+        if len > self.bytes.len() {
+            return Err(malformed(format!("unexpected end of input (need {} bytes, have {})", len, self.bytes.len())));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], BlockchainError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+}
+
+fn malformed(reason: String) -> BlockchainError {
+    BlockchainError::MalformedEncoding(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::Block;
+    use crate::transaction::Transaction;
+
+    fn coinbase_vector() -> Transaction {
+        let mut tx = Transaction::new_coinbase("bg1miner".to_string(), 100, 1);
+        tx.timestamp = 1_700_000_000;
+        tx
+    }
+
+    fn block_vector() -> Block {
+        let mut block = Block::new(1, "00".repeat(32), vec![coinbase_vector()]);
+        block.timestamp = 1_700_000_000;
+        block.bits = 0x207f_ffff;
+        block
+    }
+
+    fn is_malformed<T>(result: Result<T, BlockchainError>) -> bool {
+        matches!(result, Err(BlockchainError::MalformedEncoding(_)))
+    }
+
+    #[test]
+    fn coinbase_vector_round_trips() {
+        let bytes = hex::decode(VECTOR_COINBASE_TX_HEX).unwrap();
+        let tx = Transaction::decode(&bytes).unwrap();
+        assert_eq!(hex::encode(tx.encode()), VECTOR_COINBASE_TX_HEX);
+        assert_eq!(tx.calculate_hash_for_signing(), VECTOR_COINBASE_TXID);
+        assert_eq!(hex::encode(coinbase_vector().encode()), VECTOR_COINBASE_TX_HEX);
+    }
+
+    #[test]
+    fn block_vector_matches_header_and_hash() {
+        let mut block = block_vector();
+        assert_eq!(hex::encode(block.header_bytes()), VECTOR_BLOCK_HEADER_HEX);
+        assert_eq!(block.calculate_hash(), VECTOR_BLOCK_HASH);
+
+        block.hash = block.calculate_hash();
+        let bytes = block.encode();
+        let decoded = Block::decode(&bytes).unwrap();
+        assert_eq!(decoded.encode(), bytes);
+        assert_eq!(decoded.hash, VECTOR_BLOCK_HASH);
+
+        let header = block.header();
+        assert_eq!(crate::block::BlockHeader::decode(&header.encode()).unwrap(), header);
+    }
+
+    #[test]
+    fn field_boundaries_are_unambiguous() {
+        let mut ab_c = Transaction::new("ab".to_string(), "c".to_string(), 1);
+        ab_c.timestamp = 0;
+        let mut a_bc = Transaction::new("a".to_string(), "bc".to_string(), 1);
+        a_bc.timestamp = 0;
+        assert_eq!(ab_c.calculate_hash_for_signing(), VECTOR_AB_C_TXID);
+        assert_eq!(a_bc.calculate_hash_for_signing(), VECTOR_A_BC_TXID);
+        assert_ne!(VECTOR_AB_C_TXID, VECTOR_A_BC_TXID);
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let mut bytes = hex::decode(VECTOR_COINBASE_TX_HEX).unwrap();
+        bytes.push(0);
+        assert!(is_malformed(Transaction::decode(&bytes)));
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let mut bytes = hex::decode(VECTOR_COINBASE_TX_HEX).unwrap();
+        bytes[0] = ENCODING_VERSION + 1;
+        assert!(is_malformed(Transaction::decode(&bytes)));
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let bytes = hex::decode(VECTOR_COINBASE_TX_HEX).unwrap();
+        // 버전 바이트 뒤 송신자 길이(u32)가 중간에 끊긴 경우
+        assert!(is_malformed(Transaction::decode(&bytes[..3])));
+        // 길이는 온전하지만 내용이 모자란 경우
+        assert!(is_malformed(Transaction::decode(&bytes[..bytes.len() - 1])));
+    }
+
+    #[test]
+    fn rejects_oversized_count() {
+        let mut encoder = Encoder::new();
+        encoder.count(u32::MAX as usize).u8(0);
+        let bytes = encoder.into_bytes();
+        assert!(is_malformed(Decoder::new(&bytes).count()));
+        // 블록의 트랜잭션 수도 같은 검사를 거칩니다.
+        let mut block_bytes = block_vector().header_bytes();
+        block_bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(is_malformed(Block::decode(&block_bytes)));
+    }
+}
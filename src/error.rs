@@ -0,0 +1,205 @@
+// src/error.rs
+use super::transaction::OutPoint; // transaction 모듈의 OutPoint 구조체 사용
+use std::fmt;
+use std::io;
+
+/// 블록체인 전반에서 사용하는 오류 유형입니다.
+/// 트랜잭션이나 블록이 거부된 정확한 이유를 호출자(서버, P2P)에게 전달합니다.
+#[derive(Debug)]
+pub enum BlockchainError {
+    // --- 트랜잭션 형식 및 서명 ---
+    /// 서명이나 공개 키가 비어 있습니다.
+    Unsigned,
+    /// 금액이 0입니다.
+    ZeroAmount,
+    /// 송신자 또는 수신자 주소가 비어 있습니다.
+    EmptyAddress,
+    /// UTXO 입력/출력 구조가 올바르지 않습니다.
+    InvalidOutputs(String),
+    /// 코인베이스 트랜잭션의 형식이 올바르지 않습니다.
+    InvalidCoinbase(String),
+    /// 송신자 주소가 서명한 공개 키의 해시와 일치하지 않습니다.
+    SenderKeyMismatch,
+    /// 주소 형식이 올바르지 않습니다. (접두사, 문자, 체크섬, 길이)
+    InvalidAddress { address: String, reason: String },
+    /// 주소가 트랜잭션이나 노드의 네트워크용이 아닙니다.
+    WrongNetwork { address: String, chain_id: u64 },
+    /// 키를 해석할 수 없습니다. (16진수 형식, 곡선 위의 점/스칼라가 아님 등)
+    MalformedKey(String),
+    /// 서명을 해석할 수 없습니다.
+    MalformedSignature(String),
+    /// 서명 검증에 실패했습니다.
+    BadSignature,
+
+    // --- 대기열 추가 및 자금 ---
+    /// 코인베이스 트랜잭션은 대기열에 추가할 수 없습니다.
+    CoinbaseNotAllowed,
+    /// 트랜잭션의 네트워크 식별자가 다릅니다.
+    WrongChainId { expected: u64, actual: u64 },
+    /// 트랜잭션 nonce가 기대값과 다릅니다. (재전송 또는 순서 어긋남)
+    InvalidNonce { expected: u64, actual: u64 },
+    /// 잔액이 부족합니다.
+    InsufficientFunds { available: u64, required: u64 },
+    /// 현재 장부 방식에서 받을 수 없는 트랜잭션입니다.
+    WrongLedgerMode(String),
+    /// 대기열이 가득 찼습니다. (초과한 제한)
+    MempoolFull(String),
+    /// 존재하지 않거나 이미 소비된 출력을 입력으로 사용합니다.
+    UnknownInput(OutPoint),
+    /// 같은 출력을 한 트랜잭션에서 두 번 사용합니다.
+    DuplicateInput(OutPoint),
+    /// 송신자가 소유하지 않은 출력을 입력으로 사용합니다.
+    InputNotOwned(OutPoint),
+    /// 입력 금액의 합이 출력 금액과 수수료의 합과 다릅니다.
+    UnbalancedTransaction { inputs: u64, outputs: u64 },
+    /// 같은 출력 참조가 이미 존재합니다.
+    DuplicateOutput(OutPoint),
+    /// 금액 계산 중 오버플로가 발생했습니다.
+    AmountOverflow,
+
+    // --- 블록 및 체인 ---
+    /// 체인에 블록이 없습니다.
+    EmptyChain,
+    /// 블록 인덱스가 체인 높이와 맞지 않습니다.
+    InvalidBlockIndex { expected: u64, actual: u64 },
+    /// 블록 해시가 블록 내용과 일치하지 않습니다.
+    InvalidBlockHash { index: u64 },
+    /// 머클 루트가 블록의 트랜잭션과 일치하지 않습니다.
+    InvalidMerkleRoot { index: u64 },
+    /// 이전 블록 해시가 체인과 이어지지 않습니다.
+    BrokenLink { index: u64 },
+    /// 블록이 해당 높이에서 요구되는 목표값을 사용하지 않았습니다.
+    WrongTarget { index: u64, expected: u32, actual: u32 },
+    /// 블록의 목표값(`bits`)을 해석할 수 없습니다.
+    InvalidTarget { index: u64, bits: u32 },
+    /// 블록 해시가 목표값보다 큽니다. (작업 증명을 충족하지 않음)
+    InsufficientWork { index: u64 },
+    /// 블록 타임스탬프가 허용 범위를 벗어났습니다.
+    InvalidTimestamp { index: u64, timestamp: i64 },
+    /// 블록의 트랜잭션 총 크기가 제한을 넘습니다.
+    BlockTooLarge { index: u64, size: usize, limit: usize },
+    /// 블록에 포함된 트랜잭션이 유효하지 않습니다.
+    InvalidBlockTransaction { index: u64, position: usize, reason: Box<BlockchainError> },
+    /// 채굴한 블록이 현재 tip을 잇지 않습니다. (채굴 중 다른 블록이 먼저 연결됨)
+    StaleBlock { index: u64 },
+    /// 블록의 첫 번째 트랜잭션이 코인베이스가 아닙니다.
+    MissingCoinbase { index: u64 },
+    /// 코인베이스가 보조금과 수수료의 합보다 많이 지급합니다.
+    ExcessiveCoinbase { index: u64, claimed: u64, allowed: u64 },
+    /// 체인 매개변수(프리셋 또는 제네시스 파일)가 올바르지 않습니다.
+    InvalidChainParams(String),
+    /// 제네시스 블록이 네트워크 매개변수로 만든 블록과 다릅니다.
+    GenesisMismatch { expected: String, actual: String },
+
+    // --- 인코딩 및 저장소 ---
+    /// 바이너리 인코딩(encoding 모듈)을 해석할 수 없습니다.
+    MalformedEncoding(String),
+    /// 블록 저장소 입출력 오류
+    Storage(io::Error),
+}
+
+impl fmt::Display for BlockchainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockchainError::Unsigned => write!(f, "transaction is not signed or public key is missing"),
+            BlockchainError::ZeroAmount => write!(f, "transaction amount must be positive"),
+            BlockchainError::EmptyAddress => write!(f, "sender or recipient address is empty"),
+            BlockchainError::InvalidOutputs(reason) => write!(f, "invalid transaction outputs: {}", reason),
+            BlockchainError::InvalidCoinbase(reason) => write!(f, "invalid coinbase transaction: {}", reason),
+            BlockchainError::SenderKeyMismatch => write!(f, "sender address does not match public key"),
+            BlockchainError::InvalidAddress { address, reason } => write!(f, "invalid address {}: {}", address, reason),
+            BlockchainError::WrongNetwork { address, chain_id } => {
+                write!(f, "address {} does not belong to chain {}", address, chain_id)
+            }
+            BlockchainError::MalformedKey(reason) => write!(f, "malformed key: {}", reason),
+            BlockchainError::MalformedSignature(reason) => write!(f, "malformed signature: {}", reason),
+            BlockchainError::BadSignature => write!(f, "invalid signature for transaction"),
+            BlockchainError::CoinbaseNotAllowed => write!(f, "coinbase transactions cannot be added to pending pool"),
+            BlockchainError::WrongChainId { expected, actual } => {
+                write!(f, "transaction chain id {} does not match {}", actual, expected)
+            }
+            BlockchainError::InvalidNonce { expected, actual } => {
+                write!(f, "transaction nonce {} does not match expected nonce {}", actual, expected)
+            }
+            BlockchainError::InsufficientFunds { available, required } => {
+                write!(f, "insufficient funds: available {}, required {}", available, required)
+            }
+            BlockchainError::WrongLedgerMode(reason) => write!(f, "{}", reason),
+            BlockchainError::MempoolFull(limit) => write!(f, "pending pool is full (limit: {})", limit),
+            BlockchainError::UnknownInput(input) => {
+                write!(f, "input {}:{} is unknown or already spent", input.txid, input.index)
+            }
+            BlockchainError::DuplicateInput(input) => {
+                write!(f, "input {}:{} is spent twice in the same transaction", input.txid, input.index)
+            }
+            BlockchainError::InputNotOwned(input) => {
+                write!(f, "input {}:{} is not owned by sender", input.txid, input.index)
+            }
+            BlockchainError::UnbalancedTransaction { inputs, outputs } => {
+                write!(f, "input total {} does not match outputs plus fee {}", inputs, outputs)
+            }
+            BlockchainError::DuplicateOutput(output) => {
+                write!(f, "output {}:{} already exists", output.txid, output.index)
+            }
+            BlockchainError::AmountOverflow => write!(f, "amount overflow"),
+            BlockchainError::EmptyChain => write!(f, "chain has no blocks"),
+            BlockchainError::InvalidBlockIndex { expected, actual } => {
+                write!(f, "invalid block index: expected {}, got {}", expected, actual)
+            }
+            BlockchainError::InvalidBlockHash { index } => write!(f, "invalid block hash at index {}", index),
+            BlockchainError::InvalidMerkleRoot { index } => write!(f, "invalid merkle root at index {}", index),
+            BlockchainError::BrokenLink { index } => {
+                write!(f, "block {} does not link to the previous block hash", index)
+            }
+            BlockchainError::WrongTarget { index, expected, actual } => {
+                write!(f, "block {} has target bits {:08x}, expected {:08x}", index, actual, expected)
+            }
+            BlockchainError::InvalidTarget { index, bits } => {
+                write!(f, "block {} has invalid target bits {:08x}", index, bits)
+            }
+            BlockchainError::InsufficientWork { index } => {
+                write!(f, "block {} hash is above its target", index)
+            }
+            BlockchainError::InvalidTimestamp { index, timestamp } => {
+                write!(f, "block {} has invalid timestamp {}", index, timestamp)
+            }
+            BlockchainError::BlockTooLarge { index, size, limit } => {
+                write!(f, "block {} exceeds size limit: {} > {}", index, size, limit)
+            }
+            BlockchainError::InvalidBlockTransaction { index, position, reason } => {
+                write!(f, "invalid transaction in block {} at position {}: {}", index, position, reason)
+            }
+            BlockchainError::StaleBlock { index } => {
+                write!(f, "block {} does not extend the current tip", index)
+            }
+            BlockchainError::MissingCoinbase { index } => {
+                write!(f, "block {} does not start with a coinbase transaction", index)
+            }
+            BlockchainError::ExcessiveCoinbase { index, claimed, allowed } => {
+                write!(f, "block {} coinbase pays {}, more than subsidy plus fees {}", index, claimed, allowed)
+            }
+            BlockchainError::InvalidChainParams(reason) => write!(f, "invalid chain parameters: {}", reason),
+            BlockchainError::GenesisMismatch { expected, actual } => {
+                write!(f, "genesis block mismatch: expected {}, got {}", expected, actual)
+            }
+            BlockchainError::MalformedEncoding(reason) => write!(f, "malformed encoding: {}", reason),
+            BlockchainError::Storage(e) => write!(f, "storage error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BlockchainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BlockchainError::InvalidBlockTransaction { reason, .. } => Some(reason.as_ref()),
+            BlockchainError::Storage(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BlockchainError {
+    fn from(e: io::Error) -> Self {
+        BlockchainError::Storage(e)
+    }
+}
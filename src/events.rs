@@ -0,0 +1,49 @@
+// src/events.rs
+use super::block::Block;             // block 모듈의 Block 구조체 사용
+use super::transaction::Transaction; // transaction 모듈의 Transaction 구조체 사용
+use tokio::sync::broadcast;
+use std::sync::Arc;
+
+/// 구독자가 처리하지 못하고 쌓아 둘 수 있는 최대 이벤트 수.
+/// 이보다 뒤처진 구독자는 놓친 이벤트 수를 통보받습니다.
+pub const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// 블록체인 상태가 바뀔 때 발생하는 이벤트입니다.
+#[derive(Debug, Clone)]
+pub enum ChainEvent {
+    /// 메인 체인 끝에 블록이 연결되었습니다. (채굴, 수신, 재구성 모두 포함)
+    BlockConnected(Arc<Block>),
+    /// 트랜잭션이 대기열에 추가되었습니다.
+    TransactionAccepted(Arc<Transaction>),
+}
+
+/// 블록체인 내부에서 발생한 이벤트를 여러 구독자에게 전달하는 이벤트 버스입니다.
+/// 구독자가 없으면 이벤트는 버려지며, 이벤트 발행은 블록체인 잠금을 잡은 채로도 막히지 않습니다.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ChainEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        EventBus { sender }
+    }
+
+    /// 이후에 발행되는 이벤트를 받을 수신자를 만듭니다.
+    pub fn subscribe(&self) -> broadcast::Receiver<ChainEvent> {
+        self.sender.subscribe()
+    }
+
+    /// 이벤트를 발행합니다.
+    pub fn publish(&self, event: ChainEvent) {
+        // 구독자가 없을 때의 오류는 무시합니다.
+        let _ = self.sender.send(event);
+    }
+}
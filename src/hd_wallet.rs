@@ -0,0 +1,190 @@
+// src/hd_wallet.rs
+use super::address::Network;              // address 모듈의 네트워크 유형 사용
+use super::error::BlockchainError;        // error 모듈의 오류 유형 사용
+use super::node_client::{ClientError, NodeClient}; // 주소 탐색 시 노드에 질의
+use super::wallet::Wallet;                // wallet 모듈의 Wallet 구조체 사용
+use bip32::{ChildNumber, DerivationPath, Language, Mnemonic, XPrv};
+use hex::encode;
+use rand_core::OsRng;
+
+/// BIP-44 경로에 사용하는 코인 유형. SLIP-44에 등록된 값이 아니므로 다른 체인의 키와 겹치지 않습니다.
+pub const COIN_TYPE: u32 = 5656;
+
+/// 주소 탐색 시 연속으로 사용되지 않은 주소가 이만큼 나오면 탐색을 멈춥니다. (BIP-44 권장값)
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// 새 BIP-39 시드 문구(영어 24단어)를 무작위로 생성합니다.
+/// 이 문구만 있으면 같은 계층적 지갑의 모든 키를 다시 만들 수 있으므로 안전하게 보관해야 합니다.
+pub fn generate_mnemonic() -> String {
+    Mnemonic::random(OsRng, Language::English).phrase().to_string()
+}
+
+/// 시드 문구 하나로 여러 주소를 만드는 BIP-32 계층적 결정 지갑입니다.
+///
+/// 주소는 `m/44'/COIN_TYPE'/account'/0/index` 경로에서 파생되며,
+/// 같은 시드 문구, 암호 문구, 계정 번호, 네트워크로는 항상 같은 주소들이 만들어집니다.
+#[derive(Debug, Clone)]
+pub struct HdWallet {
+    account: u32,
+    network: Network,
+    receive_key: XPrv, // m/44'/COIN_TYPE'/account'/0 확장 개인 키
+}
+
+/// 주소 탐색에서 찾은 사용된 주소입니다.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredAddress {
+    pub index: u32,
+    pub address: String,
+    pub balance: u64,
+    pub transaction_count: usize,
+}
+
+/// 주소 탐색 결과입니다.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discovery {
+    pub used: Vec<DiscoveredAddress>, // 트랜잭션 기록이 있는 주소 (인덱스 순서)
+    pub next_index: u32,              // 마지막으로 사용된 주소 다음 인덱스 (새 수신 주소에 사용)
+}
+
+impl HdWallet {
+    /// 시드 문구와 선택적 암호 문구(BIP-39 passphrase)로 지갑을 복원합니다.
+    /// 단어 사이의 공백과 대소문자는 정규화하며, 단어 목록과 체크섬이 맞지 않으면 오류를 반환합니다.
+    pub fn from_mnemonic(phrase: &str, passphrase: &str, account: u32, network: Network) -> Result<Self, BlockchainError> {
+        let normalized = phrase.split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mnemonic = Mnemonic::new(normalized, Language::English)
+            .map_err(|_| BlockchainError::MalformedKey("invalid mnemonic phrase".to_string()))?;
+        let seed = mnemonic.to_seed(passphrase);
+
+        let path: DerivationPath = format!("m/44'/{}'/{}'/0", COIN_TYPE, account).parse()
+            .map_err(|e| BlockchainError::MalformedKey(format!("invalid derivation path: {}", e)))?;
+        let receive_key = XPrv::derive_from_path(seed.as_bytes(), &path)
+            .map_err(|e| BlockchainError::MalformedKey(format!("key derivation failed: {}", e)))?;
+        Ok(HdWallet { account, network, receive_key })
+    }
+
+    /// `index`번째 주소의 파생 경로
+    pub fn derivation_path(&self, index: u32) -> String {
+        format!("m/44'/{}'/{}'/0/{}", COIN_TYPE, self.account, index)
+    }
+
+    /// `index`번째 주소의 지갑(개인 키 포함)을 파생합니다.
+    /// BIP-32에 따라 드물게 유효하지 않은 키가 나오는 인덱스는 오류를 반환하며, 호출자는 다음 인덱스를 사용해야 합니다.
+    pub fn derive_wallet(&self, index: u32) -> Result<Wallet, BlockchainError> {
+        let child_number = ChildNumber::new(index, false)
+            .map_err(|e| BlockchainError::MalformedKey(format!("invalid child index {}: {}", index, e)))?;
+        let child = self.receive_key.derive_child(child_number)
+            .map_err(|e| BlockchainError::MalformedKey(format!("key derivation failed: {}", e)))?;
+        Wallet::from_private_key_hex(&encode(child.to_bytes()), self.network)
+    }
+
+    /// 노드에 주소별 트랜잭션 기록을 질의하여 사용된 주소를 찾습니다.
+    /// 인덱스 0부터 차례로 확인하며, 연속으로 `gap_limit`개의 주소가 사용되지 않았으면 멈춥니다.
+    pub async fn discover(&self, client: &mut NodeClient, gap_limit: u32) -> Result<Discovery, ClientError> {
+        let mut used = Vec::new();
+        let mut next_index = 0;
+        let mut index: u32 = 0;
+        let mut gap = 0;
+        while gap < gap_limit {
+            // 유효하지 않은 키가 나오는 인덱스는 건너뜁니다.
+            if let Ok(wallet) = self.derive_wallet(index) {
+                let (transaction_count, _) = client.get_address_history(wallet.get_address(), 0, 0).await?;
+                if transaction_count > 0 {
+                    let balance = client.get_balance(wallet.get_address()).await?;
+                    used.push(DiscoveredAddress {
+                        index,
+                        address: wallet.get_address().to_string(),
+                        balance,
+                        transaction_count,
+                    });
+                    next_index = index + 1;
+                    gap = 0;
+                } else {
+                    gap += 1;
+                }
+            }
+            index = match index.checked_add(1) {
+                Some(index) => index,
+                None => break,
+            };
+        }
+        Ok(Discovery { used, next_index })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP-39 공식 테스트 벡터 (엔트로피 0x00 * 32, 암호 문구 "TREZOR")
+    const VECTOR_PASSPHRASE: &str = "TREZOR";
+    const VECTOR_SEED: &str = "bda85446c68413707090a52022edd26a1c9462295029f2e60cd7c4f2bbd3097170af7a4d73245cafa9c3cca8d561a7c3de6f5d4a10be8ed2a5e608d68f92fcc8";
+    const VECTOR_XPRV: &str = "xprv9s21ZrQH143K32qBagUJAMU2LsHg3ka7jqMcV98Y7gVeVyNStwYS3U7yVVoDZ4btbRNf4h6ibWpY22iRmXq35qgLs79f312g2kj5539ebPM";
+    // 위 시드에서 m/44'/5656'/0'/0/0 경로로 파생한 개인 키와 메인넷 주소
+    const VECTOR_KEY: &str = "d59788082c0208dfabf517f1c1cd5997a8b184d4a8bf1da951ac867e504205d4";
+    const VECTOR_ADDRESS: &str = "bg14lxqs5rlr9m5zdu0x65n2gck3app0yt4sf90pz";
+
+    fn vector_phrase() -> String {
+        ["abandon"; 23].join(" ") + " art"
+    }
+
+    #[test]
+    fn known_mnemonic_derives_the_vector_keys() {
+        let mnemonic = Mnemonic::new(vector_phrase(), Language::English).unwrap();
+        let seed = mnemonic.to_seed(VECTOR_PASSPHRASE);
+        assert_eq!(encode(seed.as_bytes()), VECTOR_SEED);
+        let root = XPrv::new(seed.as_bytes()).unwrap();
+        assert_eq!(root, VECTOR_XPRV.parse::<XPrv>().unwrap());
+
+        let hd = HdWallet::from_mnemonic(&vector_phrase(), VECTOR_PASSPHRASE, 0, Network::Mainnet).unwrap();
+        assert_eq!(hd.derivation_path(0), "m/44'/5656'/0'/0/0");
+        let wallet = hd.derive_wallet(0).unwrap();
+        assert_eq!(wallet.get_private_key_hex(), VECTOR_KEY);
+        assert_eq!(wallet.get_address(), VECTOR_ADDRESS);
+
+        // 루트 키에서 한 단계씩 파생한 결과와도 같아야 합니다.
+        let child = [44 | ChildNumber::HARDENED_FLAG, COIN_TYPE | ChildNumber::HARDENED_FLAG, ChildNumber::HARDENED_FLAG, 0, 0]
+            .into_iter()
+            .fold(root, |key, index| key.derive_child(ChildNumber(index)).unwrap());
+        assert_eq!(encode(child.to_bytes()), VECTOR_KEY);
+    }
+
+    #[test]
+    fn restoring_the_same_phrase_derives_the_same_addresses() {
+        let phrase = generate_mnemonic();
+        assert_eq!(phrase.split_whitespace().count(), 24);
+        let first = HdWallet::from_mnemonic(&phrase, "", 0, Network::Regtest).unwrap();
+        // 공백과 대소문자는 정규화됩니다.
+        let messy = format!("  {}\n", phrase.to_uppercase().replace(' ', "   "));
+        let restored = HdWallet::from_mnemonic(&messy, "", 0, Network::Regtest).unwrap();
+        let addresses = |hd: &HdWallet| (0..3).map(|index| hd.derive_wallet(index).unwrap().address).collect::<Vec<_>>();
+        assert_eq!(addresses(&first), addresses(&restored));
+
+        // 암호 문구, 계정 번호가 다르면 다른 키가 나옵니다.
+        let with_passphrase = HdWallet::from_mnemonic(&phrase, "secret", 0, Network::Regtest).unwrap();
+        let other_account = HdWallet::from_mnemonic(&phrase, "", 1, Network::Regtest).unwrap();
+        assert_ne!(addresses(&first), addresses(&with_passphrase));
+        assert_ne!(addresses(&first), addresses(&other_account));
+        let mainnet = HdWallet::from_mnemonic(&phrase, "", 0, Network::Mainnet).unwrap();
+        assert_eq!(
+            mainnet.derive_wallet(0).unwrap().get_private_key_hex(),
+            first.derive_wallet(0).unwrap().get_private_key_hex()
+        );
+        assert_ne!(mainnet.derive_wallet(0).unwrap().address, first.derive_wallet(0).unwrap().address);
+    }
+
+    #[test]
+    fn rejects_invalid_mnemonics() {
+        let bad_checksum = ["abandon"; 24].join(" ");
+        let unknown_word = ["abandon"; 23].join(" ") + " bingry";
+        let too_short = ["abandon"; 22].join(" ") + " art";
+        for phrase in [bad_checksum.as_str(), unknown_word.as_str(), too_short.as_str(), ""] {
+            assert!(matches!(
+                HdWallet::from_mnemonic(phrase, "", 0, Network::Regtest),
+                Err(BlockchainError::MalformedKey(_))
+            ), "accepted {:?}", phrase);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+// src/index.rs
+use super::block::Block;                                  // block 모듈의 Block 구조체 사용
+use super::transaction::{OutPoint, Transaction, TxOutput}; // transaction 모듈의 UTXO 타입 사용
+use super::utxo::UtxoSet;                                 // utxo 모듈의 UtxoSet 구조체 사용
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+/// 메인 체인에서 트랜잭션의 위치입니다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxLocation {
+    pub block_index: u64, // 트랜잭션이 포함된 블록의 인덱스
+    pub position: usize,  // 블록 내 트랜잭션 위치
+}
+
+/// 조회한 트랜잭션과 그 위치입니다. 대기열에 있는 트랜잭션이면 `location`이 None입니다.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionInfo {
+    pub transaction: Transaction,
+    pub location: Option<TxLocation>,
+}
+
+// UTXO 집합에 적용된 트랜잭션 하나의 변경 내용
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AppliedTransaction {
+    txid: String,
+    output_count: usize,                // 새로 만든 출력 수
+    spent: Vec<(OutPoint, TxOutput)>,   // 소비한 출력
+}
+
+// 블록 하나를 UTXO 집합에 적용하며 바뀐 내용. 블록을 분리할 때 되돌리는 데 사용합니다.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct BlockUndo {
+    applied: Vec<AppliedTransaction>,
+}
+
+/// 메인 체인의 블록이 연결/분리될 때마다 갱신되는 조회용 색인입니다.
+/// - 주소별 잔액 (계정 모드와 UTXO 모드 각각)
+/// - 트랜잭션 ID → (블록, 위치)
+/// - 주소별 트랜잭션 기록 (체인 순서)
+///
+/// 블록은 체인 순서대로 연결하고, 분리는 tip부터 역순으로 해야 합니다.
+/// 블록을 연결했다가 분리하면 색인은 연결하기 전과 같아집니다. (잔액이 0인 주소는 보관하지 않습니다)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainIndex {
+    account_balances: HashMap<String, u64>,
+    utxo_set: UtxoSet,
+    utxo_balances: HashMap<String, u64>,
+    utxo_undo: Vec<BlockUndo>, // 블록 인덱스 순서
+    tx_locations: HashMap<String, TxLocation>,
+    address_history: HashMap<String, Vec<TxLocation>>,
+    supply: u64, // 유통량 (코인베이스로 발행된 금액 - 수수료)
+    chain_work: u128, // 연결된 블록의 누적 작업량
+}
+
+impl ChainIndex {
+    pub fn new() -> Self {
+        ChainIndex::default()
+    }
+
+    /// 주어진 블록들을 순서대로 연결하여 색인을 만듭니다.
+    pub fn from_blocks(blocks: &[Block]) -> Self {
+        let mut index = ChainIndex::new();
+        for block in blocks.iter() {
+            index.connect_block(block);
+        }
+        index
+    }
+
+    /// 계정 모드 잔액 (받은 금액 - 보낸 금액과 수수료)
+    pub fn account_balance(&self, address: &str) -> u64 {
+        self.account_balances.get(address).copied().unwrap_or(0)
+    }
+
+    /// UTXO 모드 잔액 (소유한 미사용 출력의 합)
+    pub fn utxo_balance(&self, address: &str) -> u64 {
+        self.utxo_balances.get(address).copied().unwrap_or(0)
+    }
+
+    /// 현재 메인 체인의 미사용 출력 집합
+    pub fn utxo_set(&self) -> &UtxoSet {
+        &self.utxo_set
+    }
+
+    /// 트랜잭션 ID로 메인 체인에서의 위치를 찾습니다.
+    pub fn locate_transaction(&self, txid: &str) -> Option<TxLocation> {
+        self.tx_locations.get(txid).copied()
+    }
+
+    /// 현재 유통량. 코인베이스로 발행된 금액의 합에서 수수료를 뺀 값입니다.
+    /// (수수료는 송신자에게서 빠져나가 같은 블록의 코인베이스로 다시 지급됩니다)
+    pub fn total_supply(&self) -> u64 {
+        self.supply
+    }
+
+    /// 연결된 블록(제네시스 포함)의 작업량 합계
+    pub fn chain_work(&self) -> u128 {
+        self.chain_work
+    }
+
+    /// 주소가 관련된 트랜잭션의 위치를 체인 순서대로 반환합니다.
+    pub fn address_history(&self, address: &str) -> &[TxLocation] {
+        self.address_history.get(address).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// 메인 체인 끝에 연결된 블록을 색인에 반영합니다.
+    pub fn connect_block(&mut self, block: &Block) {
+        self.chain_work = self.chain_work.saturating_add(block.work());
+        let mut undo = BlockUndo::default();
+        for (position, tx) in block.transactions.iter().enumerate() {
+            let location = TxLocation { block_index: block.index, position };
+            let txid = tx.txid();
+
+            // 계정 모드 잔액과 유통량
+            if tx.is_coinbase() {
+                self.supply = self.supply.saturating_add(tx.amount);
+            } else {
+                self.supply = self.supply.saturating_sub(tx.fee);
+                sub_balance(&mut self.account_balances, &tx.sender, tx.total_cost());
+            }
+            add_balance(&mut self.account_balances, &tx.recipient, tx.amount);
+
+            // UTXO 집합과 UTXO 모드 잔액 (적용할 수 없는 트랜잭션은 건너뜁니다)
+            let spent: Vec<(OutPoint, TxOutput)> = tx.inputs.iter()
+                .filter_map(|input| self.utxo_set.get(input).map(|output| (input.clone(), output.clone())))
+                .collect();
+            if self.utxo_set.apply_transaction(tx).is_ok() {
+                for (_, output) in spent.iter() {
+                    sub_balance(&mut self.utxo_balances, &output.recipient, output.amount);
+                }
+                let outputs = tx.effective_outputs();
+                for output in outputs.iter() {
+                    add_balance(&mut self.utxo_balances, &output.recipient, output.amount);
+                }
+                undo.applied.push(AppliedTransaction { txid: txid.clone(), output_count: outputs.len(), spent });
+            }
+
+            // 트랜잭션 위치와 주소별 기록
+            for address in involved_addresses(tx) {
+                self.address_history.entry(address.to_string()).or_default().push(location);
+            }
+            self.tx_locations.insert(txid, location);
+        }
+        self.utxo_undo.push(undo);
+    }
+
+    /// 메인 체인 끝에서 분리된 블록을 색인에서 되돌립니다.
+    pub fn disconnect_block(&mut self, block: &Block) {
+        self.chain_work = self.chain_work.saturating_sub(block.work());
+        // UTXO 집합: 만든 출력을 지우고 소비한 출력을 되살립니다.
+        if let Some(undo) = self.utxo_undo.pop() {
+            for applied in undo.applied.into_iter().rev() {
+                for index in 0..applied.output_count {
+                    let outpoint = OutPoint { txid: applied.txid.clone(), index: index as u32 };
+                    if let Some(output) = self.utxo_set.spend(&outpoint) {
+                        sub_balance(&mut self.utxo_balances, &output.recipient, output.amount);
+                    }
+                }
+                for (outpoint, output) in applied.spent {
+                    add_balance(&mut self.utxo_balances, &output.recipient, output.amount);
+                    self.utxo_set.insert(outpoint, output);
+                }
+            }
+        }
+
+        for tx in block.transactions.iter().rev() {
+            // 계정 모드 잔액과 유통량
+            sub_balance(&mut self.account_balances, &tx.recipient, tx.amount);
+            if tx.is_coinbase() {
+                self.supply = self.supply.saturating_sub(tx.amount);
+            } else {
+                self.supply = self.supply.saturating_add(tx.fee);
+                add_balance(&mut self.account_balances, &tx.sender, tx.total_cost());
+            }
+
+            // 트랜잭션 위치와 주소별 기록 (이 블록의 기록은 항상 맨 뒤에 있습니다)
+            for address in involved_addresses(tx) {
+                if let Some(history) = self.address_history.get_mut(address) {
+                    history.pop();
+                    if history.is_empty() {
+                        self.address_history.remove(address);
+                    }
+                }
+            }
+            self.tx_locations.remove(&tx.txid());
+        }
+    }
+
+}
+
+// 잔액을 더합니다. 더한 뒤에도 0이면 기록하지 않습니다.
+fn add_balance(balances: &mut HashMap<String, u64>, address: &str, amount: u64) {
+    if amount > 0 {
+        let balance = balances.entry(address.to_string()).or_insert(0);
+        *balance = balance.saturating_add(amount);
+    }
+}
+
+// 잔액을 뺍니다. 0이 되면 주소를 지웁니다.
+fn sub_balance(balances: &mut HashMap<String, u64>, address: &str, amount: u64) {
+    if let Some(balance) = balances.get_mut(address) {
+        *balance = balance.saturating_sub(amount);
+        if *balance == 0 {
+            balances.remove(address);
+        }
+    }
+}
+
+/// 트랜잭션과 관련된 주소 목록 (중복 없음). 코인베이스의 송신자는 제외합니다.
+fn involved_addresses(tx: &Transaction) -> Vec<&str> {
+    let mut addresses: Vec<&str> = Vec::new();
+    let candidates = std::iter::once(tx.sender.as_str())
+        .filter(|_| !tx.is_coinbase())
+        .chain(std::iter::once(tx.recipient.as_str()))
+        .chain(tx.outputs.iter().map(|output| output.recipient.as_str()));
+    for address in candidates {
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    addresses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(index: u64, transactions: Vec<Transaction>) -> Block {
+        let mut block = Block::new(index, format!("parent-{}", index), transactions);
+        block.bits = 0x207f_ffff;
+        block
+    }
+
+    fn account_transfer(sender: &str, recipient: &str, amount: u64, fee: u64) -> Transaction {
+        let mut tx = Transaction::new(sender.to_string(), recipient.to_string(), amount);
+        tx.fee = fee;
+        tx
+    }
+
+    // alice가 코인베이스 두 개를 받은 뒤의 색인과 각 코인베이스 출력
+    fn funded_index() -> (ChainIndex, OutPoint, OutPoint) {
+        let first = Transaction::new_coinbase("alice".to_string(), 100, 0);
+        let second = Transaction::new_coinbase("alice".to_string(), 50, 1);
+        let outpoints = (OutPoint { txid: first.txid(), index: 0 }, OutPoint { txid: second.txid(), index: 0 });
+        let index = ChainIndex::from_blocks(&[block(0, vec![first]), block(1, vec![second])]);
+        (index, outpoints.0, outpoints.1)
+    }
+
+    #[test]
+    fn disconnect_restores_the_index_before_connect() {
+        let (mut index, first, _) = funded_index();
+        let before = index.clone();
+
+        let mut spend = Transaction::new_utxo(
+            "alice".to_string(),
+            "bob".to_string(),
+            vec![first.clone()],
+            vec![
+                TxOutput { recipient: "bob".to_string(), amount: 60 },
+                TxOutput { recipient: "alice".to_string(), amount: 38 },
+            ],
+        );
+        spend.fee = 2;
+        let connected = block(2, vec![
+            Transaction::new_coinbase("miner".to_string(), 104, 2),
+            account_transfer("alice", "carol", 30, 2),
+            spend.clone(),
+        ]);
+        index.connect_block(&connected);
+
+        assert_eq!(index.account_balance("alice"), 150 - 32 - 62);
+        assert_eq!(index.account_balance("carol"), 30);
+        assert_eq!(index.utxo_balance("alice"), 50 + 38);
+        assert_eq!(index.utxo_balance("bob"), 60);
+        assert!(index.utxo_set().get(&first).is_none());
+        assert_eq!(index.total_supply(), 150 + 104 - 2 - 2);
+        assert_eq!(index.chain_work(), before.chain_work() + connected.work());
+        assert_eq!(index.locate_transaction(&spend.txid()), Some(TxLocation { block_index: 2, position: 2 }));
+        assert_eq!(index.address_history("alice").len(), 4);
+
+        index.disconnect_block(&connected);
+        assert_eq!(index, before);
+        assert_eq!(index.utxo_set().get(&first).map(|output| output.amount), Some(100));
+        assert_eq!(index.locate_transaction(&spend.txid()), None);
+        assert!(index.address_history("carol").is_empty());
+
+        // 되돌린 뒤 다시 연결해도 같은 결과입니다.
+        let mut reconnected = index.clone();
+        reconnected.connect_block(&connected);
+        index.connect_block(&connected);
+        assert_eq!(index, reconnected);
+    }
+
+    #[test]
+    fn unapplicable_utxo_transaction_is_not_undone() {
+        let (mut index, first, _) = funded_index();
+        let before = index.clone();
+        // 존재하지 않는 출력을 쓰는 트랜잭션은 UTXO 집합에 반영되지 않고, 분리할 때도 되돌리지 않습니다.
+        let missing = OutPoint { txid: "missing".to_string(), index: 0 };
+        let invalid = Transaction::new_utxo(
+            "alice".to_string(),
+            "bob".to_string(),
+            vec![first, missing],
+            vec![TxOutput { recipient: "bob".to_string(), amount: 10 }],
+        );
+        let connected = block(2, vec![Transaction::new_coinbase("miner".to_string(), 100, 2), invalid]);
+        index.connect_block(&connected);
+        assert_eq!(index.utxo_balance("alice"), 150);
+        assert_eq!(index.utxo_balance("bob"), 0);
+        index.disconnect_block(&connected);
+        assert_eq!(index, before);
+    }
+}
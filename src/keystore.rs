@@ -0,0 +1,375 @@
+// src/keystore.rs
+use super::address::Address; // address 모듈의 주소 유형 사용
+use super::wallet::Wallet; // wallet 모듈의 Wallet 구조체 사용
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand_core::{OsRng, RngCore};
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 키 저장소 파일 형식 버전. 형식이 호환되지 않게 바뀌면 올립니다.
+pub const KEYSTORE_VERSION: u32 = 1;
+
+// scrypt 기본 매개변수 (N = 2^15, r = 8, p = 1: 약 32MiB 메모리 사용)
+#[cfg(not(test))]
+const DEFAULT_SCRYPT_LOG_N: u8 = 15;
+// 단위 테스트에서는 비용을 낮춰 빠르게 실행합니다.
+#[cfg(test)]
+const DEFAULT_SCRYPT_LOG_N: u8 = 4;
+const DEFAULT_SCRYPT_R: u32 = 8;
+const DEFAULT_SCRYPT_P: u32 = 1;
+// 파일에서 읽은 scrypt 매개변수의 상한. 손상된 파일이 과도한 메모리나 시간을 쓰게 하지 않도록 합니다.
+const MAX_SCRYPT_LOG_N: u8 = 20;
+const MAX_SCRYPT_R: u32 = 32;
+const MAX_SCRYPT_P: u32 = 16;
+
+const SALT_SIZE: usize = 32;
+const KEY_SIZE: usize = 32;   // XChaCha20-Poly1305 키 크기
+const NONCE_SIZE: usize = 24; // XChaCha20-Poly1305 nonce 크기
+
+/// 키 저장소를 읽거나 계정을 잠금 해제하는 중 발생하는 오류입니다.
+#[derive(Debug)]
+pub enum KeystoreError {
+    /// 파일 입출력 오류
+    Io(io::Error),
+    /// 파일 내용이 키 저장소 형식이 아닙니다.
+    Format(String),
+    /// 같은 이름의 계정이 이미 있습니다.
+    DuplicateAccount(String),
+    /// 이름 또는 주소에 해당하는 계정이 없습니다.
+    UnknownAccount(String),
+    /// 비밀번호가 틀렸거나 암호문이 변조되었습니다.
+    WrongPassword,
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeystoreError::Io(e) => write!(f, "keystore i/o error: {}", e),
+            KeystoreError::Format(reason) => write!(f, "malformed keystore: {}", reason),
+            KeystoreError::DuplicateAccount(name) => write!(f, "account {} already exists", name),
+            KeystoreError::UnknownAccount(name) => write!(f, "unknown account {}", name),
+            KeystoreError::WrongPassword => write!(f, "wrong password or corrupted key"),
+        }
+    }
+}
+
+impl std::error::Error for KeystoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KeystoreError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for KeystoreError {
+    fn from(e: io::Error) -> Self {
+        KeystoreError::Io(e)
+    }
+}
+
+/// 비밀번호에서 암호화 키를 유도하는 scrypt 매개변수입니다.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScryptParams {
+    pub log_n: u8,    // 비용 N = 2^log_n
+    pub r: u32,       // 블록 크기
+    pub p: u32,       // 병렬도
+    pub salt: String, // 16진수 솔트 (계정마다 무작위)
+}
+
+/// 키 저장소에 보관된 계정 하나. 개인 키는 암호화된 상태로만 저장됩니다.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreAccount {
+    pub name: String,
+    pub address: String,
+    pub kdf: ScryptParams,
+    pub nonce: String,      // 16진수 XChaCha20-Poly1305 nonce
+    pub ciphertext: String, // 16진수 암호문 (인증 태그 포함)
+    // 시드 문구에서 파생한 키이면 그 파생 경로 (예: m/44'/5656'/0'/0/3)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+}
+
+/// 비밀번호로 암호화한 개인 키를 이름 붙은 계정별로 보관하는 로컬 키 저장소입니다.
+///
+/// 개인 키는 scrypt로 유도한 키와 XChaCha20-Poly1305로 암호화하며,
+/// 계정 이름과 주소를 연관 데이터로 묶어 다른 계정의 암호문과 바꿔치기할 수 없게 합니다.
+/// 파일에는 평문 개인 키가 기록되지 않습니다.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub accounts: Vec<KeystoreAccount>,
+}
+
+impl Default for Keystore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Keystore {
+    /// 빈 키 저장소를 만듭니다.
+    pub fn new() -> Self {
+        Keystore { version: KEYSTORE_VERSION, accounts: Vec::new() }
+    }
+
+    /// 파일에서 키 저장소를 읽습니다. 파일이 없으면 빈 키 저장소를 반환합니다.
+    pub fn load(path: &Path) -> Result<Self, KeystoreError> {
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Keystore::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let keystore: Keystore = serde_json::from_slice(&contents)
+            .map_err(|e| KeystoreError::Format(e.to_string()))?;
+        if keystore.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::Format(format!("unsupported version {}", keystore.version)));
+        }
+        Ok(keystore)
+    }
+
+    /// 키 저장소를 파일에 저장합니다.
+    /// 임시 파일에 먼저 기록한 뒤 이름을 바꾸므로, 저장 중에 중단되어도 기존 파일이 손상되지 않습니다.
+    pub fn save(&self, path: &Path) -> Result<(), KeystoreError> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(self).map_err(io::Error::from)?;
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, json)?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// 이름 또는 주소로 계정을 찾습니다.
+    pub fn find(&self, name_or_address: &str) -> Option<&KeystoreAccount> {
+        self.accounts.iter()
+            .find(|account| account.name == name_or_address || account.address == name_or_address)
+    }
+
+    /// 지갑의 개인 키를 비밀번호로 암호화하여 새 계정으로 추가합니다.
+    pub fn add_account(&mut self, name: &str, wallet: &Wallet, password: &str) -> Result<(), KeystoreError> {
+        if self.accounts.iter().any(|account| account.name == name) {
+            return Err(KeystoreError::DuplicateAccount(name.to_string()));
+        }
+
+        let mut salt = [0u8; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+        let kdf = ScryptParams {
+            log_n: DEFAULT_SCRYPT_LOG_N,
+            r: DEFAULT_SCRYPT_R,
+            p: DEFAULT_SCRYPT_P,
+            salt: hex::encode(salt),
+        };
+        let key = derive_key(password, &kdf)?;
+
+        let mut nonce = [0u8; NONCE_SIZE];
+        OsRng.fill_bytes(&mut nonce);
+        let aad = associated_data(name, wallet.get_address());
+        let payload = Payload { msg: wallet.get_private_key_hex().as_bytes(), aad: &aad };
+        let ciphertext = XChaCha20Poly1305::new(&key.into())
+            .encrypt(XNonce::from_slice(&nonce), payload)
+            .map_err(|_| KeystoreError::Format("encryption failed".to_string()))?;
+
+        self.accounts.push(KeystoreAccount {
+            name: name.to_string(),
+            address: wallet.get_address().to_string(),
+            kdf,
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+            derivation_path: None,
+        });
+        Ok(())
+    }
+
+    /// 계층적 결정 지갑에서 파생한 키를 파생 경로와 함께 새 계정으로 추가합니다.
+    pub fn add_derived_account(&mut self, name: &str, wallet: &Wallet, derivation_path: &str, password: &str) -> Result<(), KeystoreError> {
+        self.add_account(name, wallet, password)?;
+        if let Some(account) = self.accounts.last_mut() {
+            account.derivation_path = Some(derivation_path.to_string());
+        }
+        Ok(())
+    }
+
+    /// 비밀번호로 계정의 개인 키를 복호화하여 지갑을 반환합니다.
+    pub fn unlock(&self, name_or_address: &str, password: &str) -> Result<Wallet, KeystoreError> {
+        let account = self.find(name_or_address)
+            .ok_or_else(|| KeystoreError::UnknownAccount(name_or_address.to_string()))?;
+
+        let key = derive_key(password, &account.kdf)?;
+        let nonce = hex::decode(&account.nonce)
+            .ok()
+            .filter(|nonce| nonce.len() == NONCE_SIZE)
+            .ok_or_else(|| KeystoreError::Format(format!("invalid nonce for account {}", account.name)))?;
+        let ciphertext = hex::decode(&account.ciphertext)
+            .map_err(|e| KeystoreError::Format(format!("invalid ciphertext for account {}: {}", account.name, e)))?;
+
+        let aad = associated_data(&account.name, &account.address);
+        let payload = Payload { msg: &ciphertext, aad: &aad };
+        let plaintext = XChaCha20Poly1305::new(&key.into())
+            .decrypt(XNonce::from_slice(&nonce), payload)
+            .map_err(|_| KeystoreError::WrongPassword)?;
+        let private_key_hex = String::from_utf8(plaintext)
+            .map_err(|_| KeystoreError::Format(format!("invalid private key for account {}", account.name)))?;
+
+        // 복호화한 개인 키가 저장된 주소와 맞는지 확인합니다.
+        let network = account.address.parse::<Address>()
+            .map_err(|e| KeystoreError::Format(e.to_string()))?
+            .network();
+        let wallet = Wallet::from_private_key_hex(&private_key_hex, network)
+            .map_err(|e| KeystoreError::Format(e.to_string()))?;
+        if wallet.address != account.address {
+            return Err(KeystoreError::Format(format!("key does not match address for account {}", account.name)));
+        }
+        Ok(wallet)
+    }
+
+    /// 계정을 삭제합니다.
+    pub fn remove_account(&mut self, name: &str) -> Result<KeystoreAccount, KeystoreError> {
+        let position = self.accounts.iter()
+            .position(|account| account.name == name)
+            .ok_or_else(|| KeystoreError::UnknownAccount(name.to_string()))?;
+        Ok(self.accounts.remove(position))
+    }
+}
+
+// 비밀번호와 scrypt 매개변수로 암호화 키를 유도합니다.
+fn derive_key(password: &str, kdf: &ScryptParams) -> Result<[u8; KEY_SIZE], KeystoreError> {
+    if kdf.log_n > MAX_SCRYPT_LOG_N || kdf.r > MAX_SCRYPT_R || kdf.p > MAX_SCRYPT_P {
+        return Err(KeystoreError::Format(format!(
+            "scrypt parameters exceed limits (log_n {}, r {}, p {})", kdf.log_n, kdf.r, kdf.p
+        )));
+    }
+    let salt = hex::decode(&kdf.salt)
+        .map_err(|e| KeystoreError::Format(format!("invalid salt: {}", e)))?;
+    let params = scrypt::Params::new(kdf.log_n, kdf.r, kdf.p, KEY_SIZE)
+        .map_err(|e| KeystoreError::Format(format!("invalid scrypt parameters: {}", e)))?;
+    let mut key = [0u8; KEY_SIZE];
+    scrypt::scrypt(password.as_bytes(), &salt, &params, &mut key)
+        .map_err(|e| KeystoreError::Format(format!("key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+// 암호문에 묶을 연관 데이터 (형식 버전, 계정 이름, 주소)
+fn associated_data(name: &str, address: &str) -> Vec<u8> {
+    format!("bingry-keystore-v{}:{}:{}", KEYSTORE_VERSION, name, address).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Network;
+
+    const PASSWORD: &str = "correct horse";
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("bingry-keystore-{}-{}.json", name, std::process::id()))
+    }
+
+    fn keystore_with(names: &[&str]) -> (Keystore, Vec<Wallet>) {
+        let mut keystore = Keystore::new();
+        let wallets: Vec<Wallet> = names.iter().map(|_| Wallet::new_for_network(Network::Regtest)).collect();
+        for (name, wallet) in names.iter().zip(wallets.iter()) {
+            keystore.add_account(name, wallet, PASSWORD).unwrap();
+        }
+        (keystore, wallets)
+    }
+
+    #[test]
+    fn added_account_unlocks_by_name_or_address() {
+        let (mut keystore, wallets) = keystore_with(&["alice"]);
+        let account = keystore.find("alice").unwrap();
+        assert_eq!(account.address, wallets[0].address);
+        assert!(!account.ciphertext.contains(wallets[0].get_private_key_hex()));
+
+        for key in ["alice", wallets[0].address.as_str()] {
+            let wallet = keystore.unlock(key, PASSWORD).unwrap();
+            assert_eq!(wallet.get_private_key_hex(), wallets[0].get_private_key_hex());
+        }
+        assert!(matches!(keystore.add_account("alice", &wallets[0], PASSWORD), Err(KeystoreError::DuplicateAccount(_))));
+        assert!(matches!(keystore.unlock("bob", PASSWORD), Err(KeystoreError::UnknownAccount(_))));
+        keystore.remove_account("alice").unwrap();
+        assert!(keystore.find("alice").is_none());
+    }
+
+    #[test]
+    fn wrong_password_does_not_decrypt() {
+        let (keystore, _) = keystore_with(&["alice"]);
+        assert!(matches!(keystore.unlock("alice", "wrong horse"), Err(KeystoreError::WrongPassword)));
+        assert!(matches!(keystore.unlock("alice", ""), Err(KeystoreError::WrongPassword)));
+    }
+
+    #[test]
+    fn ciphertext_is_bound_to_its_account() {
+        let (mut keystore, _) = keystore_with(&["alice", "bob"]);
+        // 암호화 재료를 통째로 바꿔도 계정 이름과 주소(연관 데이터)가 달라 인증에 실패합니다.
+        let (first, second) = keystore.accounts.split_at_mut(1);
+        std::mem::swap(&mut first[0].kdf, &mut second[0].kdf);
+        std::mem::swap(&mut first[0].nonce, &mut second[0].nonce);
+        std::mem::swap(&mut first[0].ciphertext, &mut second[0].ciphertext);
+        assert!(matches!(keystore.unlock("alice", PASSWORD), Err(KeystoreError::WrongPassword)));
+        assert!(matches!(keystore.unlock("bob", PASSWORD), Err(KeystoreError::WrongPassword)));
+
+        // 이름만 바꿔도 마찬가지입니다.
+        let (mut keystore, _) = keystore_with(&["alice"]);
+        keystore.accounts[0].name = "mallory".to_string();
+        assert!(matches!(keystore.unlock("mallory", PASSWORD), Err(KeystoreError::WrongPassword)));
+    }
+
+    #[test]
+    fn saved_keystore_loads_and_unlocks() {
+        let path = temp_path("round-trip");
+        assert!(Keystore::load(&path).unwrap().accounts.is_empty());
+        let (mut keystore, wallets) = keystore_with(&["alice"]);
+        keystore.add_derived_account("hd", &wallets[0], "m/44'/5656'/0'/0/0", PASSWORD).unwrap();
+        keystore.save(&path).unwrap();
+        let loaded = Keystore::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.accounts.len(), 2);
+        assert_eq!(loaded.find("hd").unwrap().derivation_path.as_deref(), Some("m/44'/5656'/0'/0/0"));
+        assert_eq!(loaded.unlock("alice", PASSWORD).unwrap().address, wallets[0].address);
+        assert!(!path.with_extension("tmp").exists());
+    }
+
+    #[test]
+    fn corrupted_keystore_is_an_error() {
+        let path = temp_path("corrupted");
+        let (keystore, _) = keystore_with(&["alice"]);
+        keystore.save(&path).unwrap();
+        let json = fs::read_to_string(&path).unwrap();
+        for contents in [
+            "not json".to_string(),
+            json[..json.len() / 2].to_string(),
+            json.replacen(&format!("\"version\": {}", KEYSTORE_VERSION), "\"version\": 99", 1),
+        ] {
+            fs::write(&path, contents).unwrap();
+            assert!(matches!(Keystore::load(&path), Err(KeystoreError::Format(_))));
+        }
+        fs::remove_file(&path).unwrap();
+
+        let corrupt = |modify: fn(&mut KeystoreAccount)| {
+            let mut keystore = keystore.clone();
+            modify(&mut keystore.accounts[0]);
+            keystore.unlock("alice", PASSWORD)
+        };
+        assert!(matches!(corrupt(|account| account.nonce = "00".to_string()), Err(KeystoreError::Format(_))));
+        assert!(matches!(corrupt(|account| account.ciphertext = "zz".to_string()), Err(KeystoreError::Format(_))));
+        assert!(matches!(corrupt(|account| account.kdf.salt = "zz".to_string()), Err(KeystoreError::Format(_))));
+        // 과도한 비용은 계산하지 않고 거부합니다.
+        assert!(matches!(corrupt(|account| account.kdf.log_n = 60), Err(KeystoreError::Format(_))));
+        assert!(matches!(corrupt(|account| account.kdf.r = u32::MAX), Err(KeystoreError::Format(_))));
+        let flip_first_byte = |account: &mut KeystoreAccount| {
+            let byte = u8::from_str_radix(&account.ciphertext[..2], 16).unwrap() ^ 1;
+            account.ciphertext.replace_range(..2, &format!("{:02x}", byte));
+        };
+        assert!(matches!(corrupt(flip_first_byte), Err(KeystoreError::WrongPassword)));
+    }
+}
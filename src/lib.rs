@@ -4,7 +4,26 @@
 pub mod blockchain;
 pub mod block;
 pub mod transaction;
+pub mod address;
 pub mod wallet;
+pub mod keystore;
+pub mod hd_wallet;
+pub mod storage;
+pub mod utxo;
+pub mod difficulty;
+pub mod chain_params;
+pub mod p2p;
+pub mod merkle;
+pub mod encoding;
+pub mod error;
+pub mod events;
+pub mod miner;
+pub mod index;
+pub mod protocol;
+pub mod node_client;
+pub mod rpc;
+pub mod logging;
+pub mod config;
 pub mod server; // server 모듈도 lib.rs에 포함시킵니다.
 
 // 이제 각 모듈의 내용은 해당 파일(src/blockchain.rs, src/block.rs 등)에 그대로 유지됩니다.
@@ -0,0 +1,36 @@
+// src/logging.rs
+use chrono::Utc;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// 노드 로그를 콘솔에 출력하는 간단한 로거입니다.
+/// 경고와 오류는 표준 오류로, 나머지는 표준 출력으로 씁니다.
+struct ConsoleLogger;
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S");
+        if record.level() <= Level::Warn {
+            eprintln!("{} {:5} {}", timestamp, record.level(), record.args());
+        } else {
+            println!("{} {:5} {}", timestamp, record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: ConsoleLogger = ConsoleLogger;
+
+/// 콘솔 로거를 설치하고 출력할 최소 로그 수준을 설정합니다.
+/// 이미 로거가 설치되어 있으면 수준만 바꿉니다.
+pub fn init(level: LevelFilter) {
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+}
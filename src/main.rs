@@ -1,13 +1,9 @@
 // src/main.rs
 
-// 다른 모듈들을 가져옵니다.
-mod blockchain;
-mod block;
-mod transaction;
-mod wallet;
-mod server; // 서버 모듈 추가
+// 모듈들은 라이브러리 크레이트(bingry_blockchain_lib)에 정의되어 있으므로 그대로 가져옵니다.
+use bingry_blockchain_lib::server;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     server::run_server().await // 서버 실행
-}
\ No newline at end of file
+}
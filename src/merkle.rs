@@ -0,0 +1,180 @@
+// src/merkle.rs
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+// 잎(leaf)과 내부 노드의 해시를 구분하는 접두 바이트 (RFC 6962 방식)
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// 트랜잭션이 없는 블록의 머클 루트
+pub const EMPTY_MERKLE_ROOT: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// 머클 증명의 한 단계: 현재 해시와 결합할 형제 노드의 해시와 위치입니다.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProofStep {
+    pub hash: String,  // 형제 노드 해시 (16진수)
+    pub is_left: bool, // 형제 노드가 왼쪽에 있으면 true
+}
+
+/// 트랜잭션이 블록에 포함되어 있음을 증명하는 머클 포함 증명입니다.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub txid: String,     // 증명 대상 트랜잭션 ID
+    pub tx_index: usize,  // 블록 내 트랜잭션 위치
+    pub tx_count: usize,  // 블록의 트랜잭션 수 (단계마다 형제 노드가 있는지 정합니다. 머클 루트에는 커밋되지 않습니다)
+    pub steps: Vec<MerkleProofStep>,
+}
+
+impl MerkleProof {
+    /// 증명을 따라 루트를 다시 계산하여 주어진 머클 루트와 일치하는지 확인합니다.
+    /// 각 단계의 형제 노드 위치는 `tx_index`와 `tx_count`로 다시 계산하여 `is_left`와 비교하므로,
+    /// 루트가 일치하면 트랜잭션이 주장한 위치에 있음이 함께 증명됩니다.
+    ///
+    /// 다만 트랜잭션 수는 머클 루트나 블록 해시에 커밋되지 않으므로, 증명이 검증되어도 `tx_count` 자체는
+    /// 보장되지 않습니다. (예: 블록의 마지막 트랜잭션이라는 주장) 트랜잭션 수가 중요하면 블록 전체로 확인해야 하며,
+    /// 노드는 증명을 보내기 전에 `tx_count`가 블록의 트랜잭션 수와 같은지 확인합니다.
+    pub fn verify(&self, merkle_root: &str) -> bool {
+        if self.tx_index >= self.tx_count {
+            return false;
+        }
+        let mut hash = match leaf_hash(&self.txid) {
+            Some(hash) => hash,
+            None => return false,
+        };
+        let mut steps = self.steps.iter();
+        let (mut index, mut level_len) = (self.tx_index, self.tx_count);
+        while level_len > 1 {
+            let sibling_index = index ^ 1;
+            // 짝이 없는 마지막 노드는 결합 없이 올라갑니다.
+            if sibling_index < level_len {
+                let step = match steps.next() {
+                    Some(step) => step,
+                    None => return false,
+                };
+                let is_left = sibling_index < index;
+                let sibling = match decode_hash(&step.hash) {
+                    Some(sibling) if step.is_left == is_left => sibling,
+                    _ => return false,
+                };
+                hash = if is_left {
+                    node_hash(&sibling, &hash)
+                } else {
+                    node_hash(&hash, &sibling)
+                };
+            }
+            index /= 2;
+            level_len = level_len.div_ceil(2);
+        }
+        steps.next().is_none() && hex::encode(hash) == merkle_root
+    }
+}
+
+/// 트랜잭션 ID 목록으로부터 머클 루트를 계산합니다.
+/// 홀수 개의 노드가 남으면 마지막 노드는 복제하지 않고 그대로 다음 단계로 올립니다.
+pub fn merkle_root(txids: &[String]) -> String {
+    let mut level: Vec<[u8; 32]> = match txids.iter().map(|txid| leaf_hash(txid)).collect() {
+        Some(level) => level,
+        None => return EMPTY_MERKLE_ROOT.to_string(),
+    };
+    if level.is_empty() {
+        return EMPTY_MERKLE_ROOT.to_string();
+    }
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    hex::encode(level[0])
+}
+
+/// `tx_index` 위치의 트랜잭션에 대한 머클 포함 증명을 만듭니다.
+pub fn merkle_proof(txids: &[String], tx_index: usize) -> Option<MerkleProof> {
+    let txid = txids.get(tx_index)?.clone();
+    let mut level: Vec<[u8; 32]> = txids.iter().map(|txid| leaf_hash(txid)).collect::<Option<_>>()?;
+    let mut index = tx_index;
+    let mut steps = Vec::new();
+
+    while level.len() > 1 {
+        let sibling_index = index ^ 1;
+        // 짝이 없는 마지막 노드는 결합 없이 올라가므로 증명 단계가 없습니다.
+        if sibling_index < level.len() {
+            steps.push(MerkleProofStep {
+                hash: hex::encode(level[sibling_index]),
+                is_left: sibling_index < index,
+            });
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+
+    Some(MerkleProof { txid, tx_index, tx_count: txids.len(), steps })
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level.chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+fn decode_hash(hash_hex: &str) -> Option<[u8; 32]> {
+    hex::decode(hash_hex).ok()?.try_into().ok()
+}
+
+fn leaf_hash(txid: &str) -> Option<[u8; 32]> {
+    let txid_bytes = hex::decode(txid).ok()?;
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(&txid_bytes);
+    Some(hasher.finalize().into())
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn txids(count: usize) -> Vec<String> {
+        (0..count).map(|n| hex::encode(Sha256::digest([n as u8]))).collect()
+    }
+
+    #[test]
+    fn proofs_verify_for_every_position() {
+        for count in 1..=9 {
+            let txids = txids(count);
+            let root = merkle_root(&txids);
+            for tx_index in 0..count {
+                let proof = merkle_proof(&txids, tx_index).unwrap();
+                assert!(proof.verify(&root), "{} of {}", tx_index, count);
+                assert!(!proof.verify(EMPTY_MERKLE_ROOT));
+            }
+        }
+    }
+
+    #[test]
+    fn proof_is_bound_to_its_position() {
+        let txids = txids(5);
+        let root = merkle_root(&txids);
+        let proof = merkle_proof(&txids, 2).unwrap();
+
+        for tx_index in [0, 1, 3, 4, 5] {
+            let moved = MerkleProof { tx_index, ..proof.clone() };
+            assert!(!moved.verify(&root), "claimed position {}", tx_index);
+        }
+        // 형제 노드의 방향만 바꿔도 거부됩니다.
+        let mut flipped = proof.clone();
+        flipped.steps[0].is_left = !flipped.steps[0].is_left;
+        assert!(!flipped.verify(&root));
+        // 다른 트랜잭션 ID로 바꾼 증명도 거부됩니다.
+        let forged = MerkleProof { txid: txids[3].clone(), ..proof };
+        assert!(!forged.verify(&root));
+    }
+}
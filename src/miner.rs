@@ -0,0 +1,366 @@
+// src/miner.rs
+use super::block::{Block, NonceSearch}; // block 모듈의 Block 구조체와 nonce 탐색 결과 사용
+use super::blockchain::Blockchain;   // blockchain 모듈의 Blockchain 구조체 사용
+use super::error::BlockchainError;   // error 모듈의 오류 유형 사용
+use super::events::ChainEvent;       // events 모듈의 체인 이벤트 사용
+use chrono::Utc;
+use tokio::sync::{broadcast, oneshot};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use log::{debug, info};
+
+/// 채굴 작업의 통계입니다.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MiningStats {
+    pub hashes: u64,       // 모든 스레드가 계산한 해시 수
+    pub elapsed: Duration, // 채굴에 걸린 시간
+    pub threads: usize,    // 사용한 채굴 스레드 수
+}
+
+impl MiningStats {
+    /// 초당 해시 수 (H/s)
+    pub fn hashrate(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 { self.hashes as f64 / seconds } else { 0.0 }
+    }
+}
+
+/// 채굴 작업이 실패한 이유입니다.
+#[derive(Debug)]
+pub enum MiningError {
+    /// 블록 템플릿을 만들지 못했거나, 채굴한 블록이 검증을 통과하지 못했습니다.
+    Rejected(BlockchainError),
+    /// 채굴하는 동안 다른 블록이 tip에 연결되어 작업을 중단했습니다.
+    Cancelled,
+    /// 채굴 스레드를 시작하지 못했거나 결과를 받지 못했습니다.
+    WorkerFailed(String),
+}
+
+impl fmt::Display for MiningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MiningError::Rejected(e) => write!(f, "{}", e),
+            MiningError::Cancelled => write!(f, "mining cancelled: the chain tip changed"),
+            MiningError::WorkerFailed(reason) => write!(f, "mining worker failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for MiningError {}
+
+impl From<BlockchainError> for MiningError {
+    fn from(e: BlockchainError) -> Self {
+        MiningError::Rejected(e)
+    }
+}
+
+// 채굴 요청이 끝나면(요청 태스크가 취소된 경우 포함) 채굴 스레드를 멈추게 합니다.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 대기 중인 트랜잭션으로 블록을 채굴하여 체인에 연결하고, 연결된 블록과 채굴 통계를 반환합니다.
+///
+/// 블록체인 잠금은 템플릿을 만들 때와 결과를 제출할 때만 잡으며, 작업 증명은 `threads`개의 채굴 스레드가
+/// nonce 공간을 나눠 계산하므로 채굴하는 동안에도 다른 요청을 처리할 수 있습니다.
+/// 채굴 중 다른 블록(수신 블록이나 다른 채굴 요청의 블록)이 tip에 연결되면 작업을 멈추고 `Cancelled`를 반환합니다.
+pub async fn mine_block(
+    blockchain: &Arc<Mutex<Blockchain>>,
+    miner_address: &str,
+    threads: usize,
+) -> Result<(Block, MiningStats), MiningError> {
+    // 같은 잠금 안에서 구독하고 템플릿을 만들어, 그 사이에 연결된 블록을 놓치지 않습니다.
+    let (events, template) = {
+        let bc = blockchain.lock().unwrap();
+        (bc.subscribe_events(), bc.block_template(miner_address)?)
+    };
+    mine_template(blockchain, template, events, threads).await
+}
+
+// `events`를 지켜보며 템플릿을 채굴하고 결과를 제출합니다. `events`는 템플릿을 만들기 전에 구독한 수신자여야 합니다.
+async fn mine_template(
+    blockchain: &Arc<Mutex<Blockchain>>,
+    template: Block,
+    mut events: broadcast::Receiver<ChainEvent>,
+    threads: usize,
+) -> Result<(Block, MiningStats), MiningError> {
+    let template_index = template.index;
+    let parent_hash = template.previous_hash.clone();
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let _guard = CancelOnDrop(cancel.clone());
+    let (result_tx, mut result_rx) = oneshot::channel();
+    let worker_cancel = cancel.clone();
+    thread::Builder::new()
+        .name(format!("miner-{}", template_index))
+        .spawn(move || {
+            let _ = result_tx.send(mine_parallel(template, threads, &worker_cancel));
+        })
+        .map_err(|e| MiningError::WorkerFailed(e.to_string()))?;
+
+    let mut watching_events = true;
+    let (block, stats) = loop {
+        tokio::select! {
+            result = &mut result_rx => match result {
+                Ok(Ok((Some(block), stats))) => break (block, stats),
+                Ok(Ok((None, _))) => return Err(MiningError::Cancelled),
+                Ok(Err(e)) => return Err(e),
+                Err(_) => return Err(MiningError::WorkerFailed("worker exited without a result".to_string())),
+            },
+            event = events.recv(), if watching_events => match event {
+                Ok(ChainEvent::BlockConnected(_)) => {
+                    debug!("새 tip이 연결되어 블록 {} 채굴을 중단합니다.", template_index);
+                    cancel.store(true, Ordering::Relaxed);
+                }
+                Ok(ChainEvent::TransactionAccepted(_)) => {}
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    // 놓친 이벤트에 블록 연결이 있었을 수 있으므로 tip을 직접 확인합니다.
+                    let tip_changed = blockchain.lock().unwrap()
+                        .get_latest_block()
+                        .is_none_or(|tip| tip.hash != parent_hash);
+                    if tip_changed {
+                        cancel.store(true, Ordering::Relaxed);
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => watching_events = false,
+            },
+        }
+    };
+
+    // 결과를 제출합니다. 그 사이 tip이 바뀌었다면 검증 단계에서 `StaleBlock`으로 거부됩니다.
+    blockchain.lock().unwrap().submit_block(block.clone())?;
+    info!(
+        "블록 {} 채굴 완료: {} (nonce {}, 스레드 {}개, {:.1}초, {:.0} H/s)",
+        block.index, block.hash, block.nonce, stats.threads, stats.elapsed.as_secs_f64(), stats.hashrate()
+    );
+    Ok((block, stats))
+}
+
+/// 블록 템플릿의 작업 증명을 `threads`개의 스레드로 계산합니다.
+///
+/// 템플릿의 nonce를 `n`이라 하면 스레드 `i`는 nonce `n + i, n + i + threads, n + i + 2 * threads, ...`를 탐색하며,
+/// 한 스레드가 답을 찾으면 `cancel` 플래그를 설정해 나머지 스레드를 멈춥니다. 모든 스레드가 nonce 공간을 다 쓰면
+/// 타임스탬프를 갱신해 헤더를 바꾼 뒤 nonce 0부터 다시 탐색합니다.
+/// 반환값: 채굴된 블록 (`cancel`로 중단되었으면 None)과 채굴 통계.
+/// 템플릿의 목표값을 해석할 수 없거나 채굴 스레드를 시작하지 못하면 오류를 반환합니다.
+pub fn mine_parallel(
+    mut block: Block,
+    threads: usize,
+    cancel: &AtomicBool,
+) -> Result<(Option<Block>, MiningStats), MiningError> {
+    let threads = threads.max(1);
+    let hashes = AtomicU64::new(0);
+    let started = Instant::now();
+
+    let target = block.target().ok_or(BlockchainError::InvalidTarget { index: block.index, bits: block.bits })?;
+
+    let mined = loop {
+        let found = OnceLock::new();
+        thread::scope(|scope| {
+            for worker in 0..threads {
+                // nonce 공간 끝에 걸려 시작할 nonce가 없는 스레드는 띄우지 않습니다.
+                let Some(start) = block.nonce.checked_add(worker as u64) else { break };
+                let (block, target, found, hashes) = (&block, &target, &found, &hashes);
+                let spawned = thread::Builder::new()
+                    .name(format!("miner-{}-{}", block.index, worker))
+                    .spawn_scoped(scope, move || {
+                        if let NonceSearch::Found(nonce) = block.search_nonce(target, start, threads as u64, cancel, hashes) {
+                            let _ = found.set(nonce);
+                            cancel.store(true, Ordering::Relaxed); // 다른 스레드를 멈춥니다.
+                        }
+                    });
+                if let Err(e) = spawned {
+                    // 이미 시작한 스레드를 멈추고 오류를 반환합니다.
+                    cancel.store(true, Ordering::Relaxed);
+                    return Err(MiningError::WorkerFailed(format!("failed to spawn mining thread: {}", e)));
+                }
+            }
+            Ok(())
+        })?;
+
+        if let Some(&nonce) = found.get() {
+            block.nonce = nonce;
+            block.hash = block.calculate_hash();
+            break Some(block);
+        }
+        if cancel.load(Ordering::Relaxed) {
+            break None;
+        }
+        // nonce 공간을 모두 탐색했습니다. 타임스탬프를 바꾸면 새로운 해시 공간이 생깁니다.
+        block.timestamp = Utc::now().timestamp().max(block.timestamp + 1);
+        block.nonce = 0;
+        debug!("블록 {}의 nonce 공간을 모두 탐색하여 타임스탬프를 {}(으)로 갱신합니다.", block.index, block.timestamp);
+    };
+
+    let stats = MiningStats { hashes: hashes.into_inner(), elapsed: started.elapsed(), threads };
+    Ok((mined, stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Network;
+    use crate::block::MINING_CANCEL_CHECK_INTERVAL;
+    use crate::blockchain::ChainSettings;
+    use crate::chain_params::ChainParams;
+    use crate::storage::FileChainStore;
+    use crate::transaction::Transaction;
+    use crate::wallet::Wallet;
+    use std::path::PathBuf;
+
+    const EASY_BITS: u32 = 0x207fffff;
+    const IMPOSSIBLE_BITS: u32 = 0x03000001; // 사실상 충족할 수 없는 목표값
+    const CANCEL_LIMIT: Duration = Duration::from_secs(10);
+
+    // 임시 디렉터리의 파일 저장소를 쓰는 regtest 체인
+    struct RegtestChain {
+        chain: Arc<Mutex<Blockchain>>,
+        miner: Wallet,
+        dir: PathBuf,
+    }
+
+    impl RegtestChain {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("bingry-miner-{}-{}", name, std::process::id()));
+            let store = FileChainStore::open(dir.join("chain.dat")).unwrap();
+            let settings = ChainSettings { params: ChainParams::regtest(), ..Default::default() };
+            let chain = Arc::new(Mutex::new(Blockchain::open(settings, store).unwrap()));
+            RegtestChain { chain, miner: Wallet::new_for_network(Network::Regtest), dir }
+        }
+
+        // 끝나지 않는 채굴 작업의 템플릿
+        fn endless_template(&self) -> Block {
+            let mut template = self.chain.lock().unwrap().block_template(&self.miner.address).unwrap();
+            template.bits = IMPOSSIBLE_BITS;
+            template
+        }
+
+        fn connect_block(&self) {
+            self.chain.lock().unwrap().mine_pending_transactions(self.miner.address.clone()).unwrap();
+        }
+    }
+
+    impl Drop for RegtestChain {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn transaction_event() -> ChainEvent {
+        ChainEvent::TransactionAccepted(Arc::new(Transaction::new("a".to_string(), "b".to_string(), 1)))
+    }
+
+    fn template() -> (Blockchain, Block) {
+        let chain = Blockchain::new(EASY_BITS);
+        let template = chain.block_template(&Wallet::new().address).unwrap();
+        (chain, template)
+    }
+
+    #[test]
+    fn parallel_workers_find_a_valid_nonce() {
+        let (mut chain, template) = template();
+        let (mined, stats) = mine_parallel(template, 4, &AtomicBool::new(false)).unwrap();
+        let block = mined.unwrap();
+        assert_eq!(block.hash, block.calculate_hash());
+        assert!(block.has_valid_proof_of_work());
+        assert_eq!(stats.threads, 4);
+        assert!(stats.hashes >= 1);
+        chain.submit_block(block).unwrap();
+    }
+
+    #[test]
+    fn exhausted_nonce_space_refreshes_the_timestamp() {
+        let (_, mut template) = template();
+        // 마지막 nonce 하나만 남기고, 그 nonce로는 목표값을 충족하지 못하는 타임스탬프를 고릅니다.
+        template.nonce = u64::MAX;
+        loop {
+            template.hash = template.calculate_hash();
+            if !template.has_valid_proof_of_work() {
+                break;
+            }
+            template.timestamp += 1;
+        }
+        let original_timestamp = template.timestamp;
+
+        let (mined, _) = mine_parallel(template, 2, &AtomicBool::new(false)).unwrap();
+        let block = mined.unwrap();
+        assert!(block.timestamp > original_timestamp);
+        assert!(block.nonce < u64::MAX);
+        assert_eq!(block.hash, block.calculate_hash());
+        assert!(block.has_valid_proof_of_work());
+    }
+
+    #[test]
+    fn cancelled_or_invalid_templates_are_not_mined() {
+        let (_, mut template) = template();
+        template.bits = IMPOSSIBLE_BITS;
+        let (mined, stats) = mine_parallel(template.clone(), 2, &AtomicBool::new(true)).unwrap();
+        assert!(mined.is_none());
+        assert_eq!(stats.hashes, 2 * MINING_CANCEL_CHECK_INTERVAL);
+
+        template.bits = 0;
+        assert!(matches!(
+            mine_parallel(template, 2, &AtomicBool::new(false)),
+            Err(MiningError::Rejected(BlockchainError::InvalidTarget { bits: 0, .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn mining_is_cancelled_when_a_block_is_connected() {
+        let regtest = RegtestChain::new("connected");
+        let events = regtest.chain.lock().unwrap().subscribe_events();
+        let template = regtest.endless_template();
+        let chain = Arc::clone(&regtest.chain);
+        let mining = tokio::spawn(async move { mine_template(&chain, template, events, 2).await });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!mining.is_finished());
+
+        regtest.connect_block();
+        let result = tokio::time::timeout(CANCEL_LIMIT, mining).await.unwrap().unwrap();
+        assert!(matches!(result, Err(MiningError::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn lagged_events_cancel_mining_only_if_the_tip_changed() {
+        let regtest = RegtestChain::new("lagged");
+
+        // 뒤처진 사이에 tip이 바뀌었으면 멈춥니다.
+        let (sender, events) = broadcast::channel(1);
+        let template = regtest.endless_template();
+        regtest.connect_block();
+        sender.send(transaction_event()).unwrap();
+        sender.send(transaction_event()).unwrap();
+        let result = tokio::time::timeout(CANCEL_LIMIT, mine_template(&regtest.chain, template, events, 2)).await;
+        assert!(matches!(result, Ok(Err(MiningError::Cancelled))));
+
+        // tip이 그대로면 계속 채굴합니다.
+        let (sender, events) = broadcast::channel(1);
+        let template = regtest.endless_template();
+        sender.send(transaction_event()).unwrap();
+        sender.send(transaction_event()).unwrap();
+        let result = tokio::time::timeout(
+            Duration::from_millis(300),
+            mine_template(&regtest.chain, template, events, 2),
+        ).await;
+        assert!(result.is_err(), "mining stopped although the tip did not change");
+        drop(sender);
+    }
+
+    #[tokio::test]
+    async fn mine_block_connects_the_mined_block() {
+        let regtest = RegtestChain::new("mine");
+        let (block, stats) = mine_block(&regtest.chain, &regtest.miner.address, 2).await.unwrap();
+        assert_eq!(stats.threads, 2);
+        let chain = regtest.chain.lock().unwrap();
+        assert_eq!(chain.get_latest_block().unwrap().hash, block.hash);
+        assert_eq!(chain.get_balance_of_address(&regtest.miner.address), chain.reward.subsidy_at(1, 0));
+    }
+}
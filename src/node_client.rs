@@ -0,0 +1,256 @@
+// src/node_client.rs
+use tokio::net::{TcpStream, ToSocketAddrs};
+use std::fmt;
+use std::io;
+
+// 라이브러리 크레이트에서 모듈들을 가져옵니다.
+use crate::{block, blockchain, index, merkle, protocol, transaction};
+use block::{Block, BlockHeader};
+use blockchain::{ChainTip, SupplyInfo};
+use index::TransactionInfo;
+use merkle::MerkleProof;
+use protocol::{Envelope, Notification, Request, Response, Subscription, PROTOCOL_VERSION};
+use transaction::{OutPoint, Transaction, TxOutput};
+
+/// 노드에 요청을 보내는 중 발생하는 오류입니다.
+#[derive(Debug)]
+pub enum ClientError {
+    /// 연결 또는 입출력 오류
+    Io(io::Error),
+    /// 노드가 연결을 닫았거나, 응답의 버전/식별자가 요청과 맞지 않습니다.
+    Protocol(String),
+    /// 노드가 요청을 처리하지 못하고 `Response::Error`를 돌려주었습니다.
+    Node(String),
+    /// 요청과 맞지 않는 종류의 응답을 받았습니다.
+    UnexpectedResponse(Box<Response>),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Io(e) => write!(f, "connection error: {}", e),
+            ClientError::Protocol(reason) => write!(f, "protocol error: {}", reason),
+            ClientError::Node(reason) => write!(f, "{}", reason),
+            ClientError::UnexpectedResponse(response) => write!(f, "unexpected response: {:?}", response),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(e: io::Error) -> Self {
+        ClientError::Io(e)
+    }
+}
+
+/// 노드의 클라이언트 포트에 연결하여 요청을 보내는 비동기 클라이언트입니다.
+/// 요청마다 새 식별자를 붙이고, 응답의 식별자가 일치하는지 확인합니다.
+#[derive(Debug)]
+pub struct NodeClient {
+    stream: TcpStream,
+    next_id: u64,
+}
+
+impl NodeClient {
+    /// 주어진 주소의 노드에 연결합니다.
+    pub async fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        Ok(NodeClient { stream, next_id: 1 })
+    }
+
+    /// 요청을 보내고 응답을 기다립니다.
+    /// `Response::Error`도 그대로 반환하므로, 오류 응답을 직접 다루려면 이 함수를 사용합니다.
+    pub async fn request(&mut self, request: Request) -> Result<Response, ClientError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        protocol::write_message(&mut self.stream, &Envelope::new(id, request)).await?;
+
+        read_response(&mut self.stream, id).await?
+            .ok_or_else(|| ClientError::Protocol("connection closed by node".to_string()))
+    }
+
+    /// 연결을 구독 모드로 바꿉니다. 이후 이 연결로는 알림만 받을 수 있으므로 클라이언트를 소비합니다.
+    pub async fn subscribe(mut self, subscriptions: Vec<Subscription>) -> Result<NodeSubscription, ClientError> {
+        let id = self.next_id;
+        match self.call(Request::Subscribe(subscriptions)).await? {
+            Response::Subscribed(_) => Ok(NodeSubscription { stream: self.stream, id }),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 요청을 보내고, `Response::Error`는 `ClientError::Node`로 바꾸어 반환합니다.
+    async fn call(&mut self, request: Request) -> Result<Response, ClientError> {
+        match self.request(request).await? {
+            Response::Error(reason) => Err(ClientError::Node(reason)),
+            response => Ok(response),
+        }
+    }
+
+    /// 트랜잭션을 노드의 대기열에 추가합니다. 성공 메시지를 반환합니다.
+    pub async fn add_transaction(&mut self, tx: Transaction) -> Result<String, ClientError> {
+        match self.call(Request::AddTransaction(tx)).await? {
+            Response::Success(message) => Ok(message),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 노드에서 블록을 채굴합니다. 성공 메시지를 반환합니다.
+    pub async fn mine_block(&mut self, miner_address: &str) -> Result<String, ClientError> {
+        match self.call(Request::MineBlock(miner_address.to_string())).await? {
+            Response::Success(message) => Ok(message),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 주소의 잔액을 조회합니다.
+    pub async fn get_balance(&mut self, address: &str) -> Result<u64, ClientError> {
+        match self.call(Request::GetBalance(address.to_string())).await? {
+            Response::Balance(balance) => Ok(balance),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// `start` 인덱스부터 최대 `limit`개의 블록을 가져옵니다. 노드는 한 페이지의 크기를 제한합니다.
+    pub async fn get_blocks(&mut self, start: u64, limit: u32) -> Result<Vec<Block>, ClientError> {
+        match self.call(Request::GetBlocks { start, limit }).await? {
+            Response::Blocks(blocks) => Ok(blocks),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// `start` 인덱스부터 최대 `limit`개의 블록 헤더를 가져옵니다.
+    pub async fn get_headers(&mut self, start: u64, limit: u32) -> Result<Vec<BlockHeader>, ClientError> {
+        match self.call(Request::GetHeaders { start, limit }).await? {
+            Response::Headers(headers) => Ok(headers),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 인덱스로 블록을 가져옵니다. 없으면 None을 반환합니다.
+    pub async fn get_block_by_index(&mut self, index: u64) -> Result<Option<Block>, ClientError> {
+        match self.call(Request::GetBlockByIndex(index)).await? {
+            Response::Block(block) => Ok(block),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 해시로 메인 체인의 블록을 가져옵니다. 없으면 None을 반환합니다.
+    pub async fn get_block_by_hash(&mut self, hash: &str) -> Result<Option<Block>, ClientError> {
+        match self.call(Request::GetBlockByHash(hash.to_string())).await? {
+            Response::Block(block) => Ok(block),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 체인 끝의 요약 정보(높이, tip 해시, 난이도, 누적 작업량)를 가져옵니다.
+    pub async fn get_chain_tip(&mut self) -> Result<ChainTip, ClientError> {
+        match self.call(Request::GetChainTip).await? {
+            Response::ChainTip(tip) => Ok(tip),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 트랜잭션 ID로 트랜잭션과 그 위치를 조회합니다. 없으면 None을 반환합니다.
+    pub async fn get_transaction(&mut self, txid: &str) -> Result<Option<TransactionInfo>, ClientError> {
+        match self.call(Request::GetTransaction(txid.to_string())).await? {
+            Response::Transaction(info) => Ok(info),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 주소 관련 트랜잭션 기록을 최신 것부터 한 페이지 조회합니다.
+    /// 반환값: (전체 기록 수, 트랜잭션 목록)
+    pub async fn get_address_history(&mut self, address: &str, skip: u32, limit: u32) -> Result<(usize, Vec<TransactionInfo>), ClientError> {
+        let request = Request::GetAddressHistory { address: address.to_string(), skip, limit };
+        match self.call(request).await? {
+            Response::AddressHistory { total, transactions } => Ok((total, transactions)),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 주소가 소유한 미사용 출력을 조회합니다. (UTXO 모드 전용)
+    pub async fn get_utxos(&mut self, address: &str) -> Result<Vec<(OutPoint, TxOutput)>, ClientError> {
+        match self.call(Request::GetUtxos(address.to_string())).await? {
+            Response::Utxos(utxos) => Ok(utxos),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 주소가 다음 트랜잭션에 사용할 nonce와 네트워크 식별자를 조회합니다.
+    pub async fn get_nonce(&mut self, address: &str) -> Result<(u64, u64), ClientError> {
+        match self.call(Request::GetNonce(address.to_string())).await? {
+            Response::Nonce { nonce, chain_id } => Ok((nonce, chain_id)),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 노드의 네트워크 식별자를 조회합니다. 새 주소를 만들거나 주소를 검사할 때 사용합니다.
+    pub async fn get_chain_id(&mut self) -> Result<u64, ClientError> {
+        match self.call(Request::GetChainId).await? {
+            Response::ChainId(chain_id) => Ok(chain_id),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 현재 유통량, 발행 한도, 다음 블록 보조금과 다음 반감 높이를 조회합니다.
+    pub async fn get_supply(&mut self) -> Result<SupplyInfo, ClientError> {
+        match self.call(Request::GetSupply).await? {
+            Response::Supply(supply) => Ok(supply),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// 트랜잭션의 머클 포함 증명과 블록의 머클 루트를 조회합니다.
+    pub async fn get_merkle_proof(&mut self, block_index: u64, txid: &str) -> Result<(String, MerkleProof), ClientError> {
+        match self.call(Request::GetMerkleProof { block_index, txid: txid.to_string() }).await? {
+            Response::MerkleProof { merkle_root, proof } => Ok((merkle_root, proof)),
+            response => Err(ClientError::UnexpectedResponse(Box::new(response))),
+        }
+    }
+}
+
+/// 구독 모드로 바뀐 연결입니다. 노드가 보내는 알림을 차례로 받습니다.
+#[derive(Debug)]
+pub struct NodeSubscription {
+    stream: TcpStream,
+    id: u64,
+}
+
+impl NodeSubscription {
+    /// 다음 알림을 기다립니다. 노드가 연결을 닫았으면 None을 반환합니다.
+    pub async fn next(&mut self) -> Result<Option<Notification>, ClientError> {
+        match read_response(&mut self.stream, self.id).await? {
+            Some(Response::Notification(notification)) => Ok(Some(notification)),
+            Some(Response::Error(reason)) => Err(ClientError::Node(reason)),
+            Some(response) => Err(ClientError::UnexpectedResponse(Box::new(response))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// 응답 하나를 읽고 버전과 요청 id가 맞는지 확인합니다. 연결이 닫혔으면 None을 반환합니다.
+async fn read_response(stream: &mut TcpStream, id: u64) -> Result<Option<Response>, ClientError> {
+    let envelope: Envelope<Response> = match protocol::read_message(stream).await? {
+        Some(envelope) => envelope,
+        None => return Ok(None),
+    };
+    if envelope.version != PROTOCOL_VERSION {
+        return Err(ClientError::Protocol(format!(
+            "node speaks protocol version {}, expected {}", envelope.version, PROTOCOL_VERSION
+        )));
+    }
+    if envelope.id != id {
+        return Err(ClientError::Protocol(format!(
+            "response id {} does not match request id {}", envelope.id, id
+        )));
+    }
+    Ok(Some(envelope.body))
+}
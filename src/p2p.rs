@@ -0,0 +1,457 @@
+// src/p2p.rs
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use log::{debug, info, warn};
+
+// 라이브러리 크레이트에서 모듈들을 가져옵니다.
+use crate::{block, blockchain, encoding, error, protocol, transaction};
+use block::Block;
+use blockchain::{BlockAcceptance, Blockchain};
+use encoding::{Decoder, Encoder};
+use error::BlockchainError;
+use protocol::{read_frame, write_frame};
+use transaction::Transaction;
+
+/// 한 번의 `Blocks` 메시지로 보내는 최대 블록 수
+pub const MAX_BLOCKS_PER_MESSAGE: usize = 500;
+/// 중복 전파를 막기 위해 기억하는 블록/트랜잭션 해시 수
+const SEEN_CACHE_SIZE: usize = 10_000;
+/// 설정된 피어와의 연결이 끊겼을 때 다시 연결을 시도하는 간격
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+/// 피어 하나에 보내려고 쌓아 둘 수 있는 최대 메시지 수.
+/// 가득 차면 전파 메시지는 그 피어에게 보내지 않고 버립니다. (놓친 블록은 다음 블록을 받을 때 동기화합니다)
+const PEER_SEND_QUEUE_SIZE: usize = 256;
+
+/// 노드 간에 주고받는 메시지입니다.
+/// 메시지는 종류 태그(1바이트) 뒤에 내용을 정규 바이너리 인코딩(encoding 모듈)으로 붙여 프레임 하나로 보냅니다.
+#[derive(Debug, Clone)]
+pub enum PeerMessage {
+    /// 연결 직후 서로의 정보를 교환합니다.
+    Hello {
+        listen_addr: String,
+        genesis_hash: String,
+        chain_id: u64,
+        height: u64,
+        total_work: u128,
+    },
+    /// 새로 채굴되었거나 전달받은 블록
+    NewBlock(Block),
+    /// 대기열에 새로 추가된 트랜잭션
+    NewTransaction(Transaction),
+    /// locator의 블록 중 상대가 가진 가장 최근 블록 다음부터의 블록을 요청합니다. (체인 동기화)
+    GetBlocks { locator: Vec<String> },
+    /// `GetBlocks`에 대한 응답
+    Blocks(Vec<Block>),
+}
+
+// 메시지 종류 태그
+const TAG_HELLO: u8 = 0;
+const TAG_NEW_BLOCK: u8 = 1;
+const TAG_NEW_TRANSACTION: u8 = 2;
+const TAG_GET_BLOCKS: u8 = 3;
+const TAG_BLOCKS: u8 = 4;
+
+impl PeerMessage {
+    /// 메시지를 바이너리로 인코딩합니다. 블록과 트랜잭션은 각자의 정규 인코딩을 길이와 함께 담습니다.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = Encoder::versioned();
+        match self {
+            PeerMessage::Hello { listen_addr, genesis_hash, chain_id, height, total_work } => {
+                encoder.u8(TAG_HELLO).str(listen_addr).str(genesis_hash)
+                    .u64(*chain_id).u64(*height).u128(*total_work);
+            }
+            PeerMessage::NewBlock(block) => {
+                encoder.u8(TAG_NEW_BLOCK).bytes(&block.encode());
+            }
+            PeerMessage::NewTransaction(tx) => {
+                encoder.u8(TAG_NEW_TRANSACTION).bytes(&tx.encode());
+            }
+            PeerMessage::GetBlocks { locator } => {
+                encoder.u8(TAG_GET_BLOCKS).count(locator.len());
+                for hash in locator.iter() {
+                    encoder.str(hash);
+                }
+            }
+            PeerMessage::Blocks(blocks) => {
+                encoder.u8(TAG_BLOCKS).count(blocks.len());
+                for block in blocks.iter() {
+                    encoder.bytes(&block.encode());
+                }
+            }
+        }
+        encoder.into_bytes()
+    }
+
+    /// `encode`로 만든 바이트열에서 메시지를 읽습니다.
+    pub fn decode(bytes: &[u8]) -> Result<Self, BlockchainError> {
+        let mut decoder = Decoder::new(bytes);
+        decoder.version()?;
+        let message = match decoder.u8()? {
+            TAG_HELLO => PeerMessage::Hello {
+                listen_addr: decoder.str()?,
+                genesis_hash: decoder.str()?,
+                chain_id: decoder.u64()?,
+                height: decoder.u64()?,
+                total_work: decoder.u128()?,
+            },
+            TAG_NEW_BLOCK => PeerMessage::NewBlock(Block::decode(decoder.bytes()?)?),
+            TAG_NEW_TRANSACTION => PeerMessage::NewTransaction(Transaction::decode(decoder.bytes()?)?),
+            TAG_GET_BLOCKS => {
+                let mut locator = Vec::new();
+                for _ in 0..decoder.count()? {
+                    locator.push(decoder.str()?);
+                }
+                PeerMessage::GetBlocks { locator }
+            }
+            TAG_BLOCKS => {
+                let mut blocks = Vec::new();
+                for _ in 0..decoder.count()? {
+                    blocks.push(Block::decode(decoder.bytes()?)?);
+                }
+                PeerMessage::Blocks(blocks)
+            }
+            tag => return Err(BlockchainError::MalformedEncoding(format!("unknown peer message tag {}", tag))),
+        };
+        decoder.finish()?;
+        Ok(message)
+    }
+}
+
+/// 최근에 본 블록/트랜잭션 해시를 기억하여 같은 메시지를 다시 전파하지 않도록 합니다.
+#[derive(Debug, Default)]
+struct SeenCache {
+    hashes: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl SeenCache {
+    /// 처음 보는 해시이면 기록하고 true를 반환합니다.
+    fn insert(&mut self, hash: String) -> bool {
+        if !self.hashes.insert(hash.clone()) {
+            return false;
+        }
+        self.order.push_back(hash);
+        if self.order.len() > SEEN_CACHE_SIZE
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.hashes.remove(&oldest);
+        }
+        true
+    }
+}
+
+/// 다른 노드들과 블록/트랜잭션을 주고받는 P2P 네트워크 계층입니다.
+#[derive(Debug)]
+pub struct PeerNetwork {
+    blockchain: Arc<Mutex<Blockchain>>,
+    listen_addr: String,
+    peers: Mutex<HashMap<u64, mpsc::Sender<PeerMessage>>>, // 핸드셰이크를 마친 피어만 등록됩니다.
+    next_peer_id: AtomicU64,
+    seen: Mutex<SeenCache>,
+}
+
+impl PeerNetwork {
+    /// `listen_addr`: 다른 노드가 이 노드에 접속할 P2P 주소
+    pub fn new(blockchain: Arc<Mutex<Blockchain>>, listen_addr: String) -> Arc<Self> {
+        Arc::new(PeerNetwork {
+            blockchain,
+            listen_addr,
+            peers: Mutex::new(HashMap::new()),
+            next_peer_id: AtomicU64::new(0),
+            seen: Mutex::new(SeenCache::default()),
+        })
+    }
+
+    /// P2P 리스너를 열고, 설정된 피어들에 연결을 유지하며, 들어오는 연결을 처리합니다.
+    pub async fn run(self: Arc<Self>, peers: Vec<String>) -> io::Result<()> {
+        let listener = TcpListener::bind(&self.listen_addr).await?;
+        info!("P2P 노드가 {}에서 실행 중입니다.", self.listen_addr);
+
+        for peer_addr in peers {
+            let network = Arc::clone(&self);
+            tokio::spawn(network.maintain_outbound(peer_addr));
+        }
+
+        loop {
+            let (socket, addr) = listener.accept().await?;
+            let network = Arc::clone(&self);
+            tokio::spawn(async move {
+                if let Err(e) = network.handle_peer(socket).await {
+                    warn!("피어 {} 처리 오류: {:?}", addr, e);
+                }
+            });
+        }
+    }
+
+    /// 현재 연결된 피어 수를 반환합니다.
+    pub fn peer_count(&self) -> usize {
+        self.peers.lock().unwrap().len()
+    }
+
+    /// 이 노드가 만든 블록을 모든 피어에게 알립니다.
+    pub fn announce_block(&self, block: &Block) {
+        if self.mark_seen(format!("block:{}", block.hash)) {
+            self.broadcast(PeerMessage::NewBlock(block.clone()), None);
+        }
+    }
+
+    /// 이 노드의 대기열에 추가된 트랜잭션을 모든 피어에게 알립니다.
+    pub fn announce_transaction(&self, tx: &Transaction) {
+        if self.mark_seen(format!("tx:{}", tx.txid())) {
+            self.broadcast(PeerMessage::NewTransaction(tx.clone()), None);
+        }
+    }
+
+    /// 설정된 피어에 연결하고, 연결이 끊기면 일정 시간 후 다시 연결합니다.
+    async fn maintain_outbound(self: Arc<Self>, peer_addr: String) {
+        loop {
+            match TcpStream::connect(&peer_addr).await {
+                Ok(socket) => {
+                    info!("피어 {}에 연결되었습니다.", peer_addr);
+                    if let Err(e) = Arc::clone(&self).handle_peer(socket).await {
+                        warn!("피어 {} 처리 오류: {:?}", peer_addr, e);
+                    }
+                    info!("피어 {}와의 연결이 끊어졌습니다.", peer_addr);
+                }
+                Err(e) => warn!("피어 {} 연결 실패: {}", peer_addr, e),
+            }
+            tokio::time::sleep(RECONNECT_INTERVAL).await;
+        }
+    }
+
+    /// 하나의 피어 연결을 처리합니다. 연결이 끊길 때까지 반환하지 않습니다.
+    ///
+    /// 상대의 `Hello`를 확인하기 전에는 피어로 등록하지 않아 전파 메시지를 보내지 않으며,
+    /// 그 전에 받은 다른 메시지는 처리하지 않고 버립니다.
+    async fn handle_peer(self: Arc<Self>, socket: TcpStream) -> io::Result<()> {
+        let (mut reader, mut writer) = socket.into_split();
+        let (sender, mut receiver) = mpsc::channel::<PeerMessage>(PEER_SEND_QUEUE_SIZE);
+
+        // 보낼 메시지는 채널을 통해 전용 쓰기 작업으로 전달됩니다.
+        let writer_task = tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                if write_frame(&mut writer, &message.encode()).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let peer_id = self.next_peer_id.fetch_add(1, Ordering::Relaxed);
+        let _ = sender.send(self.hello()).await;
+        let mut handshake = Handshake::Pending;
+
+        let result = loop {
+            let message = match read_frame(&mut reader).await {
+                Ok(Some(frame)) => match PeerMessage::decode(&frame) {
+                    Ok(message) => message,
+                    Err(e) => break Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+                },
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            };
+            let is_hello = matches!(message, PeerMessage::Hello { .. });
+            match (handshake, is_hello) {
+                (Handshake::Pending, false) => {
+                    debug!("피어 {}가 핸드셰이크 전에 보낸 메시지를 버립니다.", peer_id);
+                    continue;
+                }
+                (Handshake::Done, true) => {
+                    debug!("피어 {}가 다시 보낸 Hello 메시지를 무시합니다.", peer_id);
+                    continue;
+                }
+                _ => {}
+            }
+            let Some(replies) = self.handle_message(peer_id, message) else {
+                break Ok(()); // 호환되지 않는 피어
+            };
+            if is_hello {
+                handshake = Handshake::Done;
+                self.peers.lock().unwrap().insert(peer_id, sender.clone());
+            }
+            for reply in replies {
+                let _ = sender.send(reply).await;
+            }
+        };
+
+        self.peers.lock().unwrap().remove(&peer_id);
+        drop(sender);
+        let _ = writer_task.await;
+        result
+    }
+
+    /// 수신한 메시지를 처리하고, 해당 피어에게 보낼 응답 메시지를 반환합니다.
+    /// 연결을 끊어야 하면 None을 반환합니다.
+    fn handle_message(&self, peer_id: u64, message: PeerMessage) -> Option<Vec<PeerMessage>> {
+        let mut replies = Vec::new();
+        match message {
+            PeerMessage::Hello { listen_addr, genesis_hash, chain_id, height, total_work } => {
+                let bc = self.blockchain.lock().unwrap();
+                // 제네시스 블록 해시와 네트워크 식별자가 모두 같아야 같은 네트워크입니다.
+                if bc.chain[0].hash != genesis_hash || bc.chain_id != chain_id {
+                    warn!("피어 {}는 다른 네트워크입니다. (제네시스 {}, 네트워크 식별자 {}) 연결을 종료합니다.",
+                        listen_addr, genesis_hash, chain_id);
+                    return None;
+                }
+                info!("피어 {} (높이 {})와 핸드셰이크 완료.", listen_addr, height);
+                // 상대 체인의 누적 작업량이 더 많으면 동기화합니다.
+                if total_work > bc.cumulative_work() {
+                    replies.push(PeerMessage::GetBlocks { locator: bc.block_locator() });
+                }
+            }
+            PeerMessage::NewBlock(block) => {
+                if !self.mark_seen(format!("block:{}", block.hash)) {
+                    return Some(replies);
+                }
+                let mut bc = self.blockchain.lock().unwrap();
+                match bc.accept_block(block.clone()) {
+                    BlockAcceptance::Extended | BlockAcceptance::Reorganized => {
+                        drop(bc);
+                        self.broadcast(PeerMessage::NewBlock(block), Some(peer_id));
+                    }
+                    // 이전 블록을 모르므로 먼저 동기화합니다.
+                    BlockAcceptance::Orphan => {
+                        replies.push(PeerMessage::GetBlocks { locator: bc.block_locator() });
+                    }
+                    _ => {}
+                }
+            }
+            PeerMessage::NewTransaction(tx) => {
+                if !self.mark_seen(format!("tx:{}", tx.txid())) {
+                    return Some(replies);
+                }
+                let result = self.blockchain.lock().unwrap().add_transaction(tx.clone());
+                match result {
+                    Ok(()) => self.broadcast(PeerMessage::NewTransaction(tx), Some(peer_id)),
+                    Err(e) => debug!("피어 {}의 트랜잭션 거부: {}", peer_id, e),
+                }
+            }
+            PeerMessage::GetBlocks { locator } => {
+                let bc = self.blockchain.lock().unwrap();
+                replies.push(PeerMessage::Blocks(bc.blocks_after_locator(&locator, MAX_BLOCKS_PER_MESSAGE)));
+            }
+            PeerMessage::Blocks(blocks) => {
+                let received = blocks.len();
+                let mut bc = self.blockchain.lock().unwrap();
+                let old_tip = bc.get_latest_block().unwrap().hash.clone();
+                let mut all_accepted = true;
+                for block in blocks {
+                    self.mark_seen(format!("block:{}", block.hash));
+                    match bc.accept_block(block) {
+                        BlockAcceptance::Orphan | BlockAcceptance::Rejected => {
+                            all_accepted = false;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                // 가득 찬 응답을 받았다면 남은 블록이 더 있을 수 있습니다.
+                if all_accepted && received == MAX_BLOCKS_PER_MESSAGE {
+                    replies.push(PeerMessage::GetBlocks { locator: bc.block_locator() });
+                }
+                let new_tip = bc.get_latest_block().cloned().unwrap();
+                drop(bc);
+                // 동기화로 새 tip을 얻었다면 다른 피어에게도 알려 뒤처진 노드가 따라올 수 있게 합니다.
+                if new_tip.hash != old_tip {
+                    self.broadcast(PeerMessage::NewBlock(new_tip), Some(peer_id));
+                }
+            }
+        }
+        Some(replies)
+    }
+
+    fn hello(&self) -> PeerMessage {
+        let bc = self.blockchain.lock().unwrap();
+        PeerMessage::Hello {
+            listen_addr: self.listen_addr.clone(),
+            genesis_hash: bc.chain[0].hash.clone(),
+            chain_id: bc.chain_id,
+            height: bc.chain.len() as u64,
+            total_work: bc.cumulative_work(),
+        }
+    }
+
+    fn mark_seen(&self, key: String) -> bool {
+        self.seen.lock().unwrap().insert(key)
+    }
+
+    /// `except` 피어를 제외한 모든 피어에게 메시지를 보냅니다.
+    /// 전송 대기열이 가득 찬 피어에게는 보내지 않습니다.
+    fn broadcast(&self, message: PeerMessage, except: Option<u64>) {
+        let peers = self.peers.lock().unwrap();
+        for (peer_id, sender) in peers.iter() {
+            if Some(*peer_id) != except
+                && let Err(mpsc::error::TrySendError::Full(_)) = sender.try_send(message.clone())
+            {
+                warn!("피어 {}의 전송 대기열이 가득 차 메시지를 버립니다.", peer_id);
+            }
+        }
+    }
+}
+
+// 연결별 핸드셰이크 상태
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Handshake {
+    /// 상대의 Hello를 기다리는 중
+    Pending,
+    /// Hello를 확인하여 피어로 등록됨
+    Done,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Network;
+    use crate::encoding::ENCODING_VERSION;
+    use crate::wallet::Wallet;
+
+    #[test]
+    fn messages_round_trip() {
+        let mut chain = Blockchain::new(0x207f_ffff);
+        let miner = Wallet::new_for_network(Network::Mainnet);
+        chain.mine_pending_transactions(miner.address.clone()).unwrap();
+        let block = chain.get_latest_block().unwrap().clone();
+        let messages = [
+            PeerMessage::Hello {
+                listen_addr: "127.0.0.1:7000".to_string(),
+                genesis_hash: chain.chain[0].hash.clone(),
+                chain_id: chain.chain_id,
+                height: 2,
+                total_work: chain.cumulative_work(),
+            },
+            PeerMessage::NewBlock(block.clone()),
+            PeerMessage::NewTransaction(block.transactions[0].clone()),
+            PeerMessage::GetBlocks { locator: chain.block_locator() },
+            PeerMessage::Blocks(chain.chain.clone()),
+        ];
+        for message in messages {
+            let bytes = message.encode();
+            assert_eq!(PeerMessage::decode(&bytes).unwrap().encode(), bytes);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_tag_and_trailing_bytes() {
+        assert!(PeerMessage::decode(&[ENCODING_VERSION, 9]).is_err());
+        let mut bytes = PeerMessage::GetBlocks { locator: Vec::new() }.encode();
+        bytes.push(0);
+        assert!(PeerMessage::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn seen_cache_forgets_the_oldest_hash() {
+        let mut seen = SeenCache::default();
+        assert!(seen.insert("block:0".to_string()));
+        assert!(!seen.insert("block:0".to_string()));
+        for i in 1..=SEEN_CACHE_SIZE {
+            assert!(seen.insert(format!("block:{}", i)));
+        }
+        assert_eq!(seen.hashes.len(), SEEN_CACHE_SIZE);
+        assert!(seen.insert("block:0".to_string()));
+        assert!(!seen.insert(format!("block:{}", SEEN_CACHE_SIZE)));
+    }
+}
@@ -0,0 +1,157 @@
+// src/protocol.rs
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use serde::de::DeserializeOwned;
+use serde::{Serialize, Deserialize};
+use std::io;
+
+// 라이브러리 크레이트에서 모듈들을 가져옵니다.
+use crate::{block, blockchain, index, merkle, transaction};
+use block::{Block, BlockHeader};
+use blockchain::{ChainTip, SupplyInfo};
+use index::TransactionInfo;
+use merkle::MerkleProof;
+use transaction::{OutPoint, Transaction, TxOutput};
+
+/// 클라이언트-노드 프로토콜 버전. 메시지 형식이 호환되지 않게 바뀌면 올립니다.
+/// 2: 노드에서 지갑을 생성하는 `GenerateWallet` 제거 (개인 키는 클라이언트의 키 저장소에서만 다룹니다)
+/// 3: 체인 전체를 한 번에 보내는 `GetChain` 제거 (페이지 단위의 `GetBlocks`, `GetHeaders`를 사용합니다)
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// 한 프레임(메시지)의 최대 크기 (바이트)
+pub const MAX_FRAME_SIZE: usize = 32 * 1024 * 1024;
+
+/// `GetBlocks` 한 번에 돌려주는 최대 블록 수
+pub const MAX_BLOCKS_PER_PAGE: u32 = 100;
+/// `GetHeaders` 한 번에 돌려주는 최대 헤더 수
+pub const MAX_HEADERS_PER_PAGE: u32 = 2000;
+/// `GetAddressHistory` 한 번에 돌려주는 최대 트랜잭션 수
+pub const MAX_HISTORY_PER_PAGE: u32 = 100;
+
+// 클라이언트와 서버 간 메시지 유형 정의
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Request {
+    AddTransaction(Transaction),
+    MineBlock(String), // miner_address
+    GetBalance(String), // address
+    GetBlocks { start: u64, limit: u32 },  // start 인덱스부터 최대 limit개 (MAX_BLOCKS_PER_PAGE로 제한)
+    GetHeaders { start: u64, limit: u32 }, // start 인덱스부터 최대 limit개 (MAX_HEADERS_PER_PAGE로 제한)
+    GetBlockByIndex(u64),
+    GetBlockByHash(String),
+    GetChainTip,
+    GetTransaction(String), // txid (메인 체인과 대기열에서 찾음)
+    // 주소 관련 트랜잭션을 최신 것부터 skip개 건너뛰고 최대 limit개 (MAX_HISTORY_PER_PAGE로 제한)
+    GetAddressHistory { address: String, skip: u32, limit: u32 },
+    GetUtxos(String), // address (UTXO 모드 전용)
+    GetNonce(String), // address
+    GetChainId,       // 노드의 네트워크 식별자 (주소의 네트워크 접두사를 정함)
+    GetSupply,        // 현재 유통량, 발행 한도, 다음 반감 높이
+    GetMerkleProof { block_index: u64, txid: String },
+    // 연결을 구독 모드로 바꿉니다. 이후 서버는 이 요청의 id로 `Response::Notification`을 계속 보냅니다.
+    Subscribe(Vec<Subscription>),
+    // 필요에 따라 다른 요청 추가
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Response {
+    Success(String),
+    Blocks(Vec<Block>),
+    Headers(Vec<BlockHeader>),
+    Block(Option<Block>), // 블록이 없으면 None
+    ChainTip(ChainTip),
+    Transaction(Option<TransactionInfo>), // 트랜잭션이 없으면 None
+    AddressHistory { total: usize, transactions: Vec<TransactionInfo> },
+    Balance(u64),
+    Utxos(Vec<(OutPoint, TxOutput)>),
+    Nonce { nonce: u64, chain_id: u64 },
+    ChainId(u64),
+    Supply(SupplyInfo),
+    MerkleProof { merkle_root: String, proof: MerkleProof },
+    Subscribed(Vec<Subscription>),
+    Notification(Notification),
+    Error(String),
+    // 필요에 따라 다른 응답 추가
+}
+
+/// 구독할 이벤트 종류입니다.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Subscription {
+    NewBlockHeaders,         // 메인 체인에 연결되는 블록의 헤더
+    PendingTransactions,     // 대기열에 새로 추가되는 트랜잭션
+    AddressActivity(String), // 주소가 송신자/수신자인 트랜잭션 (대기열 추가 및 블록 포함)
+}
+
+/// 구독 모드에서 서버가 보내는 알림입니다.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Notification {
+    NewBlockHeader(BlockHeader),
+    PendingTransaction(Transaction),
+    // block_index가 None이면 대기열에 추가된 트랜잭션, Some이면 해당 블록에 포함된 트랜잭션입니다.
+    AddressActivity { address: String, block_index: Option<u64>, transaction: Transaction },
+    // 구독자가 처리 속도를 따라가지 못해 이벤트를 놓쳤습니다. 필요하면 체인을 다시 조회해야 합니다.
+    Lagged { missed: u64 },
+}
+
+/// 요청과 응답을 감싸는 메시지 봉투입니다.
+/// 서버는 요청의 `id`를 응답에 그대로 돌려주므로 클라이언트는 응답을 요청과 짝지을 수 있습니다.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    pub version: u32, // 프로토콜 버전 (PROTOCOL_VERSION)
+    pub id: u64,      // 요청 식별자 (응답은 요청과 같은 값)
+    pub body: T,
+}
+
+impl<T> Envelope<T> {
+    /// 현재 프로토콜 버전으로 메시지를 감쌉니다.
+    pub fn new(id: u64, body: T) -> Self {
+        Envelope { version: PROTOCOL_VERSION, id, body }
+    }
+}
+
+/// 길이(u32, big-endian) + 페이로드 형식으로 프레임 하나를 보냅니다.
+pub async fn write_frame<W: AsyncWriteExt + Unpin>(writer: &mut W, payload: &[u8]) -> io::Result<()> {
+    let payload_len = u32::try_from(payload.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Frame too large"))?;
+    writer.write_all(&payload_len.to_be_bytes()).await?;
+    writer.write_all(payload).await?;
+    Ok(())
+}
+
+/// 프레임 하나를 읽습니다. 연결이 닫혔거나 길이 0인 프레임(종료 신호)을 받으면 None을 반환합니다.
+pub async fn read_frame<R: AsyncReadExt + Unpin>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut len_bytes = [0u8; 4];
+    if reader.read_exact(&mut len_bytes).await.is_err() {
+        return Ok(None);
+    }
+    let frame_len = u32::from_be_bytes(len_bytes) as usize;
+    if frame_len == 0 {
+        return Ok(None);
+    }
+    if frame_len > MAX_FRAME_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Frame too large"));
+    }
+    let mut buffer = vec![0u8; frame_len];
+    reader.read_exact(&mut buffer).await?;
+    Ok(Some(buffer))
+}
+
+/// 메시지를 JSON으로 직렬화하여 프레임 하나로 보냅니다.
+pub async fn write_message<W, T>(writer: &mut W, message: &T) -> io::Result<()>
+where
+    W: AsyncWriteExt + Unpin,
+    T: Serialize,
+{
+    let json = serde_json::to_vec(message)?;
+    write_frame(writer, &json).await
+}
+
+/// 프레임 하나를 읽어 JSON 메시지로 역직렬화합니다. 연결이 닫혔으면 None을 반환합니다.
+pub async fn read_message<R, T>(reader: &mut R) -> io::Result<Option<T>>
+where
+    R: AsyncReadExt + Unpin,
+    T: DeserializeOwned,
+{
+    match read_frame(reader).await? {
+        Some(frame) => Ok(Some(serde_json::from_slice(&frame)?)),
+        None => Ok(None),
+    }
+}
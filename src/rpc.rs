@@ -0,0 +1,522 @@
+// src/rpc.rs
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io;
+use std::sync::{Arc, Mutex};
+use log::warn;
+
+// 라이브러리 크레이트에서 모듈들을 가져옵니다.
+use crate::{blockchain, difficulty, error, p2p, protocol, transaction};
+use blockchain::{Blockchain, LedgerMode};
+use difficulty::Target;
+use error::BlockchainError;
+use p2p::PeerNetwork;
+use transaction::Transaction;
+
+/// HTTP 요청 헤더 부분(요청 줄 + 헤더)의 최대 크기 (바이트)
+const MAX_HEADER_SIZE: u64 = 16 * 1024;
+/// HTTP 요청 본문의 최대 크기 (바이트)
+const MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+// JSON-RPC 2.0 표준 오류 코드
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+// 서버 정의 오류 코드 (-32000 ~ -32099): 트랜잭션 검증 실패 유형
+/// 형식이나 서명이 올바르지 않은 트랜잭션
+pub const INVALID_TRANSACTION: i64 = -32001;
+/// 잔액 부족, 존재하지 않거나 이미 소비된 입력 등 자금을 사용할 수 없는 트랜잭션
+pub const UNSPENDABLE_FUNDS: i64 = -32002;
+/// nonce 또는 네트워크 식별자가 맞지 않는 트랜잭션
+pub const NONCE_MISMATCH: i64 = -32003;
+/// 대기열이 가득 차 받지 못한 트랜잭션 (수수료를 올리거나 나중에 다시 보내야 합니다)
+pub const MEMPOOL_FULL: i64 = -32004;
+
+/// JSON-RPC 오류 객체입니다.
+#[derive(Debug, Clone, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError { code, message: message.into() }
+    }
+}
+
+impl From<BlockchainError> for RpcError {
+    /// 검증 실패 유형을 JSON-RPC 오류 코드로 변환합니다.
+    /// 해석할 수 없는 인코딩과 잘못된 주소는 요청 매개변수의 문제로 보고 `INVALID_PARAMS`를 사용합니다.
+    fn from(e: BlockchainError) -> Self {
+        let code = match e {
+            BlockchainError::MalformedEncoding(_)
+            | BlockchainError::InvalidAddress { .. }
+            | BlockchainError::WrongNetwork { .. }
+            | BlockchainError::EmptyAddress => INVALID_PARAMS,
+            BlockchainError::MempoolFull(_) => MEMPOOL_FULL,
+            BlockchainError::InsufficientFunds { .. }
+            | BlockchainError::UnknownInput(_)
+            | BlockchainError::DuplicateInput(_)
+            | BlockchainError::InputNotOwned(_)
+            | BlockchainError::UnbalancedTransaction { .. }
+            | BlockchainError::DuplicateOutput(_)
+            | BlockchainError::AmountOverflow => UNSPENDABLE_FUNDS,
+            BlockchainError::InvalidNonce { .. } | BlockchainError::WrongChainId { .. } => NONCE_MISMATCH,
+            BlockchainError::Storage(_) | BlockchainError::EmptyChain => INTERNAL_ERROR,
+            _ => INVALID_TRANSACTION,
+        };
+        RpcError::new(code, e.to_string())
+    }
+}
+
+/// HTTP JSON-RPC 요청을 받는 서버를 실행합니다. 모든 요청은 `POST`로 받습니다.
+///
+/// 브라우저의 요청(`Origin` 헤더가 있는 요청)은 `allowed_origin`과 출처가 같을 때만 처리하고 CORS 헤더를 붙입니다.
+/// `allowed_origin`이 없으면 모든 브라우저 요청을 거부하여, 임의의 웹 페이지가 노드에 트랜잭션을 보내지 못하게 합니다.
+pub async fn serve(
+    listener: TcpListener,
+    blockchain: Arc<Mutex<Blockchain>>,
+    network: Arc<PeerNetwork>,
+    allowed_origin: Option<String>,
+) -> io::Result<()> {
+    loop {
+        let (socket, addr) = listener.accept().await?;
+        let blockchain = Arc::clone(&blockchain);
+        let network = Arc::clone(&network);
+        let allowed_origin = allowed_origin.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(socket, blockchain, network, allowed_origin).await {
+                warn!("RPC 연결 {} 처리 오류: {:?}", addr, e);
+            }
+        });
+    }
+}
+
+/// 읽어들인 HTTP 요청의 필요한 부분입니다.
+struct HttpRequest {
+    method: String,
+    body: Vec<u8>,
+    keep_alive: bool,
+    origin: Option<String>, // 브라우저가 보낸 요청 출처
+    // Transfer-Encoding(chunked 등)이 적용된 요청. 지원하지 않으므로 본문을 읽지 않았습니다.
+    transfer_encoded: bool,
+}
+
+/// 하나의 HTTP 연결을 처리합니다. keep-alive 연결에서는 여러 요청을 차례로 처리합니다.
+async fn handle_connection(
+    socket: TcpStream,
+    blockchain: Arc<Mutex<Blockchain>>,
+    network: Arc<PeerNetwork>,
+    allowed_origin: Option<String>,
+) -> io::Result<()> {
+    let (reader, mut writer) = socket.into_split();
+    let mut reader = BufReader::new(reader);
+
+    loop {
+        let request = match read_http_request(&mut reader).await {
+            Ok(Some(request)) => request,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                write_http_response(&mut writer, "400 Bad Request", None, None, false).await?;
+                return Err(e);
+            }
+        };
+
+        // 본문의 끝을 알 수 없으므로 응답한 뒤 연결을 닫습니다.
+        if request.transfer_encoded {
+            write_http_response(&mut writer, "501 Not Implemented", None, None, false).await?;
+            return Ok(());
+        }
+
+        // 허용하지 않은 출처의 브라우저 요청은 처리하지 않습니다. (CORS 사전 요청이 필요 없는 단순 요청 포함)
+        let cors_origin = match &request.origin {
+            Some(origin) if allowed_origin.as_ref() == Some(origin) => Some(origin.as_str()),
+            Some(_) => {
+                write_http_response(&mut writer, "403 Forbidden", None, None, request.keep_alive).await?;
+                if !request.keep_alive {
+                    return Ok(());
+                }
+                continue;
+            }
+            None => None,
+        };
+
+        match request.method.as_str() {
+            "POST" => {
+                let response = handle_body(&request.body, &blockchain, &network);
+                match response {
+                    Some(response) => {
+                        let body = serde_json::to_vec(&response)?;
+                        write_http_response(&mut writer, "200 OK", Some(&body), cors_origin, request.keep_alive).await?;
+                    }
+                    // 알림(id가 없는 요청)만 있으면 돌려줄 내용이 없습니다.
+                    None => write_http_response(&mut writer, "204 No Content", None, cors_origin, request.keep_alive).await?,
+                }
+            }
+            // 브라우저 대시보드의 CORS 사전 요청
+            "OPTIONS" => write_http_response(&mut writer, "204 No Content", None, cors_origin, request.keep_alive).await?,
+            _ => write_http_response(&mut writer, "405 Method Not Allowed", None, cors_origin, request.keep_alive).await?,
+        }
+
+        if !request.keep_alive {
+            return Ok(());
+        }
+    }
+}
+
+/// HTTP/1.1 요청 하나를 읽습니다. 요청 전에 연결이 닫혔으면 None을 반환합니다.
+/// 본문은 Content-Length로만 받으며, Transfer-Encoding이 있으면 본문을 읽지 않고 `transfer_encoded`로 표시합니다.
+async fn read_http_request<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> io::Result<Option<HttpRequest>> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+    // 헤더 부분은 크기를 제한하여 읽습니다.
+    let mut head = (&mut *reader).take(MAX_HEADER_SIZE);
+    let mut request_line = String::new();
+    if head.read_line(&mut request_line).await? == 0 {
+        return Ok(None);
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().ok_or_else(|| invalid("Missing HTTP method"))?.to_string();
+    let _path = parts.next().ok_or_else(|| invalid("Missing request path"))?;
+    let version = parts.next().unwrap_or("HTTP/1.0");
+    let mut keep_alive = version == "HTTP/1.1";
+
+    let mut content_length = 0usize;
+    let mut origin = None;
+    let mut transfer_encoded = false;
+    loop {
+        let mut line = String::new();
+        if head.read_line(&mut line).await? == 0 {
+            return Err(invalid("HTTP headers too large or truncated"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':').ok_or_else(|| invalid("Malformed HTTP header"))?;
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => {
+                content_length = value.parse().map_err(|_| invalid("Invalid Content-Length"))?;
+            }
+            "transfer-encoding" => transfer_encoded = true,
+            "origin" => origin = Some(value.to_string()),
+            "connection" => {
+                if value.eq_ignore_ascii_case("close") {
+                    keep_alive = false;
+                } else if value.eq_ignore_ascii_case("keep-alive") {
+                    keep_alive = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if transfer_encoded {
+        return Ok(Some(HttpRequest { method, body: Vec::new(), keep_alive: false, origin, transfer_encoded }));
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err(invalid("HTTP body too large"));
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(HttpRequest { method, body, keep_alive, origin, transfer_encoded }))
+}
+
+/// HTTP 응답을 보냅니다. `cors_origin`이 있으면 그 출처에 대한 CORS 헤더를 붙입니다.
+async fn write_http_response<W: AsyncWriteExt + Unpin>(
+    writer: &mut W,
+    status: &str,
+    body: Option<&[u8]>,
+    cors_origin: Option<&str>,
+    keep_alive: bool,
+) -> io::Result<()> {
+    let body = body.unwrap_or_default();
+    let cors = match cors_origin {
+        Some(origin) => format!(
+            "Access-Control-Allow-Origin: {}\r\n\
+             Access-Control-Allow-Methods: POST, OPTIONS\r\n\
+             Access-Control-Allow-Headers: Content-Type\r\n\
+             Vary: Origin\r\n",
+            origin,
+        ),
+        None => String::new(),
+    };
+    let head = format!(
+        "HTTP/1.1 {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         {}\
+         Connection: {}\r\n\r\n",
+        status,
+        body.len(),
+        cors,
+        if keep_alive { "keep-alive" } else { "close" },
+    );
+    writer.write_all(head.as_bytes()).await?;
+    writer.write_all(body).await?;
+    writer.flush().await
+}
+
+/// HTTP 본문(단일 요청 또는 배치 요청)을 처리하여 JSON-RPC 응답을 만듭니다.
+/// 돌려줄 응답이 없으면(알림만 있는 경우) None을 반환합니다.
+fn handle_body(body: &[u8], blockchain: &Mutex<Blockchain>, network: &PeerNetwork) -> Option<Value> {
+    let value: Value = match serde_json::from_slice(body) {
+        Ok(value) => value,
+        Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string()))),
+    };
+
+    match value {
+        Value::Array(calls) => {
+            if calls.is_empty() {
+                return Some(error_response(Value::Null, RpcError::new(INVALID_REQUEST, "Empty batch")));
+            }
+            let responses: Vec<Value> = calls.into_iter()
+                .filter_map(|call| handle_call(call, blockchain, network))
+                .collect();
+            if responses.is_empty() { None } else { Some(Value::Array(responses)) }
+        }
+        call => handle_call(call, blockchain, network),
+    }
+}
+
+/// JSON-RPC 호출 하나를 처리합니다. 알림(id가 없는 호출)이면 None을 반환합니다.
+fn handle_call(call: Value, blockchain: &Mutex<Blockchain>, network: &PeerNetwork) -> Option<Value> {
+    let Value::Object(mut call) = call else {
+        return Some(error_response(Value::Null, RpcError::new(INVALID_REQUEST, "Request must be an object")));
+    };
+    let id = call.remove("id");
+    let is_notification = id.is_none();
+    let id = id.unwrap_or(Value::Null);
+
+    if call.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Some(error_response(id, RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\"")));
+    }
+    let Some(Value::String(method)) = call.remove("method") else {
+        return Some(error_response(id, RpcError::new(INVALID_REQUEST, "method must be a string")));
+    };
+    let params = call.remove("params").unwrap_or(Value::Null);
+
+    let result = call_method(&method, &params, blockchain, network);
+    if is_notification {
+        return None;
+    }
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error_response(id, e),
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": error })
+}
+
+/// 메서드 이름에 따라 요청을 처리합니다.
+fn call_method(method: &str, params: &Value, blockchain: &Mutex<Blockchain>, network: &PeerNetwork) -> Result<Value, RpcError> {
+    match method {
+        "getBlockByIndex" => {
+            let index: u64 = param(params, 0, "index")?;
+            let bc = blockchain.lock().unwrap();
+            to_value(bc.chain.get(index as usize))
+        }
+        "getBlockByHash" => {
+            let hash: String = param(params, 0, "hash")?;
+            let bc = blockchain.lock().unwrap();
+            to_value(bc.get_block_by_hash(&hash))
+        }
+        "getBalance" => {
+            let address: String = param(params, 0, "address")?;
+            let bc = blockchain.lock().unwrap();
+            bc.parse_address(&address)?;
+            to_value(bc.get_balance_of_address(&address))
+        }
+        "sendTransaction" => {
+            let tx: Transaction = param(params, 0, "transaction")?;
+            let txid = tx.txid();
+            blockchain.lock().unwrap().add_transaction(tx.clone())?;
+            network.announce_transaction(&tx); // 다른 노드에 전파
+            to_value(txid)
+        }
+        "getTransaction" => {
+            let txid: String = param(params, 0, "txid")?;
+            let bc = blockchain.lock().unwrap();
+            to_value(bc.get_transaction(&txid))
+        }
+        "getAddressHistory" => {
+            let address: String = param(params, 0, "address")?;
+            let skip: usize = optional_param(params, 1, "skip")?.unwrap_or(0);
+            let limit: u32 = optional_param(params, 2, "limit")?.unwrap_or(protocol::MAX_HISTORY_PER_PAGE);
+            let bc = blockchain.lock().unwrap();
+            bc.parse_address(&address)?;
+            let limit = limit.min(protocol::MAX_HISTORY_PER_PAGE) as usize;
+            let (total, transactions) = bc.get_address_history(&address, skip, limit);
+            Ok(json!({ "total": total, "transactions": transactions }))
+        }
+        "getMempool" => {
+            let bc = blockchain.lock().unwrap();
+            to_value(&bc.pending_transactions)
+        }
+        "getChainInfo" => {
+            let bc = blockchain.lock().unwrap();
+            let tip = bc.chain_tip();
+            Ok(json!({
+                "height": tip.height,
+                "tipHash": tip.tip_hash,
+                "bits": format!("{:08x}", tip.bits),
+                "target": Target::from_compact(tip.bits).map(|target| target.to_string()),
+                // u128은 JSON 숫자로 정확히 표현되지 않을 수 있으므로 문자열로 보냅니다.
+                "cumulativeWork": tip.cumulative_work.to_string(),
+                "chainId": bc.chain_id,
+                "ledgerMode": match bc.ledger_mode {
+                    LedgerMode::Account => "account",
+                    LedgerMode::Utxo => "utxo",
+                },
+                "pendingTransactions": bc.pending_transactions.len(),
+                "peers": network.peer_count(),
+            }))
+        }
+        "getSupply" => {
+            let supply = blockchain.lock().unwrap().supply_info();
+            Ok(json!({
+                "height": supply.height,
+                "totalSupply": supply.total_supply,
+                "maxSupply": supply.max_supply,
+                "nextBlockSubsidy": supply.next_block_subsidy,
+                "nextHalvingHeight": supply.next_halving_height,
+            }))
+        }
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+    }
+}
+
+/// 위치 인자(`[값]`) 또는 이름 인자(`{"이름": 값}`)에서 매개변수 하나를 꺼냅니다.
+fn param<T: serde::de::DeserializeOwned>(params: &Value, position: usize, name: &str) -> Result<T, RpcError> {
+    optional_param(params, position, name)?
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Missing parameter: {}", name)))
+}
+
+/// 생략할 수 있는 매개변수를 꺼냅니다. 없거나 null이면 None을 반환합니다.
+fn optional_param<T: serde::de::DeserializeOwned>(params: &Value, position: usize, name: &str) -> Result<Option<T>, RpcError> {
+    let value = match params {
+        Value::Array(values) => values.get(position),
+        Value::Object(values) => values.get(name),
+        _ => None,
+    };
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid parameter {}: {}", name, e))),
+    }
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Network;
+    use crate::wallet::Wallet;
+
+    async fn read(raw: &str) -> io::Result<Option<HttpRequest>> {
+        read_http_request(&mut BufReader::new(raw.as_bytes())).await
+    }
+
+    #[tokio::test]
+    async fn reads_body_by_content_length() {
+        let raw = "POST / HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}POST";
+        let request = read(raw).await.unwrap().unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.body, b"{}");
+        assert!(request.keep_alive && !request.transfer_encoded);
+    }
+
+    #[tokio::test]
+    async fn flags_transfer_encoded_requests() {
+        let raw = "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n2\r\n{}\r\n0\r\n\r\n";
+        let request = read(raw).await.unwrap().unwrap();
+        assert!(request.transfer_encoded);
+        assert!(!request.keep_alive);
+        assert!(request.body.is_empty());
+    }
+
+    // 허용 출처를 지정해 RPC 서버를 띄우고, 요청 하나를 보내 받은 응답 전체를 반환합니다.
+    async fn exchange(allowed_origin: Option<&str>, origin: Option<&str>) -> String {
+        let blockchain = Arc::new(Mutex::new(Blockchain::new(0x207f_ffff)));
+        let network = PeerNetwork::new(Arc::clone(&blockchain), "127.0.0.1:0".to_string());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, blockchain, network, allowed_origin.map(str::to_string)));
+
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"getChainInfo","params":[]}"#;
+        let origin_header = origin.map_or(String::new(), |origin| format!("Origin: {}\r\n", origin));
+        let request = format!(
+            "POST / HTTP/1.1\r\n{}Content-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            origin_header, body.len(), body
+        );
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        socket.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        socket.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn browser_requests_need_an_allowed_origin() {
+        // 출처가 없는 요청(명령줄 도구, 다른 프로그램)은 처리하고 CORS 헤더를 붙이지 않습니다.
+        let response = exchange(None, None).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(!response.contains("Access-Control-Allow-Origin"));
+
+        let response = exchange(None, Some("http://evil.example")).await;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"));
+        let response = exchange(Some("http://localhost:3000"), Some("http://evil.example")).await;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"));
+
+        let response = exchange(Some("http://localhost:3000"), Some("http://localhost:3000")).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("Access-Control-Allow-Origin: http://localhost:3000\r\n"));
+    }
+
+    fn code_of(e: BlockchainError) -> i64 {
+        RpcError::from(e).code
+    }
+
+    #[test]
+    fn blockchain_errors_map_to_distinct_codes() {
+        assert_eq!(code_of(BlockchainError::MempoolFull("full".to_string())), MEMPOOL_FULL);
+        assert_eq!(code_of(BlockchainError::MalformedEncoding("eof".to_string())), INVALID_PARAMS);
+        assert_eq!(code_of(BlockchainError::InvalidAddress { address: "x".to_string(), reason: "bad".to_string() }), INVALID_PARAMS);
+        assert_eq!(code_of(BlockchainError::WrongNetwork { address: "x".to_string(), chain_id: 2 }), INVALID_PARAMS);
+        assert_eq!(code_of(BlockchainError::InsufficientFunds { available: 1, required: 2 }), UNSPENDABLE_FUNDS);
+        assert_eq!(code_of(BlockchainError::InvalidNonce { expected: 1, actual: 2 }), NONCE_MISMATCH);
+        assert_eq!(code_of(BlockchainError::BadSignature), INVALID_TRANSACTION);
+        assert_eq!(code_of(BlockchainError::EmptyChain), INTERNAL_ERROR);
+    }
+
+    #[test]
+    fn address_parameters_are_validated() {
+        let blockchain = Arc::new(Mutex::new(Blockchain::new(0x207f_ffff)));
+        let network = PeerNetwork::new(Arc::clone(&blockchain), "127.0.0.1:0".to_string());
+        let call = |method: &str, params: Value| call_method(method, &params, &blockchain, &network);
+
+        let mainnet = Wallet::new_for_network(Network::Mainnet).address;
+        let testnet = Wallet::new_for_network(Network::Testnet).address;
+        assert_eq!(call("getBalance", json!([mainnet])).unwrap(), json!(0));
+        for method in ["getBalance", "getAddressHistory"] {
+            for address in [json!("not-an-address"), json!(testnet), json!(mainnet.to_uppercase()), json!(42)] {
+                assert_eq!(call(method, json!([address])).unwrap_err().code, INVALID_PARAMS, "{} {}", method, address);
+            }
+            assert_eq!(call(method, json!([])).unwrap_err().code, INVALID_PARAMS);
+        }
+        assert_eq!(call("getAddressHistory", json!({ "address": mainnet })).unwrap()["total"], json!(0));
+    }
+}
@@ -1,52 +1,68 @@
 // src/server.rs
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
-use serde::{Serialize, Deserialize};
-use serde_json;
+use tokio::sync::{broadcast, mpsc};
+use std::io;
 use std::sync::{Arc, Mutex};
+use log::{debug, error, info, warn};
 
 // 라이브러리 크레이트에서 모듈들을 가져옵니다.
-use crate::{blockchain, transaction, wallet}; 
-use blockchain::Blockchain;
-use transaction::Transaction;
-use wallet::Wallet;
-
-// 클라이언트와 서버 간 메시지 유형 정의
-#[derive(Debug, Serialize, Deserialize)]
-pub enum Request {
-    AddTransaction(Transaction),
-    MineBlock(String), // miner_address
-    GetBalance(String), // address
-    GetChain,
-    GenerateWallet,
-    // 필요에 따라 다른 요청 추가
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub enum Response {
-    Success(String),
-    Blockchain(Blockchain),
-    Balance(u64),
-    Wallet(Wallet),
-    Error(String),
-    // 필요에 따라 다른 응답 추가
-}
+use crate::{blockchain, config, events, logging, miner, p2p, protocol, rpc, storage};
+use blockchain::{Blockchain, LedgerMode};
+use config::NodeConfig;
+use events::ChainEvent;
+use p2p::PeerNetwork;
+use protocol::{Envelope, Notification, Request, Response, Subscription};
+use protocol::{MAX_BLOCKS_PER_PAGE, MAX_HEADERS_PER_PAGE, MAX_HISTORY_PER_PAGE, PROTOCOL_VERSION};
+use storage::FileChainStore;
 
 // #[tokio::main] // 이 매크로를 제거합니다.
 pub async fn run_server() -> Result<(), Box<dyn std::error::Error>> {
-    let listener = TcpListener::bind("127.0.0.1:8080").await?;
-    println!("블록체인 서버가 127.0.0.1:8080에서 실행 중입니다.");
+    let config = NodeConfig::from_args()?;
+    run_node(config).await
+}
+
+/// 주어진 설정으로 노드(클라이언트 서버 + P2P 네트워크 + HTTP JSON-RPC)를 실행합니다.
+pub async fn run_node(config: NodeConfig) -> Result<(), Box<dyn std::error::Error>> {
+    logging::init(config.log_level_filter()?);
+
+    let settings = config.chain_settings()?;
+    let network_name = settings.params.network;
+
+    let listener = TcpListener::bind(&config.listen_addr).await?;
+    info!("블록체인 서버가 {}에서 실행 중입니다. (네트워크: {})", config.listen_addr, network_name);
 
-    let blockchain = Arc::new(Mutex::new(Blockchain::new(2)));
+    // 블록은 데이터 디렉터리의 chain.dat 파일에 저장되며, 재시작 시 다시 불러옵니다.
+    let store = FileChainStore::open(format!("{}/chain.dat", config.data_dir))?;
+    let blockchain = Arc::new(Mutex::new(Blockchain::open(settings, store)?));
+    info!("제네시스 블록: {}", blockchain.lock().unwrap().chain[0].hash);
+
+    // P2P 네트워크를 별도 작업으로 실행합니다.
+    let network = PeerNetwork::new(Arc::clone(&blockchain), config.p2p_listen_addr.clone());
+    let network_clone = Arc::clone(&network);
+    let peers = config.peers.clone();
+    tokio::spawn(async move {
+        if let Err(e) = network_clone.run(peers).await {
+            error!("P2P 네트워크 오류: {:?}", e);
+        }
+    });
+
+    // HTTP JSON-RPC 서버를 별도 작업으로 실행합니다.
+    let rpc_listener = TcpListener::bind(&config.rpc_listen_addr).await?;
+    info!("JSON-RPC 서버가 http://{}에서 실행 중입니다.", config.rpc_listen_addr);
+    tokio::spawn(rpc::serve(rpc_listener, Arc::clone(&blockchain), Arc::clone(&network), config.rpc_allowed_origin.clone()));
+
+    let mining_threads = config.mining_thread_count();
+    info!("채굴 스레드: {}개", mining_threads);
 
     loop {
         let (socket, addr) = listener.accept().await?;
         let blockchain_clone = Arc::clone(&blockchain);
+        let network_clone = Arc::clone(&network);
 
-        println!("새 클라이언트 연결: {}", addr);
+        info!("새 클라이언트 연결: {}", addr);
         tokio::spawn(async move {
-            if let Err(e) = handle_client(socket, blockchain_clone).await {
-                eprintln!("클라이언트 처리 오류: {:?}", e);
+            if let Err(e) = handle_client(socket, blockchain_clone, network_clone, mining_threads).await {
+                warn!("클라이언트 처리 오류: {:?}", e);
             }
         });
     }
@@ -55,81 +71,429 @@ pub async fn run_server() -> Result<(), Box<dyn std::error::Error>> {
 async fn handle_client(
     mut socket: TcpStream,
     blockchain: Arc<Mutex<Blockchain>>,
+    network: Arc<PeerNetwork>,
+    mining_threads: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut buffer = Vec::new();
-    let mut total_bytes_read = 0;
+    while let Some(frame) = protocol::read_frame(&mut socket).await? {
+        let envelope: Envelope<Request> = match serde_json::from_slice(&frame) {
+            Ok(envelope) => envelope,
+            Err(e) => {
+                warn!("요청 역직렬화 오류: {:?}", e);
+                // 식별자를 알 수 없으므로 0으로 응답합니다.
+                let err_resp = Envelope::new(0, Response::Error(format!("Invalid request format: {}", e)));
+                protocol::write_message(&mut socket, &err_resp).await?;
+                continue;
+            }
+        };
 
-    loop {
-        let mut len_bytes = [0u8; 4];
-        if socket.read_exact(&mut len_bytes).await.is_err() {
-            break;
-        }
-        let msg_len = u32::from_be_bytes(len_bytes) as usize;
+        debug!("수신된 요청 #{}: {:?}", envelope.id, envelope.body);
 
-        if msg_len == 0 {
-            break;
-        }
+        let response = if envelope.version != PROTOCOL_VERSION {
+            Response::Error(format!(
+                "지원하지 않는 프로토콜 버전입니다: {} (서버: {})", envelope.version, PROTOCOL_VERSION
+            ))
+        } else if let Request::Subscribe(subscriptions) = envelope.body {
+            // 구독을 등록한 뒤에 응답하므로, 응답 이후의 이벤트는 빠짐없이 전달됩니다.
+            let events = blockchain.lock().unwrap().subscribe_events();
+            let response = Envelope::new(envelope.id, Response::Subscribed(subscriptions.clone()));
+            protocol::write_message(&mut socket, &response).await?;
+            stream_events(socket, envelope.id, subscriptions, events).await?;
+            info!("구독 연결 종료.");
+            return Ok(());
+        } else if let Request::MineBlock(miner_address) = envelope.body {
+            // 채굴은 잠금 없이 채굴 스레드에서 진행되므로, 다른 연결의 요청은 계속 처리됩니다.
+            match miner::mine_block(&blockchain, &miner_address, mining_threads).await {
+                Ok((new_block, stats)) => {
+                    network.announce_block(&new_block); // 다른 노드에 전파
+                    Response::Success(format!(
+                        "새 블록이 채굴되어 블록체인에 추가되었습니다. 채굴자: {} (해시레이트: {:.0} H/s, 스레드 {}개)",
+                        miner_address, stats.hashrate(), stats.threads
+                    ))
+                }
+                Err(e) => Response::Error(format!("블록을 체인에 추가하지 못했습니다: {}", e)),
+            }
+        } else {
+            handle_request(envelope.body, &blockchain, &network)
+        };
+        protocol::write_message(&mut socket, &Envelope::new(envelope.id, response)).await?;
+    }
 
-        buffer.resize(msg_len, 0);
-        if socket.read_exact(&mut buffer).await.is_err() {
-            break;
-        }
+    info!("클라이언트 연결 종료.");
+    Ok(())
+}
 
-        let request: Request = match serde_json::from_slice(&buffer) {
-            Ok(req) => req,
-            Err(e) => {
-                eprintln!("요청 역직렬화 오류: {:?}", e);
-                let err_resp = Response::Error(format!("Invalid request format: {}", e));
-                let resp_json = serde_json::to_vec(&err_resp)?;
-                socket.write_all(&(resp_json.len() as u32).to_be_bytes()).await?;
-                socket.write_all(&resp_json).await?;
-                continue;
+/// 구독 모드: 연결이 끊길 때까지 구독 조건에 맞는 이벤트를 알림으로 보냅니다.
+/// 알림에는 구독 요청의 id가 붙습니다. 구독 모드에서는 다른 요청을 처리하지 않습니다.
+async fn stream_events(
+    socket: TcpStream,
+    subscription_id: u64,
+    subscriptions: Vec<Subscription>,
+    mut events: broadcast::Receiver<ChainEvent>,
+) -> io::Result<()> {
+    let (mut reader, mut writer) = socket.into_split();
+
+    // 프레임 읽기는 중간에 취소되면 안 되므로 별도 작업에서 읽어 채널로 전달합니다.
+    let (frame_sender, mut frames) = mpsc::channel::<()>(1);
+    let reader_task = tokio::spawn(async move {
+        while let Ok(Some(_)) = protocol::read_frame(&mut reader).await {
+            if frame_sender.send(()).await.is_err() {
+                break;
             }
+        }
+    });
+
+    let result = loop {
+        let notifications = tokio::select! {
+            frame = frames.recv() => match frame {
+                Some(()) => vec![Response::Error("구독 중인 연결에서는 다른 요청을 처리하지 않습니다.".to_string())],
+                None => break Ok(()), // 클라이언트가 연결을 닫았습니다.
+            },
+            event = events.recv() => match event {
+                Ok(event) => notifications_for(&event, &subscriptions)
+                    .into_iter()
+                    .map(Response::Notification)
+                    .collect(),
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    vec![Response::Notification(Notification::Lagged { missed })]
+                }
+                Err(broadcast::error::RecvError::Closed) => break Ok(()),
+            },
         };
+        for notification in notifications {
+            if let Err(e) = protocol::write_message(&mut writer, &Envelope::new(subscription_id, notification)).await {
+                reader_task.abort();
+                return Err(e);
+            }
+        }
+    };
+    reader_task.abort();
+    result
+}
 
-        println!("수신된 요청: {:?}", request);
+/// 이벤트 하나에서 구독 조건에 맞는 알림을 만듭니다.
+fn notifications_for(event: &ChainEvent, subscriptions: &[Subscription]) -> Vec<Notification> {
+    let mut notifications = Vec::new();
+    for subscription in subscriptions {
+        match (subscription, event) {
+            (Subscription::NewBlockHeaders, ChainEvent::BlockConnected(block)) => {
+                notifications.push(Notification::NewBlockHeader(block.header()));
+            }
+            (Subscription::PendingTransactions, ChainEvent::TransactionAccepted(tx)) => {
+                notifications.push(Notification::PendingTransaction(tx.as_ref().clone()));
+            }
+            (Subscription::AddressActivity(address), ChainEvent::BlockConnected(block)) => {
+                for tx in block.transactions.iter().filter(|tx| tx.involves(address)) {
+                    notifications.push(Notification::AddressActivity {
+                        address: address.clone(),
+                        block_index: Some(block.index),
+                        transaction: tx.clone(),
+                    });
+                }
+            }
+            (Subscription::AddressActivity(address), ChainEvent::TransactionAccepted(tx)) if tx.involves(address) => {
+                notifications.push(Notification::AddressActivity {
+                    address: address.clone(),
+                    block_index: None,
+                    transaction: tx.as_ref().clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+    notifications
+}
 
-        let response = match request {
-            Request::AddTransaction(tx) => {
-                let mut bc = blockchain.lock().unwrap();
-                if bc.get_balance_of_address(&tx.sender) < tx.amount {
-                    Response::Error(format!("잔액 부족: 송신자 {}의 잔액은 {} 코인입니다. 요청된 금액: {}", 
-                                            tx.sender, bc.get_balance_of_address(&tx.sender), tx.amount))
-                } else {
-                    bc.add_transaction(tx);
+/// 요청 하나를 처리하여 응답을 만듭니다.
+fn handle_request(request: Request, blockchain: &Mutex<Blockchain>, network: &PeerNetwork) -> Response {
+    match request {
+        Request::AddTransaction(tx) => {
+            let result = blockchain.lock().unwrap().add_transaction(tx.clone());
+            match result {
+                Ok(()) => {
+                    network.announce_transaction(&tx); // 다른 노드에 전파
                     Response::Success("트랜잭션이 대기열에 추가되었습니다.".to_string())
                 }
+                Err(e) => Response::Error(format!("트랜잭션이 거부되었습니다: {}", e)),
             }
-            Request::MineBlock(miner_address) => {
-                let mut bc = blockchain.lock().unwrap();
-                let prev_chain_len = bc.chain.len();
-                bc.mine_pending_transactions(miner_address.clone());
-                if bc.chain.len() > prev_chain_len {
-                    Response::Success(format!("새 블록이 채굴되어 블록체인에 추가되었습니다. 채굴자: {}", miner_address))
-                } else {
-                    Response::Success("채굴할 대기 중인 트랜잭션이 없어 새 블록이 생성되지 않았습니다.".to_string())
+        }
+        Request::MineBlock(_) => Response::Error("채굴 요청은 TCP 연결에서만 처리됩니다.".to_string()),
+        Request::GetBalance(address) => {
+            let bc = blockchain.lock().unwrap();
+            let balance = bc.get_balance_of_address(&address);
+            Response::Balance(balance)
+        }
+        Request::GetBlocks { start, limit } => {
+            let bc = blockchain.lock().unwrap();
+            Response::Blocks(bc.get_blocks(start, limit.min(MAX_BLOCKS_PER_PAGE) as usize).to_vec())
+        }
+        Request::GetHeaders { start, limit } => {
+            let bc = blockchain.lock().unwrap();
+            Response::Headers(bc.get_headers(start, limit.min(MAX_HEADERS_PER_PAGE) as usize))
+        }
+        Request::GetBlockByIndex(index) => {
+            let bc = blockchain.lock().unwrap();
+            Response::Block(bc.chain.get(index as usize).cloned())
+        }
+        Request::GetBlockByHash(hash) => {
+            let bc = blockchain.lock().unwrap();
+            Response::Block(bc.get_block_by_hash(&hash).cloned())
+        }
+        Request::GetChainTip => {
+            let bc = blockchain.lock().unwrap();
+            Response::ChainTip(bc.chain_tip())
+        }
+        Request::GetTransaction(txid) => {
+            let bc = blockchain.lock().unwrap();
+            Response::Transaction(bc.get_transaction(&txid))
+        }
+        Request::GetAddressHistory { address, skip, limit } => {
+            let bc = blockchain.lock().unwrap();
+            let limit = limit.min(MAX_HISTORY_PER_PAGE) as usize;
+            let (total, transactions) = bc.get_address_history(&address, skip as usize, limit);
+            Response::AddressHistory { total, transactions }
+        }
+        Request::GetNonce(address) => {
+            let bc = blockchain.lock().unwrap();
+            Response::Nonce { nonce: bc.get_next_nonce(&address), chain_id: bc.chain_id }
+        }
+        Request::GetChainId => {
+            let bc = blockchain.lock().unwrap();
+            Response::ChainId(bc.chain_id)
+        }
+        Request::GetSupply => {
+            let bc = blockchain.lock().unwrap();
+            Response::Supply(bc.supply_info())
+        }
+        Request::GetMerkleProof { block_index, txid } => {
+            let bc = blockchain.lock().unwrap();
+            match (bc.chain.get(block_index as usize), bc.get_merkle_proof(block_index, &txid)) {
+                // 트랜잭션 수는 머클 루트에 커밋되지 않으므로 블록과 맞는지 확인한 뒤에 보냅니다.
+                (Some(block), Some(proof))
+                    if proof.tx_count == block.transactions.len() && proof.verify(&block.merkle_root) =>
+                {
+                    Response::MerkleProof { merkle_root: block.merkle_root.clone(), proof }
                 }
+                _ => Response::Error(format!("블록 {}에서 트랜잭션 {}을(를) 찾을 수 없습니다.", block_index, txid)),
             }
-            Request::GetBalance(address) => {
-                let bc = blockchain.lock().unwrap();
-                let balance = bc.get_balance_of_address(&address);
-                Response::Balance(balance)
+        }
+        // 구독은 연결 상태를 바꾸므로 handle_client에서 처리합니다.
+        Request::Subscribe(_) => Response::Error("구독은 TCP 연결에서만 사용할 수 있습니다.".to_string()),
+        Request::GetUtxos(address) => {
+            let bc = blockchain.lock().unwrap();
+            if bc.ledger_mode == LedgerMode::Utxo {
+                Response::Utxos(bc.utxo_set().outputs_of(&address))
+            } else {
+                Response::Error("서버가 UTXO 모드로 실행 중이 아닙니다.".to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Network;
+    use crate::block::Block;
+    use crate::blockchain::ChainSettings;
+    use crate::chain_params::ChainParams;
+    use crate::transaction::Transaction;
+    use crate::wallet::Wallet;
+    use std::time::Duration;
+    use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+
+    // 채굴 보상으로 기록이 쌓인 주소와, 한 페이지보다 긴 regtest 체인
+    fn long_chain(name: &str, blocks: usize) -> (Arc<Mutex<Blockchain>>, Arc<PeerNetwork>, Wallet) {
+        let path = std::env::temp_dir().join(format!("bingry-server-{}-{}.dat", name, std::process::id()));
+        let store = FileChainStore::open(&path).unwrap();
+        let settings = ChainSettings { params: ChainParams::regtest(), ..Default::default() };
+        let mut chain = Blockchain::open(settings, store).unwrap();
+        std::fs::remove_file(&path).unwrap(); // 열어 둔 파일에는 테스트가 끝날 때까지 계속 쓸 수 있습니다.
+        let miner = Wallet::new_for_network(Network::Regtest);
+        for _ in 0..blocks {
+            chain.mine_pending_transactions(miner.address.clone()).unwrap();
+        }
+        let chain = Arc::new(Mutex::new(chain));
+        let network = PeerNetwork::new(Arc::clone(&chain), "127.0.0.1:0".to_string());
+        (chain, network, miner)
+    }
+
+    #[test]
+    fn pages_are_clamped_to_their_limits() {
+        let (chain, network, miner) = long_chain("pages", MAX_BLOCKS_PER_PAGE as usize + 5);
+        let height = chain.lock().unwrap().chain.len();
+
+        match handle_request(Request::GetBlocks { start: 0, limit: u32::MAX }, &chain, &network) {
+            Response::Blocks(blocks) => {
+                assert_eq!(blocks.len(), MAX_BLOCKS_PER_PAGE as usize);
+                assert_eq!(blocks[0].index, 0);
             }
-            Request::GetChain => {
-                let bc = blockchain.lock().unwrap();
-                Response::Blockchain(bc.clone())
+            response => panic!("unexpected response {:?}", response),
+        }
+        match handle_request(Request::GetBlocks { start: 100, limit: 3 }, &chain, &network) {
+            Response::Blocks(blocks) => assert_eq!(blocks.iter().map(|b| b.index).collect::<Vec<_>>(), [100, 101, 102]),
+            response => panic!("unexpected response {:?}", response),
+        }
+        // 헤더 페이지는 더 크므로 체인 전체가 한 번에 옵니다.
+        match handle_request(Request::GetHeaders { start: 0, limit: u32::MAX }, &chain, &network) {
+            Response::Headers(headers) => assert_eq!(headers.len(), height.min(MAX_HEADERS_PER_PAGE as usize)),
+            response => panic!("unexpected response {:?}", response),
+        }
+        match handle_request(Request::GetHeaders { start: height as u64, limit: 10 }, &chain, &network) {
+            Response::Headers(headers) => assert!(headers.is_empty()),
+            response => panic!("unexpected response {:?}", response),
+        }
+
+        let history = Request::GetAddressHistory { address: miner.address.clone(), skip: 0, limit: u32::MAX };
+        match handle_request(history, &chain, &network) {
+            Response::AddressHistory { total, transactions } => {
+                assert_eq!(total, height - 1);
+                assert_eq!(transactions.len(), MAX_HISTORY_PER_PAGE as usize);
             }
-            Request::GenerateWallet => {
-                let wallet = Wallet::new();
-                Response::Wallet(wallet)
+            response => panic!("unexpected response {:?}", response),
+        }
+        let last_page = Request::GetAddressHistory { address: miner.address, skip: MAX_HISTORY_PER_PAGE, limit: u32::MAX };
+        match handle_request(last_page, &chain, &network) {
+            Response::AddressHistory { total, transactions } => {
+                assert_eq!(transactions.len(), total - MAX_HISTORY_PER_PAGE as usize);
             }
+            response => panic!("unexpected response {:?}", response),
+        }
+    }
+
+    #[test]
+    fn served_merkle_proofs_match_the_block() {
+        let (chain, network, miner) = long_chain("proof", 1);
+        let txids = {
+            let mut bc = chain.lock().unwrap();
+            let mut tx = Transaction::new(miner.address.clone(), Wallet::new_for_network(Network::Regtest).address, 10);
+            tx.nonce = bc.get_next_nonce(&miner.address);
+            tx.chain_id = bc.chain_id;
+            tx.sign(&miner.to_signing_key().unwrap(), miner.public_key_hex.clone());
+            bc.add_transaction(tx).unwrap();
+            bc.mine_pending_transactions(miner.address.clone()).unwrap();
+            bc.chain[2].transactions.iter().map(|tx| tx.txid()).collect::<Vec<_>>()
         };
+        assert_eq!(txids.len(), 2);
 
-        let resp_json = serde_json::to_vec(&response)?;
-        socket.write_all(&(resp_json.len() as u32).to_be_bytes()).await?;
-        socket.write_all(&resp_json).await?;
+        for (position, txid) in txids.iter().enumerate() {
+            match handle_request(Request::GetMerkleProof { block_index: 2, txid: txid.clone() }, &chain, &network) {
+                Response::MerkleProof { merkle_root, proof } => {
+                    assert_eq!((proof.tx_index, proof.tx_count), (position, 2));
+                    assert_eq!(merkle_root, chain.lock().unwrap().chain[2].merkle_root);
+                    assert!(proof.verify(&merkle_root));
+                }
+                response => panic!("unexpected response {:?}", response),
+            }
+        }
+        // 다른 블록의 트랜잭션에 대한 증명은 만들지 않습니다.
+        let request = Request::GetMerkleProof { block_index: 1, txid: txids[1].clone() };
+        assert!(matches!(handle_request(request, &chain, &network), Response::Error(_)));
     }
 
-    println!("클라이언트 연결 종료.");
-    Ok(())
+    // alice가 bob에게 보내는 트랜잭션과, 그 트랜잭션 및 miner의 보상을 담은 블록
+    fn sample_events() -> (ChainEvent, ChainEvent) {
+        let transfer = Transaction::new("alice".to_string(), "bob".to_string(), 5);
+        let coinbase = Transaction::new_coinbase("miner".to_string(), 100, 1);
+        let block = Block::new(1, "00".repeat(32), vec![coinbase, transfer.clone()]);
+        (ChainEvent::TransactionAccepted(Arc::new(transfer)), ChainEvent::BlockConnected(Arc::new(block)))
+    }
+
+    #[test]
+    fn each_subscription_gets_its_own_notifications() {
+        let (accepted, connected) = sample_events();
+
+        let headers = [Subscription::NewBlockHeaders];
+        assert!(notifications_for(&accepted, &headers).is_empty());
+        match notifications_for(&connected, &headers).as_slice() {
+            [Notification::NewBlockHeader(header)] => assert_eq!((header.index, header.transaction_count), (1, 2)),
+            notifications => panic!("unexpected notifications {:?}", notifications),
+        }
+
+        let pending = [Subscription::PendingTransactions];
+        assert!(notifications_for(&connected, &pending).is_empty());
+        match notifications_for(&accepted, &pending).as_slice() {
+            [Notification::PendingTransaction(tx)] => assert_eq!(tx.recipient, "bob"),
+            notifications => panic!("unexpected notifications {:?}", notifications),
+        }
+
+        // 주소 활동은 대기열 추가와 블록 포함을 모두 알리며, 관련 없는 주소는 알리지 않습니다.
+        let bob = [Subscription::AddressActivity("bob".to_string())];
+        match notifications_for(&accepted, &bob).as_slice() {
+            [Notification::AddressActivity { address, block_index: None, .. }] => assert_eq!(address, "bob"),
+            notifications => panic!("unexpected notifications {:?}", notifications),
+        }
+        match notifications_for(&connected, &bob).as_slice() {
+            [Notification::AddressActivity { block_index: Some(1), transaction, .. }] => assert_eq!(transaction.sender, "alice"),
+            notifications => panic!("unexpected notifications {:?}", notifications),
+        }
+        let miner = [Subscription::AddressActivity("miner".to_string())];
+        assert!(notifications_for(&accepted, &miner).is_empty());
+        assert_eq!(notifications_for(&connected, &miner).len(), 1);
+        assert!(notifications_for(&connected, &[Subscription::AddressActivity("carol".to_string())]).is_empty());
+
+        // 여러 구독은 각각 알림을 만듭니다.
+        let all = [Subscription::NewBlockHeaders, Subscription::PendingTransactions, bob[0].clone(), miner[0].clone()];
+        assert_eq!(notifications_for(&connected, &all).len(), 3);
+        assert_eq!(notifications_for(&accepted, &all).len(), 2);
+    }
+
+    // 구독 모드로 바뀐 서버 쪽 연결과 클라이언트 쪽 연결
+    async fn subscribed_connection(
+        subscriptions: Vec<Subscription>,
+        events: broadcast::Receiver<ChainEvent>,
+    ) -> (OwnedReadHalf, OwnedWriteHalf, tokio::task::JoinHandle<io::Result<()>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        let streaming = tokio::spawn(stream_events(server, 7, subscriptions, events));
+        let (reader, writer) = client.into_split();
+        (reader, writer, streaming)
+    }
+
+    async fn next_response(reader: &mut OwnedReadHalf) -> Response {
+        let envelope: Envelope<Response> = tokio::time::timeout(Duration::from_secs(10), protocol::read_message(reader))
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(envelope.id, 7);
+        envelope.body
+    }
+
+    #[tokio::test]
+    async fn streamed_events_are_filtered_and_lag_is_reported() {
+        let (accepted, connected) = sample_events();
+        let (sender, events) = broadcast::channel(2);
+        // 수신자가 읽기 전에 용량보다 많은 이벤트가 쌓이면 놓친 수를 먼저 알립니다.
+        for _ in 0..3 {
+            sender.send(connected.clone()).unwrap();
+        }
+        let (mut reader, mut writer, streaming) = subscribed_connection(vec![Subscription::NewBlockHeaders], events).await;
+        assert!(matches!(next_response(&mut reader).await, Response::Notification(Notification::Lagged { missed: 1 })));
+        for _ in 0..2 {
+            assert!(matches!(next_response(&mut reader).await, Response::Notification(Notification::NewBlockHeader(_))));
+        }
+
+        // 구독하지 않은 이벤트는 건너뜁니다.
+        sender.send(accepted).unwrap();
+        sender.send(connected).unwrap();
+        assert!(matches!(next_response(&mut reader).await, Response::Notification(Notification::NewBlockHeader(_))));
+
+        // 구독 중에는 다른 요청을 처리하지 않습니다.
+        protocol::write_message(&mut writer, &Envelope::new(8, Request::GetChainTip)).await.unwrap();
+        assert!(matches!(next_response(&mut reader).await, Response::Error(_)));
+
+        // 클라이언트가 연결을 닫으면 구독을 끝냅니다.
+        drop(writer);
+        streaming.await.unwrap().unwrap();
+        drop(sender);
+    }
+
+    #[tokio::test]
+    async fn stream_ends_when_the_event_bus_closes() {
+        let (sender, events) = broadcast::channel(2);
+        let (_reader, _writer, streaming) = subscribed_connection(vec![Subscription::PendingTransactions], events).await;
+        drop(sender);
+        tokio::time::timeout(Duration::from_secs(10), streaming).await.unwrap().unwrap().unwrap();
+    }
 }
@@ -0,0 +1,284 @@
+// src/storage.rs
+use super::block::Block; // block 모듈의 Block 구조체 사용
+use sha2::{Digest, Sha256};
+use std::fmt::Debug;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use log::warn;
+
+/// 블록을 영구 저장소에 보관하기 위한 추상화입니다.
+/// 블록은 체인 순서대로 추가만 되며(append-only), 시작 시 전체를 다시 읽어 들입니다.
+pub trait ChainStore: Send + Debug {
+    /// 저장된 모든 블록을 체인 순서대로 읽어옵니다.
+    fn load_blocks(&mut self) -> io::Result<Vec<Block>>;
+
+    /// 새 블록을 저장소 끝에 추가합니다.
+    /// 이 함수가 `Ok`를 반환하면 블록은 디스크에 기록된 상태여야 합니다.
+    fn append_block(&mut self, block: &Block) -> io::Result<()>;
+
+    /// 인덱스가 `height` 이상인 블록을 모두 제거합니다. (체인 재구성 시 사용)
+    fn truncate_blocks(&mut self, height: u64) -> io::Result<()>;
+}
+
+// 레코드 헤더: 페이로드 길이(u32, big-endian) + 페이로드의 SHA-256 체크섬(32바이트)
+const RECORD_LEN_SIZE: usize = 4;
+const RECORD_CHECKSUM_SIZE: usize = 32;
+const RECORD_HEADER_SIZE: usize = RECORD_LEN_SIZE + RECORD_CHECKSUM_SIZE;
+
+/// 파일 기반의 append-only 블록 저장소입니다.
+///
+/// 각 블록은 `[길이][체크섬][블록 인코딩]` 형태의 레코드로 기록됩니다. (블록 인코딩은 encoding 모듈 참고)
+/// 쓰기 도중 프로세스가 종료되어 마지막 레코드가 잘리거나 손상된 경우,
+/// 다음 로드 시 마지막으로 온전한 레코드 뒤를 잘라내어 복구합니다.
+/// 체크섬이 맞지 않는 레코드 뒤에 데이터가 더 있거나(쓰기 중단이 아닌 파일 손상),
+/// 체크섬은 맞지만 블록으로 디코딩할 수 없는 레코드가 있으면 파일을 건드리지 않고 `InvalidData` 오류를 반환합니다.
+#[derive(Debug)]
+pub struct FileChainStore {
+    path: PathBuf,
+    file: File,
+    // 각 블록 레코드가 시작하는 파일 위치 (블록 인덱스 순서)
+    offsets: Vec<u64>,
+}
+
+impl FileChainStore {
+    /// 주어진 경로의 저장소 파일을 열거나 새로 생성합니다.
+    /// 상위 디렉터리가 없으면 함께 생성합니다.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        Ok(FileChainStore { path, file, offsets: Vec::new() })
+    }
+
+    /// 저장소 파일 경로를 반환합니다.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn checksum(payload: &[u8]) -> [u8; RECORD_CHECKSUM_SIZE] {
+        Sha256::digest(payload).into()
+    }
+}
+
+impl ChainStore for FileChainStore {
+    fn load_blocks(&mut self) -> io::Result<Vec<Block>> {
+        let mut data = Vec::new();
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.read_to_end(&mut data)?;
+
+        let mut blocks = Vec::new();
+        let mut offset = 0usize;
+        self.offsets.clear();
+
+        while offset < data.len() {
+            // 헤더가 온전하지 않으면 쓰기 도중 중단된 레코드입니다.
+            if data.len() - offset < RECORD_HEADER_SIZE {
+                break;
+            }
+            let mut len_bytes = [0u8; RECORD_LEN_SIZE];
+            len_bytes.copy_from_slice(&data[offset..offset + RECORD_LEN_SIZE]);
+            let payload_len = u32::from_be_bytes(len_bytes) as usize;
+            let checksum = &data[offset + RECORD_LEN_SIZE..offset + RECORD_HEADER_SIZE];
+
+            let payload_start = offset + RECORD_HEADER_SIZE;
+            if data.len() - payload_start < payload_len {
+                break;
+            }
+            let payload = &data[payload_start..payload_start + payload_len];
+            let record_end = payload_start + payload_len;
+            if Self::checksum(payload) != checksum {
+                // 쓰기 중단으로 손상되는 것은 마지막 레코드뿐입니다. 뒤에 레코드가 더 있으면
+                // 잘라낼 때 온전한 블록까지 잃으므로 오류를 반환합니다.
+                if record_end < data.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Checksum mismatch in block record at offset {} in {}, followed by {} more bytes",
+                            offset, self.path.display(), data.len() - record_end
+                        ),
+                    ));
+                }
+                break;
+            }
+            // 체크섬이 맞는데 디코딩할 수 없는 레코드는 잘린 쓰기가 아니라 다른 형식(이전 버전 등)의 데이터이므로,
+            // 잘라내지 않고 오류를 반환합니다.
+            let block = Block::decode(payload).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Cannot decode block record at offset {} in {}: {}", offset, self.path.display(), e),
+                )
+            })?;
+
+            blocks.push(block);
+            self.offsets.push(offset as u64);
+            offset = record_end;
+        }
+
+        // 손상되었거나 잘린 꼬리 부분을 제거하여 이후 추가되는 레코드가 올바른 위치에 기록되도록 합니다.
+        if offset < data.len() {
+            warn!(
+                "Truncating {} trailing bytes of incomplete record in {}",
+                data.len() - offset,
+                self.path.display()
+            );
+            self.file.set_len(offset as u64)?;
+            self.file.sync_all()?;
+        }
+
+        Ok(blocks)
+    }
+
+    fn append_block(&mut self, block: &Block) -> io::Result<()> {
+        let payload = block.encode();
+        let payload_len = u32::try_from(payload.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Block is too large to store"))?;
+
+        // 레코드를 하나의 버퍼로 만들어 한 번에 기록합니다.
+        let mut record = Vec::with_capacity(RECORD_HEADER_SIZE + payload.len());
+        record.extend_from_slice(&payload_len.to_be_bytes());
+        record.extend_from_slice(&Self::checksum(&payload));
+        record.extend_from_slice(&payload);
+
+        let offset = self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(&record)?;
+        self.file.sync_data()?; // 디스크에 기록될 때까지 대기
+        self.offsets.push(offset);
+        Ok(())
+    }
+
+    fn truncate_blocks(&mut self, height: u64) -> io::Result<()> {
+        let height = height as usize;
+        if height >= self.offsets.len() {
+            return Ok(());
+        }
+        // 잘라낸 뒤 프로세스가 종료되어도 남은 레코드는 온전한 체인 앞부분입니다.
+        self.file.set_len(self.offsets[height])?;
+        self.file.sync_all()?;
+        self.offsets.truncate(height);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::Transaction;
+
+    // 테스트마다 겹치지 않는 임시 파일 경로
+    fn temp_path(name: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        std::env::temp_dir().join(format!("bingry-storage-{}-{}-{}.dat", name, std::process::id(), nanos))
+    }
+
+    fn test_block(index: u64) -> Block {
+        let coinbase = Transaction::new_coinbase("bg1miner".to_string(), 100, index);
+        let mut block = Block::new(index, "00".repeat(32), vec![coinbase]);
+        block.hash = block.calculate_hash();
+        block
+    }
+
+    fn write_record(path: &Path, payload: &[u8]) {
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(&(payload.len() as u32).to_be_bytes()).unwrap();
+        file.write_all(&FileChainStore::checksum(payload)).unwrap();
+        file.write_all(payload).unwrap();
+    }
+
+    #[test]
+    fn blocks_survive_reopen() {
+        let path = temp_path("reopen");
+        let mut store = FileChainStore::open(&path).unwrap();
+        store.append_block(&test_block(0)).unwrap();
+        store.append_block(&test_block(1)).unwrap();
+
+        let blocks = FileChainStore::open(&path).unwrap().load_blocks().unwrap();
+        assert_eq!(blocks.iter().map(|block| block.hash.clone()).collect::<Vec<_>>(),
+                   vec![test_block(0).hash, test_block(1).hash]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn torn_tail_is_truncated() {
+        let path = temp_path("torn");
+        let mut store = FileChainStore::open(&path).unwrap();
+        store.append_block(&test_block(0)).unwrap();
+        let intact_len = fs::metadata(&path).unwrap().len();
+        // 헤더만 기록되고 페이로드가 잘린 레코드
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0, 0, 1, 0]).unwrap();
+        file.write_all(&[0u8; RECORD_CHECKSUM_SIZE]).unwrap();
+        drop(file);
+
+        let blocks = FileChainStore::open(&path).unwrap().load_blocks().unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(fs::metadata(&path).unwrap().len(), intact_len);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn undecodable_record_is_an_error_and_keeps_the_file() {
+        let path = temp_path("undecodable");
+        let mut store = FileChainStore::open(&path).unwrap();
+        store.append_block(&test_block(0)).unwrap();
+        // 체크섬은 맞지만 현재 블록 인코딩이 아닌 레코드 (이전 버전의 JSON 페이로드)
+        write_record(&path, br#"{"index":1}"#);
+        let file_len = fs::metadata(&path).unwrap().len();
+
+        let error = FileChainStore::open(&path).unwrap().load_blocks().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::metadata(&path).unwrap().len(), file_len);
+        fs::remove_file(&path).unwrap();
+    }
+
+    // `index`번째 레코드의 페이로드 첫 바이트를 뒤집습니다.
+    fn corrupt_record(path: &Path, store: &FileChainStore, index: usize) {
+        let mut data = fs::read(path).unwrap();
+        data[store.offsets[index] as usize + RECORD_HEADER_SIZE] ^= 0xff;
+        fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn corrupt_last_record_is_truncated() {
+        let path = temp_path("corrupt-tail");
+        let mut store = FileChainStore::open(&path).unwrap();
+        for index in 0..3 {
+            store.append_block(&test_block(index)).unwrap();
+        }
+        let intact_len = store.offsets[2];
+        corrupt_record(&path, &store, 2);
+
+        let mut reopened = FileChainStore::open(&path).unwrap();
+        assert_eq!(reopened.load_blocks().unwrap().len(), 2);
+        assert_eq!(fs::metadata(&path).unwrap().len(), intact_len);
+        // 잘라낸 위치에 다음 블록이 기록됩니다.
+        reopened.append_block(&test_block(2)).unwrap();
+        assert_eq!(FileChainStore::open(&path).unwrap().load_blocks().unwrap().len(), 3);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn corrupt_record_before_intact_ones_is_an_error() {
+        let path = temp_path("corrupt-middle");
+        let mut store = FileChainStore::open(&path).unwrap();
+        for index in 0..3 {
+            store.append_block(&test_block(index)).unwrap();
+        }
+        corrupt_record(&path, &store, 1);
+        let file_len = fs::metadata(&path).unwrap().len();
+
+        let error = FileChainStore::open(&path).unwrap().load_blocks().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::metadata(&path).unwrap().len(), file_len);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,21 +1,54 @@
 // src/transaction.rs
 use chrono::Utc;
 use k256::ecdsa::{SigningKey, VerifyingKey, Signature};
-use k256::elliptic_curve::sec1::ToEncodedPoint;
 use hex::{encode, decode};
 use ecdsa::signature::hazmat::{PrehashSigner, PrehashVerifier};
 use generic_array::{typenum::U64, typenum::Unsigned};
 use core::convert::TryFrom;
 use serde::{Serialize, Deserialize}; // Serialize, Deserialize 트레이트 추가
+use super::error::BlockchainError; // error 모듈의 오류 유형 사용
+use super::address::{Address, Network}; // address 모듈의 주소 유형 사용
+use super::encoding::{Decoder, Encoder}; // encoding 모듈의 정규 바이너리 인코딩 사용
+
+/// 이전 트랜잭션의 특정 출력을 가리키는 참조입니다. (UTXO 모드의 입력)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OutPoint {
+    pub txid: String, // 출력을 만든 트랜잭션의 해시 (calculate_hash_for_signing)
+    pub index: u32,   // 해당 트랜잭션 내 출력 위치
+}
+
+/// 트랜잭션이 만들어내는 출력입니다. (UTXO 모드)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxOutput {
+    pub recipient: String, // 출력을 받는 주소
+    pub amount: u64,       // 금액
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)] // Serialize, Deserialize 추가
 pub struct Transaction {
-    pub sender: String,    // 송신자 주소 (공개 키의 16진수 표현)
-    pub recipient: String, // 수신자 주소 (공개 키의 16진수 표현)
+    pub sender: String,    // 송신자 주소 (공개 키 해시의 Bech32m 표현, address 모듈 참고)
+    pub recipient: String, // 수신자 주소
     pub amount: u64,       // 금액
+    // 채굴자에게 지불하는 수수료. 송신자는 amount와 fee를 함께 부담합니다.
+    #[serde(default)]
+    pub fee: u64,
     pub timestamp: i64,
     pub public_key: String, // 송신자의 공개 키 (16진수 표현)
     pub signature: String,  // 트랜잭션 서명 (16진수 표현)
+    // 송신자 계정의 트랜잭션 순번. 같은 트랜잭션의 재전송(replay)을 막습니다.
+    #[serde(default)]
+    pub nonce: u64,
+    // 트랜잭션이 유효한 네트워크 식별자. 다른 네트워크에서의 재사용을 막습니다.
+    #[serde(default)]
+    pub chain_id: u64,
+    // UTXO 모드에서 사용하는 입력/출력. 계정 모드 트랜잭션에서는 비어 있습니다.
+    #[serde(default)]
+    pub inputs: Vec<OutPoint>,
+    #[serde(default)]
+    pub outputs: Vec<TxOutput>,
+    // 코인베이스 트랜잭션이 포함된 블록 높이. 같은 채굴자의 코인베이스끼리 해시가 겹치지 않게 합니다.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coinbase_height: Option<u64>,
 }
 
 impl Transaction {
@@ -25,20 +58,154 @@ impl Transaction {
             sender,
             recipient,
             amount,
+            fee: 0,
             timestamp: Utc::now().timestamp(),
             public_key: String::new(), // 서명 시 설정
             signature: String::new(),   // 서명 시 설정
+            nonce: 0,
+            chain_id: 0,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            coinbase_height: None,
         }
     }
 
+    /// UTXO 모드 트랜잭션을 생성합니다. (서명 전)
+    /// `inputs`: 송신자가 소유한, 소비할 이전 출력들
+    /// `outputs`: 새로 만들 출력들 (거스름돈 출력 포함)
+    /// `amount`는 `recipient`에게 지불되는 출력 금액의 합으로 설정됩니다.
+    pub fn new_utxo(sender: String, recipient: String, inputs: Vec<OutPoint>, outputs: Vec<TxOutput>) -> Self {
+        let amount = outputs.iter()
+            .filter(|output| output.recipient == recipient)
+            .map(|output| output.amount)
+            .sum();
+        let mut tx = Transaction::new(sender, recipient, amount);
+        tx.inputs = inputs;
+        tx.outputs = outputs;
+        tx
+    }
+
+    /// 채굴 보상(코인베이스) 트랜잭션을 생성합니다.
+    /// 코인베이스 트랜잭션은 서명하지 않으며, 서명 없음 표시를 미리 설정합니다.
+    /// `height`: 이 트랜잭션이 포함될 블록의 인덱스
+    pub fn new_coinbase(miner_address: String, amount: u64, height: u64) -> Self {
+        let mut tx = Transaction::new("coinbase_reward".to_string(), miner_address.clone(), amount);
+        tx.coinbase_height = Some(height);
+        tx.public_key = miner_address; // 채굴자 주소를 public_key로 사용 (실제 키는 아님)
+        tx.signature = "UNSIGNED_COINBASE_TX".to_string();
+        tx
+    }
+
     /// 트랜잭션 서명에 사용될 해시 데이터를 계산합니다.
     /// 이 해시는 트랜잭션의 고유한 식별자이자 서명의 대상이 됩니다.
     pub fn calculate_hash_for_signing(&self) -> String {
-        let data = format!(
-            "{}{}{}{}",
-            self.sender, self.recipient, self.amount, self.timestamp
-        );
-        sha256::digest(data) // sha256 크레이트의 digest 함수 사용
+        sha256::digest(self.signing_bytes()) // sha256 크레이트의 digest 함수 사용
+    }
+
+    /// 서명 대상 필드(공개 키와 서명을 제외한 모든 필드)의 정규 인코딩 (encoding 모듈 참고)
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::versioned();
+        self.encode_signed_fields(&mut encoder);
+        encoder.into_bytes()
+    }
+
+    /// 트랜잭션 전체의 정규 인코딩. P2P 전송과 블록 저장에 사용합니다.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = Encoder::versioned();
+        self.encode_signed_fields(&mut encoder);
+        encoder.str(&self.public_key).str(&self.signature);
+        encoder.into_bytes()
+    }
+
+    /// `encode`로 만든 바이트열에서 트랜잭션을 읽습니다.
+    pub fn decode(bytes: &[u8]) -> Result<Self, BlockchainError> {
+        let mut decoder = Decoder::new(bytes);
+        let tx = Self::decode_from(&mut decoder)?;
+        decoder.finish()?;
+        Ok(tx)
+    }
+
+    fn encode_signed_fields(&self, encoder: &mut Encoder) {
+        encoder.str(&self.sender).str(&self.recipient)
+            .u64(self.amount).u64(self.fee).i64(self.timestamp).u64(self.nonce).u64(self.chain_id);
+        // UTXO 입력/출력도 서명 대상에 포함합니다.
+        encoder.count(self.inputs.len());
+        for input in self.inputs.iter() {
+            encoder.str(&input.txid).u32(input.index);
+        }
+        encoder.count(self.outputs.len());
+        for output in self.outputs.iter() {
+            encoder.str(&output.recipient).u64(output.amount);
+        }
+        encoder.option_u64(self.coinbase_height);
+    }
+
+    fn decode_from(decoder: &mut Decoder) -> Result<Self, BlockchainError> {
+        decoder.version()?;
+        let sender = decoder.str()?;
+        let recipient = decoder.str()?;
+        let amount = decoder.u64()?;
+        let fee = decoder.u64()?;
+        let timestamp = decoder.i64()?;
+        let nonce = decoder.u64()?;
+        let chain_id = decoder.u64()?;
+        let mut inputs = Vec::new();
+        for _ in 0..decoder.count()? {
+            inputs.push(OutPoint { txid: decoder.str()?, index: decoder.u32()? });
+        }
+        let mut outputs = Vec::new();
+        for _ in 0..decoder.count()? {
+            outputs.push(TxOutput { recipient: decoder.str()?, amount: decoder.u64()? });
+        }
+        let coinbase_height = decoder.option_u64()?;
+        let public_key = decoder.str()?;
+        let signature = decoder.str()?;
+        Ok(Transaction {
+            sender, recipient, amount, fee, timestamp, public_key, signature, nonce, chain_id,
+            inputs, outputs, coinbase_height,
+        })
+    }
+
+    /// 트랜잭션 ID를 반환합니다. UTXO 입력은 이 값으로 이전 트랜잭션을 참조합니다.
+    pub fn txid(&self) -> String {
+        self.calculate_hash_for_signing()
+    }
+
+    /// 송신자가 부담하는 총 금액(금액 + 수수료)을 반환합니다.
+    pub fn total_cost(&self) -> u64 {
+        self.amount.saturating_add(self.fee)
+    }
+
+    /// 인코딩된 트랜잭션의 크기(바이트)를 반환합니다. 블록 크기 제한과 수수료율 계산에 사용됩니다.
+    pub fn size(&self) -> usize {
+        self.encode().len()
+    }
+
+    /// 채굴 보상(코인베이스) 트랜잭션인지 확인합니다.
+    pub fn is_coinbase(&self) -> bool {
+        self.sender == "coinbase_reward"
+    }
+
+    /// UTXO 모드 트랜잭션(입력을 가진 트랜잭션)인지 확인합니다.
+    pub fn is_utxo(&self) -> bool {
+        !self.inputs.is_empty()
+    }
+
+    /// 주소가 이 트랜잭션의 송신자, 수신자 또는 출력 수신자인지 확인합니다.
+    pub fn involves(&self, address: &str) -> bool {
+        self.sender == address
+            || self.recipient == address
+            || self.outputs.iter().any(|output| output.recipient == address)
+    }
+
+    /// 이 트랜잭션이 만들어내는 출력 목록을 반환합니다.
+    /// 출력이 명시되지 않은 트랜잭션(코인베이스, 계정 모드)은 수신자에게 가는 단일 출력으로 간주합니다.
+    pub fn effective_outputs(&self) -> Vec<TxOutput> {
+        if self.outputs.is_empty() {
+            vec![TxOutput { recipient: self.recipient.clone(), amount: self.amount }]
+        } else {
+            self.outputs.clone()
+        }
     }
 
     /// 트랜잭션에 서명합니다.
@@ -64,90 +231,138 @@ impl Transaction {
         self.public_key = public_key_hex; // 송신자의 공개 키 저장
     }
 
-    /// 트랜잭션의 유효성을 검사합니다.
+    /// 트랜잭션의 유효성을 검사합니다. 유효하지 않으면 거부 이유를 반환합니다.
     /// - 해시가 올바른지 (이 함수에서는 서명 검증이 주 목적이므로 해시는 별도로 계산하지 않음)
     /// - 송신자, 수신자, 금액이 유효한지
+    /// - 주소의 형식과 체크섬이 올바르고 트랜잭션의 네트워크(chain_id)용인지 (코인베이스는 형식만 확인)
+    /// - 공개 키의 해시가 송신자 주소와 일치하는지
     /// - 서명이 올바른지 (코인베이스 보상 트랜잭션 제외)
-    pub fn is_valid(&self) -> bool {
+    pub fn validate(&self) -> Result<(), BlockchainError> {
         // 코인베이스 보상 트랜잭션은 특별히 처리 (서명 검증 없음)
-        if self.sender == "coinbase_reward" {
-            return self.signature == "UNSIGNED_COINBASE_TX" && !self.recipient.is_empty() && self.amount > 0;
+        if self.is_coinbase() {
+            if self.signature != "UNSIGNED_COINBASE_TX" {
+                return Err(BlockchainError::InvalidCoinbase("coinbase must not carry a signature".to_string()));
+            }
+            // 발행 한도에 도달한 뒤 수수료가 없는 블록의 코인베이스는 금액이 0일 수 있습니다.
+            if self.recipient.is_empty() {
+                return Err(BlockchainError::EmptyAddress);
+            }
+            if self.fee != 0 || !self.inputs.is_empty() || !self.outputs.is_empty() {
+                return Err(BlockchainError::InvalidCoinbase("coinbase cannot have fee, inputs or outputs".to_string()));
+            }
+            // 코인베이스에는 네트워크 식별자가 없으므로 채굴자 주소의 네트워크는 블록 검증에서 확인합니다.
+            self.recipient.parse::<Address>()?;
+            return Ok(());
         }
 
         // 일반 트랜잭션 유효성 검사
+        if self.coinbase_height.is_some() {
+            return Err(BlockchainError::InvalidCoinbase("only coinbase transactions carry a block height".to_string()));
+        }
         if self.public_key.is_empty() || self.signature.is_empty() {
-            println!("Error: Transaction is not signed or public key is missing.");
-            return false;
+            return Err(BlockchainError::Unsigned);
         }
-        if self.amount <= 0 {
-            println!("Error: Transaction amount must be positive.");
-            return false;
+        if self.amount == 0 {
+            return Err(BlockchainError::ZeroAmount);
         }
         if self.sender.is_empty() || self.recipient.is_empty() {
-            println!("Error: Sender or recipient address is empty.");
-            return false;
+            return Err(BlockchainError::EmptyAddress);
+        }
+        // UTXO 모드 트랜잭션의 입력/출력 구조 확인
+        if self.is_utxo() {
+            if self.outputs.is_empty() || self.outputs.iter().any(|output| output.amount == 0 || output.recipient.is_empty()) {
+                return Err(BlockchainError::InvalidOutputs(
+                    "UTXO transaction must have non-empty outputs with positive amounts".to_string(),
+                ));
+            }
+            let paid_to_recipient = self.outputs.iter()
+                .filter(|output| output.recipient == self.recipient)
+                .try_fold(0u64, |acc, output| acc.checked_add(output.amount))
+                .ok_or(BlockchainError::AmountOverflow)?;
+            if paid_to_recipient != self.amount {
+                return Err(BlockchainError::InvalidOutputs(
+                    "transaction amount does not match outputs paid to recipient".to_string(),
+                ));
+            }
+        } else if !self.outputs.is_empty() {
+            return Err(BlockchainError::InvalidOutputs("transaction has outputs but no inputs".to_string()));
         }
-        // 송신자 주소가 공개 키와 일치하는지 확인
-        if self.sender != self.public_key {
-            println!("Error: Sender address does not match public key.");
-            return false;
+
+        // 모든 주소가 트랜잭션의 네트워크용인지 확인
+        let network = Network::from_chain_id(self.chain_id)
+            .ok_or_else(|| BlockchainError::WrongNetwork { address: self.sender.clone(), chain_id: self.chain_id })?;
+        let sender = Address::parse_for_network(&self.sender, network)?;
+        Address::parse_for_network(&self.recipient, network)?;
+        for output in self.outputs.iter() {
+            Address::parse_for_network(&output.recipient, network)?;
         }
 
         // 공개 키와 서명을 바이트 배열로 디코딩
-        let public_key_bytes = match decode(&self.public_key) {
-            Ok(bytes) => bytes,
-            Err(_) => {
-                println!("Error: Invalid public key hex format.");
-                return false;
-            }
-        };
-        let signature_bytes_vec = match decode(&self.signature) {
-            Ok(bytes) => bytes,
-            Err(_) => {
-                println!("Error: Invalid signature hex format.");
-                return false;
-            }
-        };
+        let public_key_bytes = decode(&self.public_key)
+            .map_err(|e| BlockchainError::MalformedKey(format!("invalid public key hex: {}", e)))?;
+        // 송신자 주소가 공개 키의 해시와 일치하는지 확인
+        if !sender.matches_public_key(&public_key_bytes) {
+            return Err(BlockchainError::SenderKeyMismatch);
+        }
+        let signature_bytes_vec = decode(&self.signature)
+            .map_err(|e| BlockchainError::MalformedSignature(format!("invalid signature hex: {}", e)))?;
 
         // VerifyingKey 생성
-        let verifying_key = match VerifyingKey::from_sec1_bytes(&public_key_bytes) {
-            Ok(key) => key,
-            Err(e) => {
-                println!("Error: Could not create verifying key from public key bytes: {:?}", e);
-                return false;
-            }
-        };
+        let verifying_key = VerifyingKey::from_sec1_bytes(&public_key_bytes)
+            .map_err(|e| BlockchainError::MalformedKey(format!("invalid public key bytes: {}", e)))?;
 
         // Signature 생성: `Signature::try_from`은 `&[u8]` 슬라이스를 기대합니다.
         // `signature_bytes_vec`의 길이가 올바른지 먼저 확인하고, 안전하게 `Signature`로 변환합니다.
         if signature_bytes_vec.len() != U64::USIZE { // U64::USIZE는 Unsigned 트레이트에서 가져옴
-            println!("Error: Signature bytes have incorrect length (expected {} bytes). Actual: {}", U64::USIZE, signature_bytes_vec.len());
-            return false;
+            return Err(BlockchainError::MalformedSignature(format!(
+                "expected {} bytes, got {}", U64::USIZE, signature_bytes_vec.len()
+            )));
         }
+        let signature = Signature::try_from(signature_bytes_vec.as_slice())
+            .map_err(|e| BlockchainError::MalformedSignature(e.to_string()))?;
 
-        let signature = match Signature::try_from(signature_bytes_vec.as_slice()) {
-            Ok(sig) => sig,
-            Err(e) => {
-                println!("Error: Could not create signature from bytes: {:?}", e);
-                return false;
-            }
-        };
-
-        // 서명할 원본 데이터의 해시를 다시 계산합니다.
-        let original_signing_data = format!(
-            "{}{}{}{}",
-            self.sender, self.recipient, self.amount, self.timestamp
-        );
-        
-        let message_hash_for_verification = decode(&sha256::digest(original_signing_data))
+        // 서명할 원본 데이터의 해시를 다시 계산합니다. (sha256 16진수 문자열이므로 항상 디코딩됩니다)
+        let message_hash_for_verification = decode(self.calculate_hash_for_signing())
             .expect("Failed to decode message hash hex for verification");
 
         // 서명 검증: `verify_prehash` 사용
-        if verifying_key.verify_prehash(&message_hash_for_verification, &signature).is_ok() {
-            true
-        } else {
-            println!("Error: Invalid signature for transaction.");
-            false
-        }
+        verifying_key
+            .verify_prehash(&message_hash_for_verification, &signature)
+            .map_err(|_| BlockchainError::BadSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::Wallet;
+
+    fn signed_transfer(from: &Wallet, recipient: &str) -> Transaction {
+        let mut tx = Transaction::new(from.address.clone(), recipient.to_string(), 10);
+        tx.chain_id = Network::Regtest.chain_id();
+        tx.sign(&from.to_signing_key().unwrap(), from.public_key_hex.clone());
+        tx
+    }
+
+    #[test]
+    fn signed_transaction_validates_and_detects_tampering() {
+        let (alice, bob) = (Wallet::new_for_network(Network::Regtest), Wallet::new_for_network(Network::Regtest));
+        let tx = signed_transfer(&alice, &bob.address);
+        assert!(tx.validate().is_ok());
+
+        let mut tampered = tx.clone();
+        tampered.amount += 1;
+        assert!(matches!(tampered.validate(), Err(BlockchainError::BadSignature)));
+    }
+
+    #[test]
+    fn uppercase_address_is_rejected() {
+        let (alice, bob) = (Wallet::new_for_network(Network::Regtest), Wallet::new_for_network(Network::Regtest));
+        // 대문자 주소로 보낸 금액이 소문자 주소와 다른 잔액으로 쌓이지 않도록 거부합니다.
+        let tx = signed_transfer(&alice, &bob.address.to_uppercase());
+        assert!(matches!(tx.validate(), Err(BlockchainError::InvalidAddress { .. })));
+
+        let coinbase = Transaction::new_coinbase(bob.address.to_uppercase(), 100, 1);
+        assert!(matches!(coinbase.validate(), Err(BlockchainError::InvalidAddress { .. })));
     }
 }
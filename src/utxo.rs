@@ -0,0 +1,180 @@
+// src/utxo.rs
+use super::block::Block;                                  // block 모듈의 Block 구조체 사용
+use super::transaction::{OutPoint, Transaction, TxOutput}; // transaction 모듈의 UTXO 타입 사용
+use super::error::BlockchainError;                    // error 모듈의 오류 유형 사용
+use std::collections::{HashMap, HashSet};
+
+/// 아직 소비되지 않은 트랜잭션 출력(UTXO)의 집합입니다.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UtxoSet {
+    outputs: HashMap<OutPoint, TxOutput>,
+}
+
+impl UtxoSet {
+    pub fn new() -> Self {
+        UtxoSet { outputs: HashMap::new() }
+    }
+
+    /// 주어진 블록들을 순서대로 적용하여 UTXO 집합을 만듭니다.
+    /// 이미 검증된 체인을 대상으로 하며, 적용할 수 없는 트랜잭션은 건너뜁니다.
+    pub fn from_blocks(blocks: &[Block]) -> Self {
+        let mut utxo_set = UtxoSet::new();
+        for block in blocks.iter() {
+            for tx in block.transactions.iter() {
+                let _ = utxo_set.apply_transaction(tx);
+            }
+        }
+        utxo_set
+    }
+
+    /// 출력 참조에 해당하는 미사용 출력을 반환합니다.
+    pub fn get(&self, outpoint: &OutPoint) -> Option<&TxOutput> {
+        self.outputs.get(outpoint)
+    }
+
+    /// 주소가 소유한 미사용 출력 목록을 반환합니다.
+    pub fn outputs_of(&self, address: &str) -> Vec<(OutPoint, TxOutput)> {
+        self.outputs.iter()
+            .filter(|(_, output)| output.recipient == address)
+            .map(|(outpoint, output)| (outpoint.clone(), output.clone()))
+            .collect()
+    }
+
+    /// 주소가 소유한 미사용 출력 금액의 합을 반환합니다.
+    pub fn balance_of(&self, address: &str) -> u64 {
+        self.outputs.values()
+            .filter(|output| output.recipient == address)
+            .map(|output| output.amount)
+            .fold(0u64, |acc, amount| acc.saturating_add(amount))
+    }
+
+    /// 미사용 출력을 추가합니다. (블록 분리 시 소비했던 출력을 되살릴 때 사용)
+    pub fn insert(&mut self, outpoint: OutPoint, output: TxOutput) {
+        self.outputs.insert(outpoint, output);
+    }
+
+    /// 출력을 소비된 것으로 표시합니다. (집합에서 제거)
+    pub fn spend(&mut self, outpoint: &OutPoint) -> Option<TxOutput> {
+        self.outputs.remove(outpoint)
+    }
+
+    /// 트랜잭션의 입력이 이 집합 기준으로 소비 가능한지 검사합니다.
+    /// - 모든 입력이 존재하는 미사용 출력이어야 합니다.
+    /// - 같은 출력을 두 번 참조할 수 없습니다.
+    /// - 모든 입력은 송신자 소유여야 합니다.
+    /// - 입력 금액의 합은 출력 금액의 합과 수수료를 더한 값과 같아야 합니다. (남는 금액은 거스름돈 출력으로 돌려받습니다)
+    pub fn check_transaction(&self, tx: &Transaction) -> Result<(), BlockchainError> {
+        self.check_transaction_with_spent(tx, &HashSet::new())
+    }
+
+    /// `check_transaction`과 같지만 `spent`에 있는 출력은 이미 소비된 것으로 봅니다.
+    /// (대기열의 다른 트랜잭션이 소비한 출력을 집합을 복제하지 않고 제외할 때 사용)
+    pub fn check_transaction_with_spent(&self, tx: &Transaction, spent: &HashSet<OutPoint>) -> Result<(), BlockchainError> {
+        if tx.is_coinbase() {
+            return Ok(());
+        }
+        if !tx.is_utxo() {
+            return Err(BlockchainError::WrongLedgerMode(
+                "transaction does not reference any inputs".to_string(),
+            ));
+        }
+
+        let mut seen = HashSet::new();
+        let mut input_total: u64 = 0;
+        for input in tx.inputs.iter() {
+            if !seen.insert(input) {
+                return Err(BlockchainError::DuplicateInput(input.clone()));
+            }
+            let output = self.outputs.get(input)
+                .filter(|_| !spent.contains(input))
+                .ok_or_else(|| BlockchainError::UnknownInput(input.clone()))?;
+            if output.recipient != tx.sender {
+                return Err(BlockchainError::InputNotOwned(input.clone()));
+            }
+            input_total = input_total.checked_add(output.amount)
+                .ok_or(BlockchainError::AmountOverflow)?;
+        }
+
+        let output_total = tx.outputs.iter()
+            .try_fold(tx.fee, |acc, output| acc.checked_add(output.amount))
+            .ok_or(BlockchainError::AmountOverflow)?;
+        if output_total != input_total {
+            return Err(BlockchainError::UnbalancedTransaction { inputs: input_total, outputs: output_total });
+        }
+        Ok(())
+    }
+
+    /// 트랜잭션을 적용합니다. 입력을 소비하고 새 출력을 추가합니다.
+    /// 입력 검사에 실패하거나 같은 출력 참조가 이미 존재하면 아무것도 변경하지 않고 오류를 반환합니다.
+    pub fn apply_transaction(&mut self, tx: &Transaction) -> Result<(), BlockchainError> {
+        self.check_transaction(tx)?;
+
+        let txid = tx.txid();
+        let new_outputs = tx.effective_outputs();
+        for index in 0..new_outputs.len() {
+            let outpoint = OutPoint { txid: txid.clone(), index: index as u32 };
+            if self.outputs.contains_key(&outpoint) {
+                return Err(BlockchainError::DuplicateOutput(outpoint));
+            }
+        }
+
+        for input in tx.inputs.iter() {
+            self.outputs.remove(input);
+        }
+        for (index, output) in new_outputs.into_iter().enumerate() {
+            self.outputs.insert(OutPoint { txid: txid.clone(), index: index as u32 }, output);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn funded_set(owner: &str, amount: u64) -> (UtxoSet, OutPoint) {
+        let coinbase = Transaction::new_coinbase(owner.to_string(), amount, 1);
+        let mut utxo_set = UtxoSet::new();
+        utxo_set.apply_transaction(&coinbase).unwrap();
+        (utxo_set, OutPoint { txid: coinbase.txid(), index: 0 })
+    }
+
+    fn spend(sender: &str, inputs: Vec<OutPoint>, outputs: Vec<(&str, u64)>) -> Transaction {
+        let outputs = outputs.into_iter()
+            .map(|(recipient, amount)| TxOutput { recipient: recipient.to_string(), amount })
+            .collect();
+        Transaction::new_utxo(sender.to_string(), "bob".to_string(), inputs, outputs)
+    }
+
+    #[test]
+    fn output_can_only_be_spent_once() {
+        let (mut utxo_set, coin) = funded_set("alice", 100);
+        let first = spend("alice", vec![coin.clone()], vec![("bob", 60), ("alice", 40)]);
+        utxo_set.apply_transaction(&first).unwrap();
+        assert_eq!(utxo_set.balance_of("alice"), 40);
+        assert_eq!(utxo_set.balance_of("bob"), 60);
+
+        let double_spend = spend("alice", vec![coin.clone()], vec![("bob", 100)]);
+        assert!(matches!(utxo_set.apply_transaction(&double_spend), Err(BlockchainError::UnknownInput(input)) if input == coin));
+    }
+
+    #[test]
+    fn rejects_invalid_inputs() {
+        let (utxo_set, coin) = funded_set("alice", 100);
+        let duplicate = spend("alice", vec![coin.clone(), coin.clone()], vec![("bob", 200)]);
+        assert!(matches!(utxo_set.check_transaction(&duplicate), Err(BlockchainError::DuplicateInput(_))));
+        let stolen = spend("mallory", vec![coin.clone()], vec![("mallory", 100)]);
+        assert!(matches!(utxo_set.check_transaction(&stolen), Err(BlockchainError::InputNotOwned(_))));
+        let unbalanced = spend("alice", vec![coin.clone()], vec![("bob", 90)]);
+        assert!(matches!(utxo_set.check_transaction(&unbalanced), Err(BlockchainError::UnbalancedTransaction { .. })));
+    }
+
+    #[test]
+    fn spent_set_excludes_outputs_without_changing_the_set() {
+        let (utxo_set, coin) = funded_set("alice", 100);
+        let tx = spend("alice", vec![coin.clone()], vec![("bob", 100)]);
+        let spent = HashSet::from([coin.clone()]);
+        assert!(matches!(utxo_set.check_transaction_with_spent(&tx, &spent), Err(BlockchainError::UnknownInput(_))));
+        assert!(utxo_set.check_transaction(&tx).is_ok());
+    }
+}
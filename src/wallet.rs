@@ -1,34 +1,59 @@
 // src/wallet.rs
 use k256::ecdsa::{SigningKey, VerifyingKey};
-use k256::elliptic_curve::sec1::ToEncodedPoint;
 use rand_core::OsRng; // 운영체제 기반 난수 생성기
 use hex::{encode, decode}; // 16진수 인코딩/디코딩
 use serde::{Serialize, Deserialize}; // Serialize, Deserialize 트레이트 추가
 use k256::elliptic_curve::SecretKey; // SecretKey를 사용하여 개인 키 바이트를 다룰 수 있습니다.
+use super::error::BlockchainError; // error 모듈의 오류 유형 사용
+use super::address::{Address, Network}; // address 모듈의 주소 유형 사용
 
 #[derive(Debug, Clone, Serialize, Deserialize)] // Serialize, Deserialize 추가
 pub struct Wallet {
     // 개인 키를 직접 저장하는 대신, 16진수 문자열로 저장하여 직렬화 가능하게 합니다.
     pub private_key_hex: String,
-    // 공개 키는 이미 address 필드에 16진수 문자열로 저장되어 있으므로 별도 필드는 필요 없습니다.
-    pub address: String, // 공개 키의 16진수 표현 (블록체인 주소로 사용)
+    // 압축된 SECP256K1 공개 키의 16진수 표현. 트랜잭션의 public_key로 사용됩니다.
+    pub public_key_hex: String,
+    pub address: String, // 공개 키 해시로 만든 블록체인 주소 (address 모듈 참고)
+}
+
+impl Default for Wallet {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Wallet {
-    /// 새로운 지갑을 생성합니다 (새로운 개인 키/공개 키 쌍 생성).
+    /// 메인넷용 새 지갑을 생성합니다 (새로운 개인 키/공개 키 쌍 생성).
     pub fn new() -> Self {
+        Self::new_for_network(Network::Mainnet)
+    }
+
+    /// 주어진 네트워크용 새 지갑을 생성합니다.
+    pub fn new_for_network(network: Network) -> Self {
         let private_key = SigningKey::random(&mut OsRng); // 안전한 난수로 개인 키 생성
-        let public_key = VerifyingKey::from(&private_key); // 개인 키로부터 공개 키 파생
+        Self::from_signing_key(&private_key, network)
+    }
 
-        // 개인 키를 16진수 문자열로 변환하여 저장
-        let private_key_hex = encode(private_key.to_bytes());
+    /// 16진수 개인 키로부터 지갑을 복원합니다. 공개 키와 주소는 개인 키에서 다시 계산합니다.
+    pub fn from_private_key_hex(private_key_hex: &str, network: Network) -> Result<Self, BlockchainError> {
+        let wallet = Wallet {
+            private_key_hex: private_key_hex.to_string(),
+            public_key_hex: String::new(),
+            address: String::new(),
+        };
+        Ok(Self::from_signing_key(&wallet.to_signing_key()?, network))
+    }
 
-        // 공개 키를 압축된 SECP256K1 형식의 16진수 문자열로 변환하여 주소로 사용
-        let address = encode(public_key.to_encoded_point(true).as_bytes());
+    fn from_signing_key(private_key: &SigningKey, network: Network) -> Self {
+        let public_key = VerifyingKey::from(private_key); // 개인 키로부터 공개 키 파생
+        let public_key_bytes = public_key.to_encoded_point(true);
 
         Wallet {
-            private_key_hex,
-            address,
+            // 개인 키와 압축 공개 키를 16진수 문자열로 변환하여 저장
+            private_key_hex: encode(private_key.to_bytes()),
+            public_key_hex: encode(public_key_bytes.as_bytes()),
+            // 공개 키의 해시에 네트워크 접두사와 체크섬을 붙여 주소로 사용
+            address: Address::from_public_key(public_key_bytes.as_bytes(), network).to_string(),
         }
     }
 
@@ -39,21 +64,32 @@ impl Wallet {
 
     /// 저장된 개인 키 16진수 문자열로부터 SigningKey 객체를 생성하여 반환합니다.
     /// 이 함수는 서명 작업 시에 호출됩니다.
-    pub fn to_signing_key(&self) -> Result<SigningKey, hex::FromHexError> {
-        let private_key_bytes = decode(&self.private_key_hex)?;
+    /// 개인 키가 올바른 16진수가 아니거나 유효한 secp256k1 스칼라가 아니면 오류를 반환합니다.
+    pub fn to_signing_key(&self) -> Result<SigningKey, BlockchainError> {
+        let private_key_bytes = decode(&self.private_key_hex)
+            .map_err(|e| BlockchainError::MalformedKey(format!("invalid private key hex: {}", e)))?;
         // SecretKey::from_slice를 사용하여 바이트로부터 SecretKey를 생성합니다.
         // SigningKey는 SecretKey로부터 파생될 수 있습니다.
-        Ok(SigningKey::from(SecretKey::from_slice(&private_key_bytes).expect("Invalid private key bytes")))
+        let secret_key = SecretKey::from_slice(&private_key_bytes)
+            .map_err(|_| BlockchainError::MalformedKey("invalid private key bytes".to_string()))?;
+        Ok(SigningKey::from(secret_key))
     }
 
-    /// 저장된 공개 키(주소) 16진수 문자열로부터 VerifyingKey 객체를 생성하여 반환합니다.
+    /// 저장된 공개 키 16진수 문자열로부터 VerifyingKey 객체를 생성하여 반환합니다.
     /// 이 함수는 서명 검증 작업 시에 호출됩니다.
-    pub fn to_verifying_key(&self) -> Result<VerifyingKey, hex::FromHexError> {
-        let public_key_bytes = decode(&self.address)?; // 주소는 공개 키의 16진수 표현입니다.
-        Ok(VerifyingKey::from_sec1_bytes(&public_key_bytes).expect("Invalid public key bytes"))
+    pub fn to_verifying_key(&self) -> Result<VerifyingKey, BlockchainError> {
+        let public_key_bytes = decode(&self.public_key_hex)
+            .map_err(|e| BlockchainError::MalformedKey(format!("invalid public key hex: {}", e)))?;
+        VerifyingKey::from_sec1_bytes(&public_key_bytes)
+            .map_err(|e| BlockchainError::MalformedKey(format!("invalid public key bytes: {}", e)))
+    }
+
+    /// 지갑의 공개 키(압축 형식의 16진수 표현)를 반환합니다. 트랜잭션 서명 시 함께 보냅니다.
+    pub fn get_public_key_hex(&self) -> &str {
+        &self.public_key_hex
     }
 
-    /// 지갑의 주소(공개 키의 16진수 표현)를 반환합니다.
+    /// 지갑의 주소를 반환합니다.
     pub fn get_address(&self) -> &str {
         &self.address
     }
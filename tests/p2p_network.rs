@@ -0,0 +1,261 @@
+// tests/p2p_network.rs
+// 같은 프로세스 안에서 localhost의 임시 포트로 여러 노드를 띄워 블록 동기화와 전파를 확인합니다.
+use bingry_blockchain_lib::address::Network;
+use bingry_blockchain_lib::blockchain::{Blockchain, ChainSettings};
+use bingry_blockchain_lib::chain_params::ChainParams;
+use bingry_blockchain_lib::p2p::{PeerMessage, PeerNetwork};
+use bingry_blockchain_lib::protocol::{read_frame, write_frame};
+use bingry_blockchain_lib::storage::FileChainStore;
+use bingry_blockchain_lib::transaction::Transaction;
+use bingry_blockchain_lib::wallet::Wallet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::net::TcpStream;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::sync::mpsc;
+
+const WAIT_LIMIT: Duration = Duration::from_secs(20);
+
+struct Node {
+    chain: Arc<Mutex<Blockchain>>,
+    network: Arc<PeerNetwork>,
+    addr: String,
+    dir: PathBuf,
+}
+
+impl Node {
+    // 파일 저장소를 쓰는 regtest 노드를 만듭니다. 네트워크는 `start`로 시작합니다.
+    fn new(name: &str) -> Node {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("bingry-p2p-{}-{}-{}", name, std::process::id(), nanos));
+        let store = FileChainStore::open(dir.join("chain.dat")).unwrap();
+        let settings = ChainSettings { params: ChainParams::regtest(), ..Default::default() };
+        let chain = Arc::new(Mutex::new(Blockchain::open(settings, store).unwrap()));
+        let addr = free_addr();
+        let network = PeerNetwork::new(Arc::clone(&chain), addr.clone());
+        Node { chain, network, addr, dir }
+    }
+
+    // P2P 리스너가 연결을 받을 수 있을 때까지 기다립니다.
+    async fn start(&self, peers: &[&Node]) {
+        let peers = peers.iter().map(|peer| peer.addr.clone()).collect();
+        tokio::spawn(Arc::clone(&self.network).run(peers));
+        let started = Instant::now();
+        while TcpStream::connect(&self.addr).await.is_err() {
+            assert!(started.elapsed() < WAIT_LIMIT, "node {} did not start listening", self.addr);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    fn mine(&self, miner: &Wallet) -> String {
+        let mut chain = self.chain.lock().unwrap();
+        chain.mine_pending_transactions(miner.address.clone()).unwrap();
+        chain.get_latest_block().unwrap().hash.clone()
+    }
+
+    // 블록을 채굴하여 피어들에게 알립니다.
+    fn mine_and_announce(&self, miner: &Wallet) -> String {
+        let hash = self.mine(miner);
+        let block = self.chain.lock().unwrap().get_latest_block().unwrap().clone();
+        self.network.announce_block(&block);
+        hash
+    }
+
+    fn tip(&self) -> String {
+        self.chain.lock().unwrap().get_latest_block().unwrap().hash.clone()
+    }
+
+    fn has_pending(&self, txid: &str) -> bool {
+        self.chain.lock().unwrap().pending_transactions.iter().any(|tx| tx.txid() == txid)
+    }
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+// 운영체제가 골라 준 빈 포트 주소
+fn free_addr() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+async fn wait_until(what: &str, condition: impl Fn() -> bool) {
+    let started = Instant::now();
+    while !condition() {
+        assert!(started.elapsed() < WAIT_LIMIT, "timed out waiting until {}", what);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+fn regtest_wallet() -> Wallet {
+    Wallet::new_for_network(Network::Regtest)
+}
+
+fn signed_transfer(node: &Node, from: &Wallet, to: &Wallet, amount: u64) -> Transaction {
+    let chain = node.chain.lock().unwrap();
+    let mut tx = Transaction::new(from.address.clone(), to.address.clone(), amount);
+    tx.nonce = chain.get_next_nonce(&from.address);
+    tx.chain_id = chain.chain_id;
+    tx.sign(&from.to_signing_key().unwrap(), from.public_key_hex.clone());
+    tx
+}
+
+// 노드에 직접 접속하여 받은 메시지를 모으는 관찰용 피어. 반환한 쓰기 절반을 버리면 연결이 끊깁니다.
+async fn connect_observer(node: &Node) -> (OwnedWriteHalf, mpsc::Receiver<PeerMessage>) {
+    let socket = TcpStream::connect(&node.addr).await.unwrap();
+    let (mut reader, writer) = socket.into_split();
+    let (sender, receiver) = mpsc::channel(1024);
+    tokio::spawn(async move {
+        while let Ok(Some(frame)) = read_frame(&mut reader).await {
+            if sender.send(PeerMessage::decode(&frame).unwrap()).await.is_err() {
+                break;
+            }
+        }
+    });
+    (writer, receiver)
+}
+
+async fn send(writer: &mut OwnedWriteHalf, message: PeerMessage) {
+    write_frame(writer, &message.encode()).await.unwrap();
+}
+
+fn observer_hello(node: &Node) -> PeerMessage {
+    let chain = node.chain.lock().unwrap();
+    PeerMessage::Hello {
+        listen_addr: "observer".to_string(),
+        genesis_hash: chain.chain[0].hash.clone(),
+        chain_id: chain.chain_id,
+        height: 1,
+        total_work: 0,
+    }
+}
+
+#[tokio::test]
+async fn nodes_sync_by_locator_and_relay_along_a_line() {
+    let (alice, bob, miner) = (regtest_wallet(), regtest_wallet(), regtest_wallet());
+    let (a, b, c) = (Node::new("line-a"), Node::new("line-b"), Node::new("line-c"));
+    // A는 네트워크에 연결되기 전에 블록을 먼저 채굴해 둡니다.
+    a.mine(&alice);
+    a.mine(&miner);
+    let premined = a.mine(&miner);
+
+    // A - B - C 일렬 연결: C는 A와 직접 연결되지 않습니다.
+    a.start(&[]).await;
+    b.start(&[&a]).await;
+    c.start(&[&b]).await;
+    wait_until("B and C sync A's blocks", || b.tip() == premined && c.tip() == premined).await;
+    assert_eq!(c.chain.lock().unwrap().chain.len(), 4);
+    let funded = a.chain.lock().unwrap().get_balance_of_address(&alice.address);
+    assert_eq!(c.chain.lock().unwrap().get_balance_of_address(&alice.address), funded);
+
+    // 끝 노드에서 채굴한 블록은 B를 거쳐 A까지 전파됩니다.
+    let relayed = c.mine_and_announce(&miner);
+    wait_until("A receives C's block", || a.tip() == relayed && b.tip() == relayed).await;
+
+    // 트랜잭션도 반대 방향으로 전파됩니다.
+    let tx = signed_transfer(&a, &alice, &bob, 10);
+    a.chain.lock().unwrap().add_transaction(tx.clone()).unwrap();
+    a.network.announce_transaction(&tx);
+    let txid = tx.txid();
+    wait_until("C receives A's transaction", || c.has_pending(&txid) && b.has_pending(&txid)).await;
+
+    // 트랜잭션을 담은 블록이 전파되면 모든 대기열에서 빠집니다.
+    let confirmed = b.mine_and_announce(&miner);
+    wait_until("all nodes confirm the transaction", || {
+        [&a, &b, &c].iter().all(|node| node.tip() == confirmed && !node.has_pending(&txid))
+    }).await;
+    assert_eq!(a.chain.lock().unwrap().get_balance_of_address(&bob.address), 10);
+}
+
+#[tokio::test]
+async fn gossip_is_forwarded_once_around_a_cycle() {
+    let (alice, bob, miner) = (regtest_wallet(), regtest_wallet(), regtest_wallet());
+    let (a, b, c) = (Node::new("cycle-a"), Node::new("cycle-b"), Node::new("cycle-c"));
+    a.mine(&alice);
+    a.start(&[]).await;
+    b.start(&[&a]).await;
+    c.start(&[&a, &b]).await;
+    let synced = a.tip();
+    wait_until("the triangle is connected and synced", || {
+        a.network.peer_count() == 2 && b.network.peer_count() == 2 && c.network.peer_count() == 2
+            && b.tip() == synced && c.tip() == synced
+    }).await;
+
+    // A는 B의 블록과 트랜잭션을 B와 C 양쪽에서 받지만, 관찰 피어에게는 한 번만 전달해야 합니다.
+    let (mut writer, mut observer) = connect_observer(&a).await;
+    send(&mut writer, observer_hello(&a)).await;
+    wait_until("A accepts the observer", || a.network.peer_count() == 3).await;
+    let block_hash = b.mine_and_announce(&miner);
+    let tx = signed_transfer(&b, &alice, &bob, 5);
+    b.chain.lock().unwrap().add_transaction(tx.clone()).unwrap();
+    b.network.announce_transaction(&tx);
+    let txid = tx.txid();
+    wait_until("the block and transaction reach every node", || {
+        [&a, &c].iter().all(|node| node.tip() == block_hash && node.has_pending(&txid))
+    }).await;
+    // 중복 전달이 있다면 도착할 시간을 줍니다.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let (mut blocks, mut transactions) = (0, 0);
+    while let Ok(message) = observer.try_recv() {
+        match message {
+            PeerMessage::NewBlock(block) if block.hash == block_hash => blocks += 1,
+            PeerMessage::NewTransaction(relayed) if relayed.txid() == txid => transactions += 1,
+            _ => {}
+        }
+    }
+    assert_eq!(blocks, 1);
+    assert_eq!(transactions, 1);
+    assert_eq!(a.chain.lock().unwrap().chain.len(), 3);
+}
+
+#[tokio::test]
+async fn messages_before_hello_are_dropped() {
+    let (alice, bob, miner) = (regtest_wallet(), regtest_wallet(), regtest_wallet());
+    let a = Node::new("handshake");
+    a.mine(&alice);
+    a.start(&[]).await;
+    let (mut writer, mut observer) = connect_observer(&a).await;
+    assert!(matches!(observer.recv().await, Some(PeerMessage::Hello { .. })));
+
+    // 핸드셰이크 전에는 트랜잭션을 처리하지 않고, 전파 대상에도 넣지 않습니다.
+    let tx = signed_transfer(&a, &alice, &bob, 5);
+    let txid = tx.txid();
+    send(&mut writer, PeerMessage::NewTransaction(tx.clone())).await;
+    let before_hello = a.mine_and_announce(&miner);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!a.has_pending(&txid));
+    assert_eq!(a.network.peer_count(), 0);
+    assert!(observer.try_recv().is_err());
+
+    // 다른 네트워크의 Hello는 연결을 끊습니다.
+    let (mut stranger, mut stranger_messages) = connect_observer(&a).await;
+    let mut foreign = observer_hello(&a);
+    if let PeerMessage::Hello { chain_id, .. } = &mut foreign {
+        *chain_id += 1;
+    }
+    send(&mut stranger, foreign).await;
+    assert!(matches!(stranger_messages.recv().await, Some(PeerMessage::Hello { .. })));
+    let closed = tokio::time::timeout(WAIT_LIMIT, stranger_messages.recv()).await.unwrap();
+    assert!(closed.is_none(), "node kept a peer from another network");
+
+    // Hello를 확인한 뒤에는 피어로 등록되어 메시지를 주고받습니다.
+    send(&mut writer, observer_hello(&a)).await;
+    wait_until("A accepts the observer", || a.network.peer_count() == 1).await;
+    send(&mut writer, PeerMessage::NewTransaction(tx)).await;
+    wait_until("A accepts the transaction", || a.has_pending(&txid)).await;
+    let after_hello = a.mine_and_announce(&miner);
+    let received = loop {
+        match tokio::time::timeout(WAIT_LIMIT, observer.recv()).await.unwrap().unwrap() {
+            PeerMessage::NewBlock(block) => break block.hash,
+            _ => continue,
+        }
+    };
+    assert_eq!(received, after_hello);
+    assert_ne!(received, before_hello);
+    assert_eq!(a.network.peer_count(), 1);
+}